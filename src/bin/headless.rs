@@ -0,0 +1,58 @@
+//! Plays the game without a window, reading inputs in the format described
+//! in `input` or a replay from the file given as the last argument, or from
+//! standard input without one. Inputs play out on a new game seeded with the
+//! number following `--seed`, or 0 without it. Prints the state of the game
+//! afterwards, in the format of a save file.
+
+use ::ggez::{
+    GameError,
+    GameResult
+};
+use ::std::{
+    env,
+    fs,
+    io::{
+        self,
+        Read
+    }
+};
+use ::blackbox::*;
+
+const USAGE: &str = "usage: headless [--seed N] [FILE]";
+
+fn main() -> GameResult {
+    let usage = || GameError::CustomError(USAGE.to_string());
+    let mut seed = 0;
+    let mut path = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" => seed = args.next().and_then(|n| n.parse::<u64>().ok()).ok_or_else(usage)?,
+            _ if path.is_none() => path = Some(arg),
+            _ => return Err(usage())
+        }
+    }
+
+    let src = match path {
+        Some(path) => fs::read_to_string(path)?,
+        None => {
+            let mut src = String::new();
+            io::stdin().read_to_string(&mut src)?;
+            src
+        }
+    };
+    let headless = if src.starts_with(REPLAY_MAGIC) {
+        let replay = Replay::parse(&src, &load_items()?)
+            .map_err(|err| GameError::ResourceLoadError(err.to_string()))?;
+        let mut headless = Headless::from_replay(&replay)?;
+        headless.play(&replay)?;
+        headless
+    } else {
+        let inputs = parse_inputs(&src).map_err(GameError::CustomError)?;
+        let mut headless = Headless::new(seed)?;
+        headless.run(&inputs)?;
+        headless
+    };
+    print!("{}", headless.game.state);
+    Ok(())
+}
@@ -0,0 +1,445 @@
+//! Text format for the keys and gamepad buttons bound to each action, kept
+//! in `BINDINGS_FILE`.
+//!
+//! Each line binds a key or button to an action, as `ACTION key KEY` or
+//! `ACTION button BUTTON`, with actions named by `Action::key` and keys and
+//! buttons as described in `input`. An action can have any number of lines,
+//! or a single `ACTION none` line to leave it unbound, except for those
+//! needed to get around menus. Actions without any lines keep their default
+//! bindings. Blank lines and lines starting with `#` are skipped:
+//!
+//! ```text
+//! forward key UP
+//! forward key W
+//! forward button DPAD_UP
+//! minimap none
+//! ```
+//!
+//! One key or button can be bound to several actions, e.g. DOWN moves down
+//! menus and turns around in the dungeon. The scene on top is given each of
+//! them in turn, until one of them leads to another scene.
+
+use ::ggez::{
+    *,
+    event::Button,
+    input::keyboard::KeyCode
+};
+use ::std::{
+    collections::BTreeMap,
+    fmt::{
+        self,
+        Display,
+        Formatter
+    },
+    fs,
+    io::ErrorKind,
+    path::Path,
+    str::FromStr
+};
+use crate::{
+    *,
+    Action::*
+};
+
+pub const BINDINGS_FILE: &str = "bindings.cfg";
+
+/// Something the player can do, whichever key or button it is bound to.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Action {
+    Up,
+    Down,
+    Confirm,
+    Cancel,
+    MoveForward,
+    MoveBack,
+    StrafeLeft,
+    StrafeRight,
+    TurnLeft,
+    TurnRight,
+    TurnAround,
+    Interact,
+    Climb,
+    OpenGuild,
+    OpenParty,
+    OpenInventory,
+    OpenSpells,
+    OpenAutomap,
+    ToggleMinimap,
+    SaveGame,
+    LoadGame,
+    Swap,
+    OpenTemple,
+    NewCharacter,
+    /// Deletes the last letter of a name.
+    Erase,
+    Reroll,
+    Unequip,
+    Stats,
+    OpenControls,
+    ToggleDebugOverlay
+}
+
+impl Action {
+    pub const ALL: &'static [Action] = &[
+        Up,
+        Down,
+        Confirm,
+        Cancel,
+        MoveForward,
+        MoveBack,
+        StrafeLeft,
+        StrafeRight,
+        TurnLeft,
+        TurnRight,
+        TurnAround,
+        Interact,
+        Climb,
+        OpenGuild,
+        OpenParty,
+        OpenInventory,
+        OpenSpells,
+        OpenAutomap,
+        ToggleMinimap,
+        SaveGame,
+        LoadGame,
+        Swap,
+        OpenTemple,
+        NewCharacter,
+        Erase,
+        Reroll,
+        Unequip,
+        Stats,
+        OpenControls,
+        ToggleDebugOverlay
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Up => "UP",
+            Down => "DOWN",
+            Confirm => "CONFIRM",
+            Cancel => "CANCEL",
+            MoveForward => "FORWARD",
+            MoveBack => "BACK",
+            StrafeLeft => "STRAFE LEFT",
+            StrafeRight => "STRAFE RIGHT",
+            TurnLeft => "TURN LEFT",
+            TurnRight => "TURN RIGHT",
+            TurnAround => "TURN AROUND",
+            Interact => "INTERACT",
+            Climb => "CLIMB",
+            OpenGuild => "GUILD",
+            OpenParty => "PARTY",
+            OpenInventory => "ITEMS",
+            OpenSpells => "SPELLS",
+            OpenAutomap => "MAP",
+            ToggleMinimap => "MINIMAP",
+            SaveGame => "SAVE",
+            LoadGame => "LOAD",
+            Swap => "SWAP",
+            OpenTemple => "TEMPLE",
+            NewCharacter => "NEW CHARACTER",
+            Erase => "ERASE",
+            Reroll => "REROLL",
+            Unequip => "UNEQUIP",
+            Stats => "STATS",
+            OpenControls => "CONTROLS",
+            ToggleDebugOverlay => "DEBUG OVERLAY"
+        }
+    }
+
+    /// The key used in data files.
+    pub fn key(&self) -> &'static str {
+        match self {
+            Up => "up",
+            Down => "down",
+            Confirm => "confirm",
+            Cancel => "cancel",
+            MoveForward => "forward",
+            MoveBack => "back",
+            StrafeLeft => "strafe-left",
+            StrafeRight => "strafe-right",
+            TurnLeft => "turn-left",
+            TurnRight => "turn-right",
+            TurnAround => "turn-around",
+            Interact => "interact",
+            Climb => "climb",
+            OpenGuild => "guild",
+            OpenParty => "party",
+            OpenInventory => "inventory",
+            OpenSpells => "spells",
+            OpenAutomap => "map",
+            ToggleMinimap => "minimap",
+            SaveGame => "save",
+            LoadGame => "load",
+            Swap => "swap",
+            OpenTemple => "temple",
+            NewCharacter => "new-character",
+            Erase => "erase",
+            Reroll => "reroll",
+            Unequip => "unequip",
+            Stats => "stats",
+            OpenControls => "controls",
+            ToggleDebugOverlay => "debug-overlay"
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Action> {
+        Action::ALL.iter().copied().find(|action| action.key() == key)
+    }
+
+    /// Whether the action is needed to get around the menus, so it must
+    /// stay bound to something.
+    pub fn is_required(&self) -> bool {
+        matches!(self, Up | Down | Confirm | Cancel)
+    }
+}
+
+/// A key on the keyboard or a button on a gamepad.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Control {
+    Key(KeyCode),
+    Button(Button)
+}
+
+impl Control {
+    /// The name of the key or button, see `KEY_NAMES` and `BUTTON_NAMES`.
+    pub fn name(&self) -> Option<&'static str> {
+        match self {
+            Control::Key(code) => key_name(*code),
+            Control::Button(button) => button_name(*button)
+        }
+    }
+
+    pub fn is_button(&self) -> bool {
+        matches!(self, Control::Button(_))
+    }
+
+    /// A name short enough for the control panel, with the glyphs of the
+    /// font for RETURN, ESCAPE and BACK.
+    pub fn label(&self) -> String {
+        match self {
+            Control::Key(KeyCode::Return) => "\n".to_string(),
+            Control::Key(KeyCode::Escape) => "\x1b".to_string(),
+            Control::Key(KeyCode::Back) => "\x08".to_string(),
+            Control::Key(KeyCode::Up) => "UP".to_string(),
+            Control::Key(KeyCode::Down) => "DN".to_string(),
+            Control::Key(KeyCode::Left) => "LT".to_string(),
+            Control::Key(KeyCode::Right) => "RT".to_string(),
+            Control::Key(KeyCode::Space) => "SP".to_string(),
+            Control::Key(KeyCode::Tab) => "TB".to_string(),
+            Control::Key(KeyCode::Delete) => "DL".to_string(),
+            Control::Key(_) => self.name().unwrap_or("?").to_string(),
+            Control::Button(button) => button_label(*button).to_string()
+        }
+    }
+}
+
+/// Labels for buttons after the layout of most gamepads.
+fn button_label(button: Button) -> &'static str {
+    match button {
+        Button::South => "A",
+        Button::East => "B",
+        Button::West => "X",
+        Button::North => "Y",
+        Button::C => "C",
+        Button::Z => "Z",
+        Button::LeftTrigger => "L1",
+        Button::LeftTrigger2 => "L2",
+        Button::RightTrigger => "R1",
+        Button::RightTrigger2 => "R2",
+        Button::Select => "SE",
+        Button::Start => "ST",
+        Button::Mode => "MO",
+        Button::LeftThumb => "L3",
+        Button::RightThumb => "R3",
+        Button::DPadUp => "UP",
+        Button::DPadDown => "DN",
+        Button::DPadLeft => "LT",
+        Button::DPadRight => "RT",
+        Button::Unknown => "?"
+    }
+}
+
+impl Display for Control {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let kind = match self {
+            Control::Key(_) => "key",
+            Control::Button(_) => "button"
+        };
+        write!(f, "{} {}", kind, self.name().unwrap_or("?"))
+    }
+}
+
+impl FromStr for Control {
+    type Err = String;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        let (kind, name) = src.split_once(' ').unwrap_or((src, ""));
+        let name = name.trim();
+        match kind {
+            "key" => key_code(name)
+                .map(Control::Key)
+                .ok_or_else(|| format!("unknown key `{}`", name)),
+            "button" => button_code(name)
+                .map(Control::Button)
+                .ok_or_else(|| format!("unknown button `{}`", name)),
+            _ => Err(format!("expected `key` or `button`, found `{}`", kind))
+        }
+    }
+}
+
+/// The keys and buttons bound to each action, in the order they are shown.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Bindings {
+    controls: BTreeMap<Action, Vec<Control>>
+}
+
+const fn key(code: KeyCode) -> Control {
+    Control::Key(code)
+}
+
+const fn button(button: Button) -> Control {
+    Control::Button(button)
+}
+
+pub const DEFAULT_BINDINGS: &[(Action, &[Control])] = &[
+    (Up, &[key(KeyCode::Up), button(Button::DPadUp)]),
+    (Down, &[key(KeyCode::Down), button(Button::DPadDown)]),
+    (Confirm, &[key(KeyCode::Return), button(Button::South)]),
+    (Cancel, &[key(KeyCode::Escape), button(Button::East)]),
+    (MoveForward, &[key(KeyCode::Up), key(KeyCode::W), button(Button::DPadUp)]),
+    (MoveBack, &[key(KeyCode::S)]),
+    (StrafeLeft, &[key(KeyCode::A), button(Button::LeftTrigger)]),
+    (StrafeRight, &[key(KeyCode::D), button(Button::RightTrigger)]),
+    (TurnLeft, &[key(KeyCode::Left), button(Button::DPadLeft)]),
+    (TurnRight, &[key(KeyCode::Right), button(Button::DPadRight)]),
+    (TurnAround, &[key(KeyCode::Down), button(Button::DPadDown)]),
+    (Interact, &[key(KeyCode::E), button(Button::South)]),
+    (Climb, &[key(KeyCode::C), button(Button::RightTrigger2)]),
+    (OpenGuild, &[key(KeyCode::G), button(Button::Start)]),
+    (OpenParty, &[key(KeyCode::P), button(Button::North)]),
+    (OpenInventory, &[key(KeyCode::I), button(Button::West)]),
+    (OpenSpells, &[key(KeyCode::X), button(Button::LeftTrigger2)]),
+    (OpenAutomap, &[key(KeyCode::M), button(Button::Select)]),
+    (ToggleMinimap, &[key(KeyCode::N)]),
+    (SaveGame, &[key(KeyCode::F5)]),
+    (LoadGame, &[key(KeyCode::F9)]),
+    (Swap, &[key(KeyCode::S), button(Button::North)]),
+    (OpenTemple, &[key(KeyCode::T), button(Button::Start)]),
+    (NewCharacter, &[key(KeyCode::N), button(Button::West)]),
+    (Erase, &[key(KeyCode::Back), button(Button::West)]),
+    (Reroll, &[key(KeyCode::R), button(Button::North)]),
+    (Unequip, &[key(KeyCode::U), button(Button::West)]),
+    (Stats, &[key(KeyCode::S), button(Button::North)]),
+    (OpenControls, &[key(KeyCode::F1), button(Button::Select)]),
+    (ToggleDebugOverlay, &[key(KeyCode::F3)])
+];
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Bindings {
+            controls: DEFAULT_BINDINGS.iter()
+                .map(|&(action, controls)| (action, controls.to_vec()))
+                .collect()
+        }
+    }
+}
+
+impl Bindings {
+    pub fn controls(&self, action: Action) -> &[Control] {
+        self.controls.get(&action).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// The actions `control` is bound to, in the order of `Action::ALL`.
+    pub fn actions(&self, control: Control) -> Vec<Action> {
+        Action::ALL.iter()
+            .copied()
+            .filter(|&action| self.controls(action).contains(&control))
+            .collect()
+    }
+
+    pub fn bind(&mut self, action: Action, control: Control) {
+        let controls = self.controls.entry(action).or_default();
+        if !controls.contains(&control) {
+            controls.push(control);
+        }
+    }
+
+    pub fn unbind(&mut self, action: Action) {
+        self.controls.insert(action, Vec::new());
+    }
+
+    /// The label of the first button bound to `action` when playing with a
+    /// `gamepad`, or of the first key otherwise, falling back to any control
+    /// bound to it.
+    pub fn label(&self, action: Action, gamepad: bool) -> Option<String> {
+        let controls = self.controls(action);
+        controls.iter()
+            .find(|control| control.is_button() == gamepad)
+            .or_else(|| controls.first())
+            .map(Control::label)
+    }
+
+    /// Reads bindings in the format described above, starting from the
+    /// defaults. Errors name the offending line.
+    pub fn parse(src: &str) -> Result<Bindings, String> {
+        let mut bindings = Bindings::default();
+        let mut seen = Vec::new();
+        for (i, line) in src.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, control) = line.split_once(' ').unwrap_or((line, ""));
+            let action = Action::from_key(key)
+                .ok_or_else(|| format!("line {}: unknown action `{}`", i + 1, key))?;
+            // the first line of an action replaces its defaults
+            if !seen.contains(&action) {
+                seen.push(action);
+                bindings.unbind(action);
+            }
+            if control.trim() == "none" {
+                if action.is_required() {
+                    return Err(format!("line {}: `{}` must stay bound", i + 1, key));
+                }
+            } else {
+                let control = control.parse()
+                    .map_err(|err| format!("line {}: {}", i + 1, err))?;
+                bindings.bind(action, control);
+            }
+        }
+        Ok(bindings)
+    }
+}
+
+impl Display for Bindings {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for &action in Action::ALL {
+            let controls = self.controls(action);
+            if controls.is_empty() {
+                writeln!(f, "{} none", action.key())?;
+            }
+            for control in controls {
+                writeln!(f, "{} {}", action.key(), control)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reads the bindings in `path`, or the defaults if there is no such file.
+pub fn read_bindings(path: &Path) -> GameResult<Bindings> {
+    match fs::read_to_string(path) {
+        Ok(src) => Bindings::parse(&src).map_err(|err| {
+            GameError::ResourceLoadError(format!("{}: {}", path.display(), err))
+        }),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(Bindings::default()),
+        Err(err) => Err(err.into())
+    }
+}
+
+pub fn write_bindings(path: &Path, bindings: &Bindings) -> GameResult {
+    fs::write(path, bindings.to_string())?;
+    Ok(())
+}
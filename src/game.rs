@@ -1,151 +1,329 @@
 use ::ggez::{
     Context,
+    GameError,
     GameResult,
-    event::EventHandler,
+    graphics::{
+        Canvas,
+        Color,
+        Sampler
+    },
     input::keyboard::KeyInput
 };
+use ::std::{
+    collections::BTreeMap,
+    time::{
+        Duration,
+        SystemTime,
+        UNIX_EPOCH
+    }
+};
 use crate::{
     *,
     model::{
         *,
-        CharacterClass::*,
-        Direction::*,
-        Item::*
+        CharacterClass::*
     },
-    scene::{
-        *,
-        Scene::*
-    }
+    scene::*
 };
 
+/// Everything that goes on in the game, apart from how it is shown. It needs
+/// no `Context` except for drawing, so it can run without a window, see
+/// `Headless`.
 pub struct Game {
-    pub resources: Resources,
+    pub items: ItemCatalog,
+    pub settings: Settings,
     pub state: State,
-    pub scene: Scene,
+    pub scenes: SceneStack,
+    /// Updates run since the game started.
+    pub updates: u64,
+    /// The inputs so far, while recording a replay.
+    pub recording: Option<Replay>,
+    /// Set once a scene asks to quit the game.
+    pub quit: bool,
+    /// Whether the last key or button pressed was on a gamepad, so that the
+    /// control panel shows buttons.
+    pub gamepad: bool
+}
+
+pub const UPDATES_PER_SECOND: u32 = 60;
+/// The time that passes with each update. It is the same for every update,
+/// so that replays play back exactly.
+pub const TIME_STEP: Duration = Duration::from_nanos(1_000_000_000 / UPDATES_PER_SECOND as u64);
+
+/// A seed that differs from one run of the game to the next.
+pub fn time_seed() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH)
+        .map(|time| time.as_nanos() as u64)
+        .unwrap_or_default()
 }
 
 impl Game {
-    pub fn new(ctx: &mut Context) -> GameResult<Self> {
-        let mut inventory = Vec::new();
-        for _ in 0..3 {
-            inventory.push(BronzeSword);
-            inventory.push(IronSword);
-            inventory.push(SteelSword);
-        }
+    pub fn new(items: ItemCatalog, seed: u64) -> GameResult<Self> {
+        let state = Self::initial_state(&items, seed)?;
         Ok(Game {
-            resources: Resources::new(ctx)?,
-            state: State {
-                level: Level::example_level(),
-                pos: (0, 0).into(),
-                dir: South,
-                party: vec![
-                    Character {
-                        name: "TELLURIUS".to_string(),
-                        class: Warrior,
-                        lvl: 1,
-                        hp: 9,
-                        max_hp: 9,
-                        mp: 0,
-                        max_mp: 0,
-                        base_atk: 6,
-                        base_def: 5,
-                        base_matk: 0,
-                        base_mdef: 0,
-                        base_agi: 1,
-                        base_luck: 0,
-                        weapon: Some(IronSword),
-                        shield: None,
-                        armor: None
-                    },
-                    Character {
-                        name: "MERCUTIO".to_string(),
-                        class: Priest,
-                        lvl: 1,
-                        hp: 8,
-                        max_hp: 8,
-                        mp: 4,
-                        max_mp: 4,
-                        base_atk: 4,
-                        base_def: 3,
-                        base_matk: 4,
-                        base_mdef: 3,
-                        base_agi: 2,
-                        base_luck: 0,
-                        weapon: None,
-                        shield: None,
-                        armor: None
-                    },
-                    Character {
-                        name: "LEUTHERIA".to_string(),
-                        class: Magician,
-                        lvl: 1,
-                        hp: 6,
-                        max_hp: 6,
-                        mp: 7,
-                        max_mp: 7,
-                        base_atk: 1,
-                        base_def: 0,
-                        base_matk: 6,
-                        base_mdef: 5,
-                        base_agi: 3,
-                        base_luck: 1,
-                        weapon: None,
-                        shield: None,
-                        armor: None
-                    }
-                ],
-                inventory
-            },
-            scene: Explore(ExploreScene {
-                anim: None,
-                selected: None
-            })
+            items,
+            settings: Settings::default(),
+            state,
+            scenes: SceneStack::new(Box::new(GuildScene {
+                i: 0,
+                swapping: None,
+                message: None
+            })),
+            updates: 0,
+            recording: None,
+            quit: false,
+            gamepad: false
         })
     }
+
+    /// Starts the game over from where `replay` starts, for playing it back.
+    /// It keeps the default bindings and does not write changes to them, so
+    /// that the replay plays the same whatever the player has bound.
+    pub fn from_replay(items: ItemCatalog, replay: &Replay) -> GameResult<Self> {
+        let mut game = Game::new(items, replay.seed)?;
+        game.state = replay.state.clone();
+        Ok(game)
+    }
+
+    pub fn initial_state(items: &ItemCatalog, seed: u64) -> GameResult<State> {
+        let dungeon = load_dungeon(&["entrance", "cellar"])?;
+        let item = |name: &str| items.get(name).ok_or_else(|| {
+            GameError::ResourceLoadError(format!("{}: missing item `{}`", ITEM_FILE, name))
+        });
+        // chests and events hold items by name, so make sure the catalog has
+        // them
+        for level in dungeon.floors.iter() {
+            for feature in level.features.values() {
+                if let Feature::Chest(chest) = feature {
+                    item(&chest.item)?;
+                }
+            }
+            for name in level.events.iter().flat_map(|event| event.items()) {
+                item(name)?;
+            }
+        }
+        let mut items = Vec::new();
+        for _ in 0..3 {
+            items.push(item("BRONZE SWORD")?);
+            items.push(item("IRON SWORD")?);
+            items.push(item("STEEL SWORD")?);
+        }
+        for name in [
+            "GREATSWORD", "STAFF", "BUCKLER", "ROBE", "LEATHER ARMOR",
+            "LEATHER CAP", "LEATHER GLOVES", "SANDALS", "LUCKY CHARM",
+            "POTION", "POTION", "POTION", "MP TONIC", "MP TONIC", "TORCH", "TORCH",
+            "HOLY WATER", "ESCAPE SCROLL", "ANTIDOTE", "ANTIDOTE"
+        ] {
+            items.push(item(name)?);
+        }
+        let mut state = State {
+            floor: 0,
+            pos: dungeon.floors[0].start,
+            dir: dungeon.floors[0].start_dir,
+            dungeon,
+            party: Vec::new(),
+            roster: vec![
+                Character {
+                    name: "TELLURIUS".to_string(),
+                    class: Warrior,
+                    lvl: 1,
+                    xp: 0,
+                    hp: 9,
+                    max_hp: 9,
+                    mp: 0,
+                    max_mp: 0,
+                    base_atk: 6,
+                    base_def: 5,
+                    base_matk: 0,
+                    base_mdef: 0,
+                    base_agi: 1,
+                    base_luck: 0,
+                    weapon: Some(item("IRON SWORD")?),
+                    shield: None,
+                    armor: None,
+                    helmet: None,
+                    gloves: None,
+                    boots: None,
+                    accessory: None,
+                    buffs: Vec::new(),
+                    statuses: Vec::new()
+                },
+                Character {
+                    name: "MERCUTIO".to_string(),
+                    class: Priest,
+                    lvl: 1,
+                    xp: 0,
+                    hp: 8,
+                    max_hp: 8,
+                    mp: 4,
+                    max_mp: 4,
+                    base_atk: 4,
+                    base_def: 3,
+                    base_matk: 4,
+                    base_mdef: 3,
+                    base_agi: 2,
+                    base_luck: 0,
+                    weapon: None,
+                    shield: None,
+                    armor: None,
+                    helmet: None,
+                    gloves: None,
+                    boots: None,
+                    accessory: None,
+                    buffs: Vec::new(),
+                    statuses: Vec::new()
+                },
+                Character {
+                    name: "LEUTHERIA".to_string(),
+                    class: Magician,
+                    lvl: 1,
+                    xp: 0,
+                    hp: 6,
+                    max_hp: 6,
+                    mp: 7,
+                    max_mp: 7,
+                    base_atk: 1,
+                    base_def: 0,
+                    base_matk: 6,
+                    base_mdef: 5,
+                    base_agi: 3,
+                    base_luck: 1,
+                    weapon: None,
+                    shield: None,
+                    armor: None,
+                    helmet: None,
+                    gloves: None,
+                    boots: None,
+                    accessory: None,
+                    buffs: Vec::new(),
+                    statuses: Vec::new()
+                }
+            ],
+            inventory: Vec::new(),
+            rng: Rngs::new(seed),
+            encounter_steps: 0,
+            ward_steps: 0,
+            light_steps: 0,
+            vars: BTreeMap::new()
+        };
+        for item in items {
+            state.add_item(item);
+        }
+        state.change_walls();
+        Ok(state)
+    }
 }
 
-impl EventHandler for Game {
-    fn key_down_event(
-        &mut self,
-        ctx: &mut Context,
-        input: KeyInput,
-        repeated: bool,
-    ) -> GameResult {
-        match &self.scene {
-            Explore(_)
-                => explore::key_down_event(ctx, input, repeated, self),
-            ViewCharacter(_)
-                => view_character::key_down_event(ctx, input, repeated, self),
-            ViewInventory(_)
-                => view_inventory::key_down_event(ctx, input, repeated, self),
-            Default
-                => Ok(())
+impl Game {
+    pub fn key_down(&mut self, input: KeyInput) -> GameResult {
+        match input.keycode {
+            Some(code) => self.press(Control::Key(code)),
+            None => Ok(())
         }
     }
 
-    fn update(&mut self, ctx: &mut Context) -> GameResult {
-        match &self.scene {
-            Explore(_)
-                => explore::update(ctx, self),
-            ViewCharacter(_)
-                => view_character::update(ctx, self),
-            ViewInventory(_)
-                => view_inventory::update(ctx, self),
-            Default
-                => Ok(())
+    /// Does what `control` is bound to, giving the scene on top each action
+    /// in turn until one of them leads to another scene. Scenes that capture
+    /// controls get `control` itself instead.
+    pub fn press(&mut self, control: Control) -> GameResult {
+        // controls without a name can't be bound, so there is no need to
+        // record them
+        if control.name().is_none() {
+            return Ok(());
         }
+        self.gamepad = control.is_button();
+        if self.scenes.top().is_some_and(|scene| scene.captures_controls()) {
+            if let Some(replay) = self.recording.as_mut() {
+                replay.inputs.push((self.updates, Input::from(control)));
+            }
+            self.on_top(|scene, game| scene.control(control, game))?;
+            return Ok(());
+        }
+        for action in self.settings.bindings.actions(control) {
+            if self.action(action)? {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Does `action`, returning whether it led to another scene.
+    pub fn action(&mut self, action: Action) -> GameResult<bool> {
+        if let Some(replay) = self.recording.as_mut() {
+            replay.inputs.push((self.updates, Input::Action(action)));
+        }
+        // the debug overlay can be toggled anywhere
+        if action == Action::ToggleDebugOverlay {
+            self.settings.debug_overlay = !self.settings.debug_overlay;
+            return Ok(false);
+        }
+        self.on_top(|scene, game| scene.action(action, game))
+    }
+
+    pub fn text_input(&mut self, character: char) -> GameResult {
+        if let Some(replay) = self.recording.as_mut() {
+            replay.inputs.push((self.updates, Input::Text(character)));
+        }
+        self.on_top(|scene, game| scene.text_input(character, game))?;
+        Ok(())
+    }
+
+    /// Lets `TIME_STEP` pass.
+    pub fn update(&mut self) -> GameResult {
+        self.on_top(|scene, game| scene.update(TIME_STEP, game))?;
+        self.updates += 1;
+        if let Some(replay) = self.recording.as_mut() {
+            replay.updates = self.updates;
+        }
+        Ok(())
     }
 
-    fn draw(&mut self, ctx: &mut Context) -> GameResult {
-        match &self.scene {
-            Explore(_)
-                => explore::draw(ctx, &*self),
-            ViewCharacter(_)
-                => view_character::draw(ctx, &*self),
-            ViewInventory(_)
-                => view_inventory::draw(ctx, &*self),
-            Default
-                => Ok(())
+    pub fn draw(&self, ctx: &mut Context, resources: &Resources) -> GameResult {
+        // set up canvas
+        let mut canvas = Canvas::from_frame(ctx, Color::BLACK);
+        canvas.set_sampler(Sampler::nearest_clamp());
+
+        for scene in self.scenes.visible() {
+            scene.draw(ctx, &mut canvas, self, resources)?;
+        }
+        if self.settings.debug_overlay {
+            debug_overlay::draw(ctx, &mut canvas, self, resources)?;
+        }
+
+        canvas.finish(ctx)
+    }
+
+    /// Lets the scene on top handle something with `handle`, then carries out
+    /// the transition it asks for, returning whether there was one. The scene
+    /// is off the stack meanwhile, so that it can change the rest of the
+    /// game.
+    fn on_top(
+        &mut self,
+        handle: impl FnOnce(&mut dyn Scene, &mut Game) -> GameResult<Transition>
+    ) -> GameResult<bool> {
+        let Some(mut scene) = self.scenes.pop() else {
+            return Ok(false);
+        };
+        let transition = handle(scene.as_mut(), self);
+        self.scenes.push(scene);
+        let transition = transition?;
+        let changed = !matches!(transition, Transition::None);
+        self.apply(transition)?;
+        Ok(changed)
+    }
+
+    fn apply(&mut self, transition: Transition) -> GameResult {
+        match transition {
+            Transition::None => {},
+            Transition::Push(scene) => self.scenes.push(scene),
+            Transition::Pop(result) => {
+                self.scenes.pop();
+                self.on_top(|scene, game| scene.resume(result, game))?;
+            },
+            Transition::Replace(scene) => self.scenes.replace(scene),
+            Transition::Reset(scene) => self.scenes.reset(scene),
+            Transition::Quit => self.quit = true
         }
+        Ok(())
     }
 }
@@ -0,0 +1,74 @@
+use ::ggez::*;
+use ::std::time::Duration;
+use crate::*;
+
+/// Runs a `Game` without a window, feeding it inputs and letting time pass
+/// in updates of `TIME_STEP`, so that the same inputs always play out the
+/// same way.
+pub struct Headless {
+    pub game: Game
+}
+
+impl Headless {
+    /// Starts a new game seeded with `seed`, with the item catalog from
+    /// `load_items`.
+    pub fn new(seed: u64) -> GameResult<Self> {
+        Ok(Headless {
+            game: Game::new(load_items()?, seed)?
+        })
+    }
+
+    /// Starts the game over from where `replay` starts, see `play`.
+    pub fn from_replay(replay: &Replay) -> GameResult<Self> {
+        Ok(Headless {
+            game: Game::from_replay(load_items()?, replay)?
+        })
+    }
+
+    pub fn input(&mut self, input: Input) -> GameResult {
+        match input {
+            Input::Key(code) => self.game.press(Control::Key(code)),
+            Input::Button(button) => self.game.press(Control::Button(button)),
+            Input::Action(action) => self.game.action(action).map(|_| ()),
+            Input::Text(character) => self.game.text_input(character),
+            Input::Wait(duration) => self.wait(duration)
+        }
+    }
+
+    /// Feeds `inputs` to the game in order, until they run out or a scene
+    /// asks to quit.
+    pub fn run(&mut self, inputs: &[Input]) -> GameResult {
+        for &input in inputs {
+            if self.game.quit {
+                break;
+            }
+            self.input(input)?;
+        }
+        Ok(())
+    }
+
+    /// Lets `duration` pass, rounded up to whole updates.
+    pub fn wait(&mut self, duration: Duration) -> GameResult {
+        let updates = duration.as_nanos().div_ceil(TIME_STEP.as_nanos());
+        for _ in 0..updates {
+            if self.game.quit {
+                break;
+            }
+            self.game.update()?;
+        }
+        Ok(())
+    }
+
+    /// Plays back `replay` to its end, which must be where the game started
+    /// from.
+    pub fn play(&mut self, replay: &Replay) -> GameResult {
+        let mut playback = Playback::new(replay);
+        loop {
+            playback.feed(&mut self.game)?;
+            if playback.is_finished(&self.game) || self.game.quit {
+                return Ok(());
+            }
+            self.game.update()?;
+        }
+    }
+}
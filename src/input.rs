@@ -0,0 +1,155 @@
+//! Inputs written as text, one per line, for driving the game without a
+//! keyboard:
+//!
+//! ```text
+//! key UP
+//! wait 0.25
+//! button SOUTH
+//! action turn-left
+//! text A
+//! ```
+//!
+//! `key KEY` presses the named key and `button BUTTON` the named gamepad
+//! button, doing whatever they are bound to, see `KEY_NAMES` and
+//! `BUTTON_NAMES` for the names. `action ACTION` does the action named by
+//! `Action::key` directly.
+//! `text C` types the single character following the space. `wait SECONDS`
+//! lets the given time pass.
+
+use ::ggez::{
+    event::Button,
+    input::keyboard::KeyCode
+};
+use ::std::{
+    fmt::{
+        self,
+        Display,
+        Formatter
+    },
+    str::FromStr,
+    time::Duration
+};
+use crate::*;
+
+/// Something the player does.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Input {
+    Key(KeyCode),
+    Button(Button),
+    Action(Action),
+    /// Types a character, like entering a name.
+    Text(char),
+    Wait(Duration)
+}
+
+/// The names of the keys the game knows about, as written in files.
+pub const KEY_NAMES: &[(&str, KeyCode)] = &[
+    ("A", KeyCode::A), ("B", KeyCode::B), ("C", KeyCode::C), ("D", KeyCode::D),
+    ("E", KeyCode::E), ("F", KeyCode::F), ("G", KeyCode::G), ("H", KeyCode::H),
+    ("I", KeyCode::I), ("J", KeyCode::J), ("K", KeyCode::K), ("L", KeyCode::L),
+    ("M", KeyCode::M), ("N", KeyCode::N), ("O", KeyCode::O), ("P", KeyCode::P),
+    ("Q", KeyCode::Q), ("R", KeyCode::R), ("S", KeyCode::S), ("T", KeyCode::T),
+    ("U", KeyCode::U), ("V", KeyCode::V), ("W", KeyCode::W), ("X", KeyCode::X),
+    ("Y", KeyCode::Y), ("Z", KeyCode::Z),
+    ("0", KeyCode::Key0), ("1", KeyCode::Key1), ("2", KeyCode::Key2),
+    ("3", KeyCode::Key3), ("4", KeyCode::Key4), ("5", KeyCode::Key5),
+    ("6", KeyCode::Key6), ("7", KeyCode::Key7), ("8", KeyCode::Key8),
+    ("9", KeyCode::Key9),
+    ("F1", KeyCode::F1), ("F2", KeyCode::F2), ("F3", KeyCode::F3),
+    ("F4", KeyCode::F4), ("F5", KeyCode::F5), ("F6", KeyCode::F6),
+    ("F7", KeyCode::F7), ("F8", KeyCode::F8), ("F9", KeyCode::F9),
+    ("F10", KeyCode::F10), ("F11", KeyCode::F11), ("F12", KeyCode::F12),
+    ("UP", KeyCode::Up), ("DOWN", KeyCode::Down),
+    ("LEFT", KeyCode::Left), ("RIGHT", KeyCode::Right),
+    ("RETURN", KeyCode::Return), ("ESCAPE", KeyCode::Escape),
+    ("SPACE", KeyCode::Space), ("BACK", KeyCode::Back),
+    ("TAB", KeyCode::Tab), ("DELETE", KeyCode::Delete)
+];
+
+pub fn key_code(name: &str) -> Option<KeyCode> {
+    KEY_NAMES.iter().find(|(n, _)| *n == name).map(|&(_, code)| code)
+}
+
+pub fn key_name(code: KeyCode) -> Option<&'static str> {
+    KEY_NAMES.iter().find(|(_, c)| *c == code).map(|&(name, _)| name)
+}
+
+/// The names of the gamepad buttons, as written in files.
+pub const BUTTON_NAMES: &[(&str, Button)] = &[
+    ("SOUTH", Button::South), ("EAST", Button::East),
+    ("NORTH", Button::North), ("WEST", Button::West),
+    ("C", Button::C), ("Z", Button::Z),
+    ("L1", Button::LeftTrigger), ("L2", Button::LeftTrigger2),
+    ("R1", Button::RightTrigger), ("R2", Button::RightTrigger2),
+    ("SELECT", Button::Select), ("START", Button::Start), ("MODE", Button::Mode),
+    ("L3", Button::LeftThumb), ("R3", Button::RightThumb),
+    ("DPAD_UP", Button::DPadUp), ("DPAD_DOWN", Button::DPadDown),
+    ("DPAD_LEFT", Button::DPadLeft), ("DPAD_RIGHT", Button::DPadRight)
+];
+
+pub fn button_code(name: &str) -> Option<Button> {
+    BUTTON_NAMES.iter().find(|(n, _)| *n == name).map(|&(_, button)| button)
+}
+
+pub fn button_name(button: Button) -> Option<&'static str> {
+    BUTTON_NAMES.iter().find(|(_, b)| *b == button).map(|&(name, _)| name)
+}
+
+impl From<Control> for Input {
+    fn from(control: Control) -> Self {
+        match control {
+            Control::Key(code) => Input::Key(code),
+            Control::Button(button) => Input::Button(button)
+        }
+    }
+}
+
+impl Display for Input {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Input::Key(code) => write!(f, "{}", Control::Key(*code)),
+            Input::Button(button) => write!(f, "{}", Control::Button(*button)),
+            Input::Action(action) => write!(f, "action {}", action.key()),
+            Input::Text(character) => write!(f, "text {}", character),
+            Input::Wait(duration) => write!(f, "wait {}", duration.as_secs_f64())
+        }
+    }
+}
+
+impl FromStr for Input {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let (command, arg) = line.split_once(' ').unwrap_or((line, ""));
+        match command {
+            "key" | "button" => line.parse::<Control>().map(Input::from),
+            "action" => Action::from_key(arg.trim())
+                .map(Input::Action)
+                .ok_or_else(|| format!("unknown action `{}`", arg.trim())),
+            "text" => {
+                let mut chars = arg.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(character), None) => Ok(Input::Text(character)),
+                    _ => Err("expected a single character".to_string())
+                }
+            },
+            "wait" => arg.trim().parse::<f64>().ok()
+                .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+                .map(Input::Wait)
+                .ok_or_else(|| format!("expected seconds, found `{}`", arg.trim())),
+            _ => Err(format!("unknown input `{}`", command))
+        }
+    }
+}
+
+/// Reads inputs written one per line, skipping blank lines and comments
+/// starting with `#`. Errors name the offending line.
+pub fn parse_inputs(src: &str) -> Result<Vec<Input>, String> {
+    src.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(i, line)| {
+            line.parse().map_err(|err| format!("line {}: {}", i + 1, err))
+        })
+        .collect()
+}
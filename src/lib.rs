@@ -0,0 +1,31 @@
+use ::ggez::graphics::Color;
+
+mod bindings;
+pub mod drawing;
+mod game;
+mod headless;
+mod input;
+pub mod model;
+mod replay;
+mod resources;
+mod saves;
+pub mod scene;
+mod settings;
+
+pub use bindings::*;
+pub use game::*;
+pub use headless::*;
+pub use input::*;
+pub use replay::*;
+pub use resources::*;
+pub use saves::*;
+pub use settings::*;
+
+pub const TITLE: &str = "blackbox";
+pub const AUTHOR: &str = "Studio Stardust";
+
+pub const WINDOW_WIDTH: f32 = 640.0;
+pub const WINDOW_HEIGHT: f32 = 480.0;
+
+pub const GRAY: Color = Color::new(0.5, 0.5, 0.5, 1.0);
+pub const TRANSPARENT: Color = Color::new(0.0, 0.0, 0.0, 0.0);
@@ -1,34 +1,149 @@
 use ::ggez::{
+    Context,
     ContextBuilder,
+    GameError,
     GameResult,
     conf::{
         FullscreenType::*,
         WindowMode,
         WindowSetup
     },
-    event::run,
-    graphics::Color
+    event::{
+        Button,
+        EventHandler,
+        GamepadId,
+        run
+    },
+    input::keyboard::KeyInput
+};
+use ::std::{
+    env,
+    fs,
+    path::{
+        Path,
+        PathBuf
+    }
 };
+use ::blackbox::*;
+
+/// The game running in a window, along with what it takes to draw it.
+struct Window {
+    game: Game,
+    resources: Resources,
+    /// Where the replay being recorded goes once the game quits.
+    record_path: Option<PathBuf>,
+    /// The replay being played back, which takes the place of the keyboard
+    /// and gamepad until it is over.
+    playback: Option<Playback>
+}
+
+const USAGE: &str = "usage: blackbox [--seed N] [--record FILE] | --replay FILE";
 
-pub mod drawing;
-mod game;
-pub mod model;
-mod resources;
-pub mod scene;
+impl EventHandler for Window {
+    fn key_down_event(
+        &mut self,
+        ctx: &mut Context,
+        input: KeyInput,
+        _repeated: bool,
+    ) -> GameResult {
+        if self.playback.is_none() {
+            self.game.key_down(input)?;
+            quit_if_asked(ctx, &self.game);
+        }
+        Ok(())
+    }
 
-pub use game::*;
-pub use resources::*;
+    fn gamepad_button_down_event(
+        &mut self,
+        ctx: &mut Context,
+        button: Button,
+        _id: GamepadId
+    ) -> GameResult {
+        if self.playback.is_none() {
+            self.game.press(Control::Button(button))?;
+            quit_if_asked(ctx, &self.game);
+        }
+        Ok(())
+    }
 
-pub const TITLE: &str = "blackbox";
-pub const AUTHOR: &str = "Studio Stardust";
+    fn text_input_event(&mut self, ctx: &mut Context, character: char) -> GameResult {
+        if self.playback.is_none() {
+            self.game.text_input(character)?;
+            quit_if_asked(ctx, &self.game);
+        }
+        Ok(())
+    }
 
-pub const WINDOW_WIDTH: f32 = 640.0;
-pub const WINDOW_HEIGHT: f32 = 480.0;
+    fn update(&mut self, ctx: &mut Context) -> GameResult {
+        while ctx.time.check_update_time(UPDATES_PER_SECOND) {
+            if let Some(playback) = self.playback.as_mut() {
+                playback.feed(&mut self.game)?;
+                if playback.is_finished(&self.game) {
+                    self.playback = None;
+                }
+            }
+            self.game.update()?;
+        }
+        quit_if_asked(ctx, &self.game);
+        Ok(())
+    }
 
-pub const GRAY: Color = Color::new(0.5, 0.5, 0.5, 1.0);
-pub const TRANSPARENT: Color = Color::new(0.0, 0.0, 0.0, 0.0);
+    fn draw(&mut self, ctx: &mut Context) -> GameResult {
+        self.game.draw(ctx, &self.resources)
+    }
+
+    fn quit_event(&mut self, _ctx: &mut Context) -> GameResult<bool> {
+        if let (Some(path), Some(replay)) = (&self.record_path, &self.game.recording) {
+            fs::write(path, replay.to_string())?;
+        }
+        Ok(false)
+    }
+}
+
+fn quit_if_asked(ctx: &mut Context, game: &Game) {
+    if game.quit {
+        ctx.request_quit();
+    }
+}
 
 fn main() -> GameResult {
+    let usage = || GameError::CustomError(USAGE.to_string());
+    let mut seed = None;
+    let mut record_path = None;
+    let mut replay_path = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" => {
+                seed = Some(args.next().and_then(|n| n.parse::<u64>().ok()).ok_or_else(usage)?);
+            },
+            "--record" => record_path = Some(PathBuf::from(args.next().ok_or_else(usage)?)),
+            "--replay" => replay_path = Some(args.next().ok_or_else(usage)?),
+            _ => return Err(usage())
+        }
+    }
+
+    let items = load_items()?;
+    let (mut game, playback) = match replay_path {
+        // replays bring their own seed
+        Some(_) if seed.is_some() || record_path.is_some() => return Err(usage()),
+        Some(path) => {
+            let replay = Replay::parse(&fs::read_to_string(&path)?, &items)
+                .map_err(|err| GameError::ResourceLoadError(format!("{}: {}", path, err)))?;
+            let playback = Playback::new(&replay);
+            (Game::from_replay(items, &replay)?, Some(playback))
+        },
+        None => {
+            let mut game = Game::new(items, seed.unwrap_or_else(time_seed))?;
+            game.settings.bindings = read_bindings(Path::new(BINDINGS_FILE))?;
+            game.settings.bindings_path = Some(PathBuf::from(BINDINGS_FILE));
+            (game, None)
+        }
+    };
+    if record_path.is_some() {
+        game.recording = Some(Replay::new(&game.state));
+    }
+
     let (mut ctx, event_loop) = ContextBuilder::new(TITLE, AUTHOR)
         .window_mode(WindowMode {
             width: WINDOW_WIDTH,
@@ -42,6 +157,11 @@ fn main() -> GameResult {
             .. WindowSetup::default()
         })
         .build()?;
-    let game = Game::new(&mut ctx)?;
-    run(ctx, event_loop, game);
+    let window = Window {
+        game,
+        resources: Resources::new(&mut ctx)?,
+        record_path,
+        playback
+    };
+    run(ctx, event_loop, window);
 }
@@ -0,0 +1,106 @@
+use ::std::collections::HashSet;
+use crate::model::{
+    *,
+    Direction::*
+};
+
+/// The parts of a level the party has seen so far.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Automap {
+    /// Indexed by `[x][y]`, like the walls of `Level`.
+    pub cells: Vec<Vec<bool>>,
+    pub horiz_walls: Vec<Vec<bool>>,
+    pub vert_walls: Vec<Vec<bool>>
+}
+
+impl Automap {
+    pub fn new(width: usize, height: usize) -> Self {
+        Automap {
+            cells: vec![vec![false; height]; width],
+            horiz_walls: vec![vec![false; height + 1]; width],
+            vert_walls: vec![vec![false; height]; width + 1]
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.iter()
+            .chain(self.horiz_walls.iter())
+            .chain(self.vert_walls.iter())
+            .all(|column| column.iter().all(|seen| !seen))
+    }
+
+    /// Marks the whole level as seen.
+    pub fn reveal_all(&mut self) {
+        for column in self.cells.iter_mut()
+            .chain(self.horiz_walls.iter_mut())
+            .chain(self.vert_walls.iter_mut())
+        {
+            column.iter_mut().for_each(|seen| *seen = true);
+        }
+    }
+
+    pub fn wall_seen(&self, pos: Position, dir: Direction) -> bool {
+        match dir {
+            East => self.vert_walls[pos.x + 1][pos.y],
+            South => self.horiz_walls[pos.x][pos.y + 1],
+            West => self.vert_walls[pos.x][pos.y],
+            North => self.horiz_walls[pos.x][pos.y]
+        }
+    }
+
+    fn see_wall(&mut self, pos: Position, dir: Direction) {
+        match dir {
+            East => self.vert_walls[pos.x + 1][pos.y] = true,
+            South => self.horiz_walls[pos.x][pos.y + 1] = true,
+            West => self.vert_walls[pos.x][pos.y] = true,
+            North => self.horiz_walls[pos.x][pos.y] = true
+        }
+    }
+}
+
+impl Level {
+    /// Marks the cells within `radius` steps of `pos` in every direction as
+    /// seen, along with all their walls, regardless of walls in between.
+    pub fn reveal_around(&mut self, pos: Position, radius: isize) {
+        for dy in -radius..=radius {
+            for dx in -radius..=radius {
+                let Some(cell) = pos.translate(North, dx, dy, self) else {
+                    continue;
+                };
+                self.automap.cells[cell.x][cell.y] = true;
+                for dir in [East, South, West, North] {
+                    self.automap.see_wall(cell, dir);
+                }
+            }
+        }
+    }
+
+    /// Marks everything the party can see from `pos` looking towards `dir` as
+    /// seen. These are the cells within `max_dist` in the frame of the
+    /// viewport that are not hidden behind walls or closed doors, along with the
+    /// wall faces drawn for them.
+    pub fn reveal(&mut self, pos: Position, dir: Direction, max_dist: isize) {
+        let mut stack = vec![(0isize, 0isize, pos)];
+        let mut visited = HashSet::new();
+        while let Some((x, y, cell)) = stack.pop() {
+            if !visited.insert((x, y)) {
+                continue;
+            }
+            self.automap.cells[cell.x][cell.y] = true;
+            for (face, dx, dy) in [(dir, 0, 1), (dir.left(), -1, 0), (dir.right(), 1, 0)] {
+                self.automap.see_wall(cell, face);
+                // sight only spreads away from the party
+                let (nx, ny) = (x + dx, y + dy);
+                if nx.abs() < x.abs() || nx.abs() > max_dist || ny > max_dist {
+                    continue;
+                }
+                if !self.looks_towards(cell, face).is_see_through() {
+                    continue;
+                }
+                if let Some(next) = cell.move_by(face, 1, self) {
+                    stack.push((nx, ny, next));
+                }
+            }
+        }
+    }
+}
@@ -1,6 +1,5 @@
 use crate::model::{
     *,
-    Item::*,
     ItemSlot::*
 };
 
@@ -9,6 +8,8 @@ pub struct Character {
     pub name: String,
     pub class: CharacterClass,
     pub lvl: u32,
+    /// Total experience points earned so far.
+    pub xp: u32,
     pub hp: u32,
     pub max_hp: u32,
     pub mp: u32,
@@ -21,46 +22,63 @@ pub struct Character {
     pub base_luck: u32,
     pub weapon: Option<Item>,
     pub shield: Option<Item>,
-    pub armor: Option<Item>
+    pub armor: Option<Item>,
+    pub helmet: Option<Item>,
+    pub gloves: Option<Item>,
+    pub boots: Option<Item>,
+    pub accessory: Option<Item>,
+    /// Effects of spells that last until the end of the battle.
+    pub buffs: Vec<Buff>,
+    pub statuses: Vec<StatusEffect>
 }
 
 impl Character {
     pub fn atk(&self) -> u32 {
-        let mut atk = self.base_atk;
-        match self.weapon {
-            Some(BronzeSword) => atk += 3,
-            Some(IronSword) => atk += 5,
-            Some(SteelSword) => atk += 7,
-            _ => {}
-        }
-        atk
+        self.stat(Stat::Atk)
     }
 
     pub fn def(&self) -> u32 {
-        self.base_def
+        self.stat(Stat::Def)
     }
 
     pub fn matk(&self) -> u32 {
-        self.base_matk
+        self.stat(Stat::Matk)
     }
 
     pub fn mdef(&self) -> u32 {
-        self.base_mdef
+        self.stat(Stat::Mdef)
     }
 
     pub fn agi(&self) -> u32 {
-        self.base_agi
+        self.stat(Stat::Agi)
     }
 
     pub fn luck(&self) -> u32 {
-        self.base_luck
+        self.stat(Stat::Luck)
+    }
+
+    pub fn is_alive(&self) -> bool {
+        !self.has(Status::Dead)
+    }
+
+    /// Takes `amount` HP, dying at 0. Being hit wakes the character up.
+    pub fn take_damage(&mut self, amount: u32) {
+        self.hp = self.hp.saturating_sub(amount);
+        match self.hp {
+            0 => self.inflict(Status::Dead, None),
+            _ => self.cure(Status::Sleep)
+        };
     }
 
-    pub fn item(&self, slot: ItemSlot) -> Option<Item> {
+    pub fn item(&self, slot: ItemSlot) -> Option<&Item> {
         match slot {
-            Weapon => self.weapon,
-            Shield => self.shield,
-            Armor => self.armor
+            Weapon => self.weapon.as_ref(),
+            Shield => self.shield.as_ref(),
+            Armor => self.armor.as_ref(),
+            Helmet => self.helmet.as_ref(),
+            Gloves => self.gloves.as_ref(),
+            Boots => self.boots.as_ref(),
+            Accessory => self.accessory.as_ref()
         }
     }
 
@@ -68,7 +86,11 @@ impl Character {
         match slot {
             Weapon => &mut self.weapon,
             Shield => &mut self.shield,
-            Armor => &mut self.armor
+            Armor => &mut self.armor,
+            Helmet => &mut self.helmet,
+            Gloves => &mut self.gloves,
+            Boots => &mut self.boots,
+            Accessory => &mut self.accessory
         }
     }
 }
@@ -1,4 +1,7 @@
-use crate::model::CharacterClass::*;
+use crate::model::{
+    *,
+    CharacterClass::*
+};
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum CharacterClass {
@@ -8,6 +11,12 @@ pub enum CharacterClass {
 }
 
 impl CharacterClass {
+    pub const ALL: &'static [CharacterClass] = &[
+        Warrior,
+        Priest,
+        Magician
+    ];
+
     pub fn name(&self) -> &'static str {
         match self {
             Warrior => "WARRIOR",
@@ -16,6 +25,22 @@ impl CharacterClass {
         }
     }
 
+    pub fn start_base_hp(&self) -> u32 {
+        match self {
+            Warrior => 8,
+            Priest => 6,
+            Magician => 4
+        }
+    }
+
+    pub fn start_base_mp(&self) -> u32 {
+        match self {
+            Warrior => 0,
+            Priest => 3,
+            Magician => 5
+        }
+    }
+
     pub fn start_base_atk(&self) -> u32 {
         match self {
             Warrior => 2,
@@ -47,4 +72,29 @@ impl CharacterClass {
             Magician => 0
         }
     }
+
+    pub fn start_base_agi(&self) -> u32 {
+        match self {
+            Warrior => 1,
+            Priest => 1,
+            Magician => 2
+        }
+    }
+
+    pub fn start_base_luck(&self) -> u32 {
+        match self {
+            Warrior => 0,
+            Priest => 1,
+            Magician => 1
+        }
+    }
+
+    /// Modifiers every character of the class has.
+    pub fn passives(&self) -> &'static [(Stat, Modifier)] {
+        match self {
+            Warrior => &[(Stat::Atk, Modifier::Percent(10))],
+            Priest => &[(Stat::Mdef, Modifier::Add(1))],
+            Magician => &[(Stat::Matk, Modifier::Percent(10))]
+        }
+    }
 }
@@ -0,0 +1,21 @@
+/// Damage dealt by a physical attack. Half of the defender's `def` is
+/// subtracted from the attacker's `atk`, and defending halves the result.
+pub fn physical_damage(atk: u32, def: u32, defending: bool) -> u32 {
+    let damage = atk.saturating_sub(def / 2).max(1);
+    if defending {
+        (damage / 2).max(1)
+    } else {
+        damage
+    }
+}
+
+/// HP restored by a healing spell.
+pub fn healing(matk: u32) -> u32 {
+    2 + matk * 3 / 2
+}
+
+/// Damage dealt by an offensive spell, computed like `physical_damage` but
+/// from `matk`/`mdef` and with the caster's magic counting double.
+pub fn magic_damage(matk: u32, mdef: u32, defending: bool) -> u32 {
+    physical_damage(2 * matk, mdef, defending)
+}
@@ -0,0 +1,46 @@
+use crate::model::*;
+
+impl State {
+    /// Whether the party carries a key for `lock`.
+    pub fn has_key(&self, lock: u32) -> bool {
+        self.inventory.iter().any(|stack| stack.item.key == Some(lock))
+    }
+
+    /// Opens or closes the door in front of the party, or searches the wall
+    /// there for a secret door. Returns what happened, `None` if there is
+    /// nothing in front of the party.
+    pub fn interact(&mut self) -> Option<String> {
+        let (pos, dir) = (self.pos, self.dir);
+        pos.move_by(dir, 1, self.level())?;
+        let unlocked = match self.level().wall_towards(pos, dir) {
+            Wall::Door(Door { open: false, lock: Some(lock) }) => self.has_key(lock),
+            _ => false
+        };
+        let wall = self.level_mut().wall_towards_mut(pos, dir);
+        let text = match wall {
+            Wall::None => return None,
+            Wall::Door(door) if door.open => {
+                door.open = false;
+                "THE DOOR CLOSES."
+            },
+            Wall::Door(door) if door.lock.is_some() && !unlocked => "THE DOOR IS LOCKED.",
+            Wall::Door(door) if door.lock.is_some() => {
+                *door = Door::OPEN;
+                "THE DOOR IS UNLOCKED."
+            },
+            Wall::Door(door) => {
+                door.open = true;
+                "THE DOOR OPENS."
+            },
+            Wall::Secret => {
+                *wall = Wall::Door(Door::CLOSED);
+                "A SECRET DOOR!"
+            },
+            // one-way walls cannot be told apart from solid ones from the
+            // side they block
+            Wall::OneWay(way) if *way == dir => return None,
+            Wall::Some | Wall::OneWay(_) => "NOTHING IS FOUND."
+        };
+        Some(text.to_string())
+    }
+}
@@ -0,0 +1,40 @@
+use crate::model::*;
+
+/// A stack of levels, the first being the topmost floor.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Dungeon {
+    pub floors: Vec<Level>
+}
+
+impl Dungeon {
+    /// Checks that every stair, ladder, pit and teleporter leads to a floor
+    /// that exists and to a position inside that floor.
+    pub fn validate(&self) -> Result<(), String> {
+        for (floor, level) in self.floors.iter().enumerate() {
+            for (pos, feature) in level.features.iter() {
+                let Some((to_floor, to_pos)) = feature.destination(floor) else {
+                    if !feature.changes_floor() {
+                        continue;
+                    }
+                    return Err(format!(
+                        "{} ({}, {}): leads above the top floor",
+                        level.name, pos.x, pos.y
+                    ));
+                };
+                let Some(to_level) = self.floors.get(to_floor) else {
+                    return Err(format!(
+                        "{} ({}, {}): leads below the bottom floor",
+                        level.name, pos.x, pos.y
+                    ));
+                };
+                if !to_level.contains(to_pos.x as isize, to_pos.y as isize) {
+                    return Err(format!(
+                        "{} ({}, {}): leads outside of {}",
+                        level.name, pos.x, pos.y, to_level.name
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
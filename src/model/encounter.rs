@@ -0,0 +1,54 @@
+use crate::model::*;
+
+/// A region of a level with its own random encounters.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Zone {
+    pub name: String,
+    /// Chance in percent of an encounter on each step.
+    pub rate: u32,
+    /// Top-left and bottom-right cell covered by the zone, both inclusive.
+    /// `None` covers the whole level.
+    pub area: Option<(Position, Position)>,
+    pub groups: Vec<EncounterGroup>
+}
+
+/// A group of monsters that can be met in a zone.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EncounterGroup {
+    /// How likely this group is compared to the others in the zone.
+    pub weight: u32,
+    pub monsters: Vec<Monster>
+}
+
+impl Zone {
+    pub fn contains(&self, pos: Position) -> bool {
+        match self.area {
+            Some((from, to)) => (from.x..=to.x).contains(&pos.x)
+                && (from.y..=to.y).contains(&pos.y),
+            None => true
+        }
+    }
+
+    /// Picks one of the groups by weight. Returns `None` if there are none.
+    pub fn roll_party(&self, rng: &mut Rng) -> Option<EnemyParty> {
+        let total = self.groups.iter().map(|group| group.weight).sum::<u32>();
+        if total == 0 {
+            return None;
+        }
+        let mut roll = rng.below(total);
+        for group in self.groups.iter() {
+            if roll < group.weight {
+                return Some(EnemyParty::new(&group.monsters));
+            }
+            roll -= group.weight;
+        }
+        None
+    }
+}
+
+impl Level {
+    /// Returns the zone at `pos`. Zones listed later take precedence.
+    pub fn zone_at(&self, pos: Position) -> Option<&Zone> {
+        self.zones.iter().rev().find(|zone| zone.contains(pos))
+    }
+}
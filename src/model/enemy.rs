@@ -0,0 +1,138 @@
+use crate::model::Monster::*;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Monster {
+    Slime,
+    GiantRat,
+    Kobold,
+    Imp,
+    Basilisk
+}
+
+#[derive(Clone, Debug)]
+pub struct Enemy {
+    pub monster: Monster,
+    pub hp: u32
+}
+
+#[derive(Clone, Debug)]
+pub struct EnemyParty {
+    pub enemies: Vec<Enemy>
+}
+
+impl Monster {
+    pub const ALL: &'static [Monster] = &[
+        Slime,
+        GiantRat,
+        Kobold,
+        Imp,
+        Basilisk
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Slime => "SLIME",
+            GiantRat => "GIANT RAT",
+            Kobold => "KOBOLD",
+            Imp => "IMP",
+            Basilisk => "BASILISK"
+        }
+    }
+
+    pub fn max_hp(&self) -> u32 {
+        match self {
+            Slime => 6,
+            GiantRat => 5,
+            Kobold => 9,
+            Imp => 7,
+            Basilisk => 12
+        }
+    }
+
+    pub fn atk(&self) -> u32 {
+        match self {
+            Slime => 4,
+            GiantRat => 5,
+            Kobold => 6,
+            Imp => 2,
+            Basilisk => 7
+        }
+    }
+
+    pub fn def(&self) -> u32 {
+        match self {
+            Slime => 1,
+            GiantRat => 0,
+            Kobold => 2,
+            Imp => 1,
+            Basilisk => 3
+        }
+    }
+
+    pub fn matk(&self) -> u32 {
+        match self {
+            Slime | GiantRat | Kobold | Basilisk => 0,
+            Imp => 6
+        }
+    }
+
+    pub fn mdef(&self) -> u32 {
+        match self {
+            Slime => 2,
+            GiantRat => 0,
+            Kobold => 1,
+            Imp => 3,
+            Basilisk => 2
+        }
+    }
+
+    /// Experience points for defeating the monster.
+    pub fn xp(&self) -> u32 {
+        match self {
+            Slime => 3,
+            GiantRat => 3,
+            Kobold => 6,
+            Imp => 8,
+            Basilisk => 14
+        }
+    }
+
+    pub fn agi(&self) -> u32 {
+        match self {
+            Slime => 1,
+            GiantRat => 4,
+            Kobold => 2,
+            Imp => 3,
+            Basilisk => 1
+        }
+    }
+}
+
+impl Enemy {
+    pub fn new(monster: Monster) -> Self {
+        Enemy {
+            monster,
+            hp: monster.max_hp()
+        }
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.hp > 0
+    }
+
+    pub fn take_damage(&mut self, amount: u32) {
+        self.hp = self.hp.saturating_sub(amount);
+    }
+}
+
+impl EnemyParty {
+    pub fn new(monsters: &[Monster]) -> Self {
+        EnemyParty {
+            enemies: monsters.iter().cloned().map(Enemy::new).collect()
+        }
+    }
+
+    pub fn is_defeated(&self) -> bool {
+        self.enemies.iter().all(|enemy| !enemy.is_alive())
+    }
+}
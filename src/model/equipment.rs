@@ -0,0 +1,56 @@
+use crate::model::{
+    *,
+    ItemSlot::*
+};
+
+impl Character {
+    /// Everything the character has equipped.
+    pub fn equipment(&self) -> impl Iterator<Item = &Item> {
+        ItemSlot::ALL.iter().filter_map(|slot| self.item(*slot))
+    }
+
+    /// Whether a weapon held in both hands keeps the shield slot empty.
+    pub fn shield_blocked(&self) -> bool {
+        self.weapon.as_ref().is_some_and(|weapon| weapon.hands >= 2)
+    }
+}
+
+impl State {
+    /// Moves item `item_i` from the inventory to `slot` of party member
+    /// `member`, putting back whatever it replaces. A weapon held in both
+    /// hands also puts back the shield.
+    pub fn equip(&mut self, member: usize, slot: ItemSlot, item_i: usize) {
+        if member >= self.party.len() {
+            return;
+        }
+        let Some(item) = self.take_item(item_i) else {
+            return;
+        };
+        let character = &mut self.party[member];
+        let old = character.item_mut(slot).replace(item);
+        let shield = match character.shield_blocked() {
+            true => character.shield.take(),
+            false => None
+        };
+        for item in old.into_iter().chain(shield) {
+            self.add_item(item);
+        }
+    }
+
+    /// Moves what party member `member` has in `slot` to the inventory.
+    pub fn unequip(&mut self, member: usize, slot: ItemSlot) {
+        if let Some(item) = self.party.get_mut(member)
+            .and_then(|character| character.item_mut(slot).take())
+        {
+            self.add_item(item);
+        }
+    }
+}
+
+impl Item {
+    pub fn equippable(&self, by: &Character, slot: ItemSlot) -> bool {
+        self.slot == Some(slot)
+            && (self.classes.is_empty() || self.classes.contains(&by.class))
+            && !(slot == Shield && by.shield_blocked())
+    }
+}
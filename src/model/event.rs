@@ -0,0 +1,200 @@
+use crate::model::*;
+
+/// A script attached to a cell of a level, see `level_file` for how they are
+/// written.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Event {
+    pub pos: Position,
+    pub trigger: Trigger,
+    /// Whether the event is removed from the level after running once.
+    pub once: bool,
+    /// Conditions that must all hold for the event to run.
+    pub conditions: Vec<Condition>,
+    pub commands: Vec<Command>
+}
+
+/// What makes an event run.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Trigger {
+    /// The party steps onto the cell.
+    Step,
+    /// The party interacts with the wall on the given side of the cell.
+    Wall(Direction),
+    /// The party uses the named item while standing on the cell, outside of
+    /// battle.
+    Item(String),
+    /// The party wins a battle fought on the cell.
+    Victory
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Command {
+    /// Shows a line of text.
+    Text(String),
+    /// Puts the named item into the inventory.
+    Give(String),
+    /// Takes the named item out of the inventory, if the party has it.
+    Take(String),
+    /// Sets a story flag.
+    Flag(String),
+    /// Clears a story flag or variable.
+    Unflag(String),
+    /// Sets a story variable.
+    Set(String, Value),
+    /// Adds to a story variable holding a number, which counts as 0 if it
+    /// holds anything else. The sum stops at the largest and smallest
+    /// numbers a variable can hold.
+    Add(String, i64),
+    /// Moves the party to a position on the same floor, facing the given
+    /// direction.
+    Move(Position, Direction),
+    /// Opens the wall on the given side of a cell, turning it into an open
+    /// door if it is not one already.
+    Open(Position, Direction),
+    /// Starts a battle against the given monsters.
+    Battle(Vec<Monster>),
+    /// Asks the party to choose one of the given answers, which is stored in
+    /// the named story variable before the commands after this one run.
+    Choose(String, Vec<String>)
+}
+
+/// A question waiting for the party's answer, along with the commands left
+/// to run once it is given.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Choice {
+    pub var: String,
+    pub options: Vec<String>,
+    pub rest: Vec<Command>
+}
+
+/// What running events led to.
+#[derive(Clone, Debug, Default)]
+pub struct EventOutcome {
+    pub messages: Vec<String>,
+    /// The battle to start, if any. It is up to the scene that ran the
+    /// events to start it.
+    pub battle: Option<EnemyParty>,
+    /// The question that stopped the events, if any. It is up to the scene
+    /// that ran the events to ask it and pass the answer to `State::answer`.
+    pub choice: Option<Choice>
+}
+
+impl Event {
+    /// The names of the items the event gives or takes.
+    pub fn items(&self) -> impl Iterator<Item = &str> {
+        self.commands.iter().filter_map(|command| match command {
+            Command::Give(name) | Command::Take(name) => Some(name.as_str()),
+            _ => None
+        })
+    }
+}
+
+impl State {
+    /// Whether an event with `trigger` waits on the cell the party stands
+    /// on.
+    pub fn has_events(&self, trigger: &Trigger) -> bool {
+        self.level().events.iter().any(|event| {
+            event.pos == self.pos
+                && event.trigger == *trigger
+                && event.conditions.iter().all(|condition| self.holds(condition))
+        })
+    }
+
+    /// Runs every event with `trigger` on the cell the party stands on whose
+    /// conditions hold, in the order they appear in the level, looking items
+    /// up in `items`. Walls waiting for the story to progress change
+    /// afterwards. Returns `None` if no event ran.
+    pub fn run_events(&mut self, trigger: &Trigger, items: &ItemCatalog) -> Option<EventOutcome> {
+        let pos = self.pos;
+        // conditions are checked before any event runs, so that one event
+        // cannot enable another for the same trigger
+        let ready = self.level().events.iter()
+            .map(|event| {
+                event.pos == pos
+                    && event.trigger == *trigger
+                    && event.conditions.iter().all(|condition| self.holds(condition))
+            })
+            .collect::<Vec<_>>();
+        let events = self.level().events.iter().zip(ready.iter())
+            .filter(|&(_, &ready)| ready)
+            .map(|(event, _)| event.clone())
+            .collect::<Vec<_>>();
+        if events.is_empty() {
+            return None;
+        }
+        let mut ready = ready.into_iter();
+        self.level_mut().events.retain(|event| !(ready.next() == Some(true) && event.once));
+        let commands = events.into_iter()
+            .flat_map(|event| event.commands)
+            .collect::<Vec<_>>();
+        Some(self.run_commands(&commands, items))
+    }
+
+    /// Stores the option at index `answer` of `choice` and runs the commands
+    /// that waited for it.
+    pub fn answer(&mut self, choice: Choice, answer: usize, items: &ItemCatalog) -> EventOutcome {
+        if let Some(option) = choice.options.get(answer) {
+            self.set_var(&choice.var, Value::parse(option));
+        }
+        self.run_commands(&choice.rest, items)
+    }
+
+    /// Runs `commands` until the first question, then changes the walls
+    /// waiting for the story to progress.
+    fn run_commands(&mut self, commands: &[Command], items: &ItemCatalog) -> EventOutcome {
+        let mut outcome = EventOutcome::default();
+        for (i, command) in commands.iter().enumerate() {
+            if let Command::Choose(var, options) = command {
+                outcome.choice = Some(Choice {
+                    var: var.clone(),
+                    options: options.clone(),
+                    rest: commands[i + 1..].to_vec()
+                });
+                break;
+            }
+            self.run_command(command, items, &mut outcome);
+        }
+        self.change_walls();
+        outcome
+    }
+
+    fn run_command(&mut self, command: &Command, items: &ItemCatalog, outcome: &mut EventOutcome) {
+        match command {
+            Command::Text(line) => outcome.messages.push(line.clone()),
+            Command::Give(name) => {
+                if let Some(item) = items.get(name) {
+                    outcome.messages.push(format!("RECEIVED {}.", item.name()));
+                    self.add_item(item);
+                }
+            },
+            Command::Take(name) => {
+                if let Some(i) = self.inventory.iter().position(|stack| stack.item.name() == name) {
+                    self.take_item(i);
+                    outcome.messages.push(format!("HANDED OVER {}.", name));
+                }
+            },
+            Command::Flag(flag) => self.set_var(flag, Value::Bool(true)),
+            Command::Unflag(flag) => {
+                self.vars.remove(flag);
+            },
+            Command::Set(var, value) => self.set_var(var, value.clone()),
+            Command::Add(var, n) => {
+                let old = match self.var(var) {
+                    Some(Value::Int(old)) => *old,
+                    _ => 0
+                };
+                self.set_var(var, Value::Int(old.saturating_add(*n)));
+            },
+            Command::Move(pos, dir) => {
+                self.pos = *pos;
+                self.dir = *dir;
+            },
+            Command::Open(pos, dir) => {
+                *self.level_mut().wall_towards_mut(*pos, *dir) = Wall::Door(Door::OPEN);
+            },
+            Command::Battle(monsters) => outcome.battle = Some(EnemyParty::new(monsters)),
+            // handled by `run_commands`
+            Command::Choose(..) => {}
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use crate::model::*;
+
+/// Something occupying a cell of a level.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Feature {
+    /// Taken as soon as the party steps onto them, leading to the given
+    /// position on the adjacent floor.
+    Stairs(Vertical, Position),
+    /// Like stairs, but the party has to choose to climb them.
+    Ladder(Vertical, Position),
+    /// Drops the party to the given position on the floor below, hurting
+    /// everybody a little.
+    Pit(Position),
+    /// Opened as soon as the party steps onto it, and gone afterwards.
+    Chest(Chest),
+    /// Hurts everybody by the given amount of HP whenever the party steps
+    /// onto it.
+    Trap(u32),
+    /// Restores the HP of everybody alive and cures poison.
+    Fountain,
+    /// Moves the party to the given position on the same floor.
+    Teleporter(Position),
+    /// Turns the party to face a random direction.
+    Spinner,
+    /// Spells cast here fizzle.
+    AntiMagic,
+    /// Nothing can be seen here, not even with the `Light` spell.
+    Darkness
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Chest {
+    /// Name of the item in the chest, see `ItemCatalog`.
+    pub item: String,
+    /// Chance in percent that opening the chest springs a trap.
+    pub trap: u32
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Vertical {
+    Up,
+    Down
+}
+
+/// HP that a trapped chest takes from everybody.
+pub const CHEST_TRAP_DAMAGE: u32 = 3;
+
+impl Feature {
+    /// Whether the feature takes the party to another floor.
+    pub fn changes_floor(&self) -> bool {
+        matches!(self, Feature::Stairs(..) | Feature::Ladder(..) | Feature::Pit(_))
+    }
+
+    /// The floor and position this feature leads to, if it leads anywhere.
+    pub fn destination(&self, floor: usize) -> Option<(usize, Position)> {
+        match *self {
+            Feature::Stairs(Vertical::Up, pos) | Feature::Ladder(Vertical::Up, pos)
+                => floor.checked_sub(1).map(|floor| (floor, pos)),
+            Feature::Stairs(Vertical::Down, pos)
+            | Feature::Ladder(Vertical::Down, pos)
+            | Feature::Pit(pos)
+                => Some((floor + 1, pos)),
+            Feature::Teleporter(pos) => Some((floor, pos)),
+            _ => None
+        }
+    }
+}
+
+impl State {
+    /// The feature of the cell the party stands on.
+    pub fn feature(&self) -> Option<&Feature> {
+        self.level().features.get(&self.pos)
+    }
+
+    /// Whether the party cannot see anything where it stands.
+    pub fn in_darkness(&self) -> bool {
+        self.feature() == Some(&Feature::Darkness)
+    }
+
+    /// Whether spells fizzle where the party stands.
+    pub fn magic_blocked(&self) -> bool {
+        self.feature() == Some(&Feature::AntiMagic)
+    }
+
+    /// Hurts everybody alive by `damage` HP, leaving them with at least 1.
+    fn spring_trap(&mut self, damage: u32) -> String {
+        for character in self.party.iter_mut().filter(|c| c.is_alive()) {
+            character.hp = character.hp.saturating_sub(damage).max(1);
+        }
+        format!("EVERYONE TAKES {} DAMAGE.", damage)
+    }
+
+    /// Sets off the feature of the cell the party just stepped onto, taking
+    /// chest contents from `items`. Stairs, ladders and pits are left to the
+    /// explore scene. Returns lines describing what happened.
+    pub fn trigger_feature(&mut self, items: &ItemCatalog) -> Vec<String> {
+        let Some(feature) = self.feature().cloned() else {
+            return Vec::new();
+        };
+        match feature {
+            Feature::Chest(chest) => {
+                let pos = self.pos;
+                self.level_mut().features.remove(&pos);
+                let mut messages = vec!["THE PARTY OPENS A CHEST.".to_string()];
+                if self.rng.loot.chance(chest.trap) {
+                    messages.push("IT WAS TRAPPED!".to_string());
+                    messages.push(self.spring_trap(CHEST_TRAP_DAMAGE));
+                }
+                match items.get(&chest.item) {
+                    Some(item) => {
+                        messages.push(format!("FOUND {}.", item.name()));
+                        self.add_item(item);
+                    },
+                    None => messages.push("IT IS EMPTY.".to_string())
+                }
+                messages
+            },
+            Feature::Trap(damage) => vec![
+                "A TRAP IS SPRUNG!".to_string(),
+                self.spring_trap(damage)
+            ],
+            Feature::Fountain => {
+                for character in self.party.iter_mut().filter(|c| c.is_alive()) {
+                    character.hp = character.max_hp;
+                    character.cure(Status::Poison);
+                }
+                vec![
+                    "THE PARTY DRINKS FROM A".to_string(),
+                    "FOUNTAIN AND IS HEALED.".to_string()
+                ]
+            },
+            // teleporters and spinners work without a word, to confuse
+            // mapmakers
+            Feature::Teleporter(pos) => {
+                self.pos = pos;
+                Vec::new()
+            },
+            Feature::Spinner => {
+                for _ in 0..self.rng.dungeon.below(4) {
+                    self.dir = self.dir.right();
+                }
+                Vec::new()
+            },
+            _ => Vec::new()
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use crate::model::*;
+
+/// Identical items in the inventory. Only items that are used up stack, so
+/// every piece of equipment has a stack of its own.
+#[derive(Clone, Debug)]
+pub struct ItemStack {
+    pub item: Item,
+    pub count: u32
+}
+
+impl Item {
+    /// Whether using the item uses it up.
+    pub fn consumable(&self) -> bool {
+        self.effect.is_some()
+    }
+}
+
+impl State {
+    /// Puts `item` into the inventory, on top of its stack if it has one.
+    pub fn add_item(&mut self, item: Item) {
+        if item.consumable() {
+            if let Some(stack) = self.inventory.iter_mut().find(|stack| stack.item == item) {
+                stack.count += 1;
+                return;
+            }
+        }
+        self.inventory.push(ItemStack { item, count: 1 });
+    }
+
+    /// Takes one item off stack `i` of the inventory, removing the stack once
+    /// it is empty.
+    pub fn take_item(&mut self, i: usize) -> Option<Item> {
+        let stack = self.inventory.get_mut(i)?;
+        let item = stack.item.clone();
+        stack.count -= 1;
+        if stack.count == 0 {
+            self.inventory.remove(i);
+        }
+        Some(item)
+    }
+
+    /// Uses up one item of stack `i` of the inventory on party member
+    /// `target`, which is ignored for items that affect the whole party.
+    /// Items that would have no effect on the target are kept. Returns lines
+    /// describing what happened.
+    pub fn use_item(&mut self, i: usize, target: usize) -> Vec<String> {
+        let Some(effect) = self.inventory.get(i).and_then(|stack| stack.item.effect) else {
+            return Vec::new();
+        };
+        let character = self.party.get(target);
+        let applies = match effect {
+            ItemEffect::Heal(_) | ItemEffect::RestoreMp(_) => {
+                character.is_some_and(Character::is_alive)
+            },
+            ItemEffect::Cure(status) => character.is_some_and(|character| character.has(status)),
+            ItemEffect::Light(_) | ItemEffect::Ward(_) | ItemEffect::Escape => true
+        };
+        if !applies {
+            return vec!["IT HAS NO EFFECT.".to_string()];
+        }
+        self.take_item(i);
+        match effect {
+            ItemEffect::Heal(hp) => {
+                let character = &mut self.party[target];
+                let before = character.hp;
+                character.heal(hp);
+                vec![format!("{} RECOVERS {} HP.", character.name, character.hp - before)]
+            },
+            ItemEffect::RestoreMp(mp) => {
+                let character = &mut self.party[target];
+                let before = character.mp;
+                character.mp = (character.mp + mp).min(character.max_mp);
+                vec![format!("{} RECOVERS {} MP.", character.name, character.mp - before)]
+            },
+            ItemEffect::Cure(status) => {
+                let character = &mut self.party[target];
+                character.cure(status);
+                vec![format!("{} {}", character.name, status.cured_text())]
+            },
+            ItemEffect::Light(steps) => {
+                self.light_steps = self.light_steps.max(steps);
+                vec!["THE WAY AHEAD IS LIT.".to_string()]
+            },
+            ItemEffect::Ward(steps) => {
+                self.ward_encounters(steps);
+                vec!["MONSTERS WILL KEEP AWAY.".to_string()]
+            },
+            ItemEffect::Escape => {
+                self.floor = 0;
+                self.pos = self.level().start;
+                self.dir = self.level().start_dir;
+                vec![
+                    "THE PARTY IS WHISKED".to_string(),
+                    "BACK TO THE ENTRANCE.".to_string()
+                ]
+            }
+        }
+    }
+}
@@ -1,47 +1,175 @@
+use ::std::{
+    ops::Deref,
+    rc::Rc
+};
 use crate::model::{
     *,
-    CharacterClass::*,
-    Item::*,
     ItemSlot::*
 };
 
+/// The most characters that fit on a line of an item's description.
+pub const DESC_WIDTH: usize = 24;
+
+/// An item as defined in the item catalog, see `item_file`.
+#[derive(Clone, Debug)]
+pub struct ItemDef {
+    pub name: String,
+    /// Flavor text shown after the generated part of the description.
+    pub text: Vec<String>,
+    /// Where the item is equipped, `None` if it cannot be.
+    pub slot: Option<ItemSlot>,
+    /// The classes that can equip the item, empty if every class can.
+    pub classes: Vec<CharacterClass>,
+    /// Modifiers to the stats of whoever has the item equipped.
+    pub mods: Vec<(Stat, Modifier)>,
+    /// How many hands it takes to wield a weapon.
+    pub hands: u32,
+    pub price: u32,
+    /// What happens when the item is used, `None` if it cannot be.
+    pub effect: Option<ItemEffect>,
+    /// The locks the item opens, `None` if it is not a key.
+    pub key: Option<u32>
+}
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
-pub enum Item {
-    BronzeSword,
-    IronSword,
-    SteelSword
+pub enum ItemEffect {
+    /// Restores the given amount of HP.
+    Heal(u32),
+    /// Restores the given amount of MP.
+    RestoreMp(u32),
+    /// Lights the way for the given number of steps, like the `Light` spell.
+    Light(u32),
+    /// Keeps monsters away for the given number of steps, like the `Ward`
+    /// spell.
+    Ward(u32),
+    /// Cures the given status.
+    Cure(Status),
+    /// Takes the party back to the dungeon entrance, fleeing any battle.
+    Escape
 }
 
-impl Item {
-    pub fn name(&self) -> &'static str {
+impl ItemEffect {
+    /// Whether the effect needs a party member to be chosen to receive it.
+    pub fn targets_member(&self) -> bool {
+        matches!(self, ItemEffect::Heal(_) | ItemEffect::RestoreMp(_) | ItemEffect::Cure(_))
+    }
+
+    pub fn in_battle(&self) -> bool {
+        !matches!(self, ItemEffect::Light(_) | ItemEffect::Ward(_))
+    }
+
+    pub fn desc(&self) -> String {
         match self {
-            BronzeSword => "BRONZE SWORD",
-            IronSword => "IRON SWORD",
-            SteelSword => "STEEL SWORD"
+            ItemEffect::Heal(hp) => format!("RESTORES {} HP.", hp),
+            ItemEffect::RestoreMp(mp) => format!("RESTORES {} MP.", mp),
+            ItemEffect::Light(steps) => format!("LIGHTS THE WAY FOR {} STEPS.", steps),
+            ItemEffect::Ward(steps) => format!("KEEPS MONSTERS AWAY FOR {} STEPS.", steps),
+            ItemEffect::Cure(Status::Dead) => "BRINGS BACK THE DEAD.".to_string(),
+            ItemEffect::Cure(status) => format!("CURES {}.", status.name()),
+            ItemEffect::Escape => "RETURNS THE PARTY TO THE ENTRANCE.".to_string()
         }
     }
+}
 
-    pub fn equippable(&self, by: &Character, slot: ItemSlot) -> bool {
-        match self {
-            BronzeSword | IronSword | SteelSword
-                => by.class == Warrior && slot == Weapon
+/// An item in the inventory or in a character's hands. Copies of an item
+/// share their definition, so they are cheap to clone.
+#[derive(Clone, Debug)]
+pub struct Item(pub Rc<ItemDef>);
+
+impl Deref for Item {
+    type Target = ItemDef;
+
+    fn deref(&self) -> &ItemDef {
+        &self.0
+    }
+}
+
+/// Item names are unique within the catalog.
+impl PartialEq for Item {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl Eq for Item {}
+
+impl Item {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Describes the item in lines of at most `DESC_WIDTH` characters. All
+    /// but the flavor text is generated from the definition, so it always
+    /// says what the item really does.
+    pub fn desc(&self) -> Vec<String> {
+        let mut reqs = Vec::new();
+        match self.slot {
+            Some(Weapon) => reqs.push(match self.hands {
+                1 => "1 HAND".to_string(),
+                hands => format!("{} HANDS", hands)
+            }),
+            Some(slot) => reqs.push(slot.name().to_string()),
+            None => {}
+        }
+        if !self.classes.is_empty() {
+            let classes = self.classes.iter()
+                .map(|class| class.name())
+                .collect::<Vec<_>>();
+            reqs.push(format!("{} REQ.", classes.join("/")));
+        }
+        let mut desc = Vec::new();
+        if !reqs.is_empty() {
+            desc.extend(list("[", &reqs, "]"));
         }
+        if !self.mods.is_empty() {
+            let mods = self.mods.iter()
+                .map(|(stat, modifier)| format!("{} {}", modifier.text(), stat.name()))
+                .collect::<Vec<_>>();
+            desc.extend(list("GIVES ", &mods, "."));
+        }
+        if let Some(effect) = self.effect {
+            desc.extend(wrap(&[effect.desc()]));
+        }
+        if self.key.is_some() {
+            desc.extend(wrap(&["OPENS A LOCKED DOOR.".to_string()]));
+        }
+        desc.extend(self.text.iter().cloned());
+        desc
     }
+}
 
-    pub fn desc(self) -> &'static [&'static str] {
-        match self {
-            BronzeSword => &[
-                "[1 HAND, WARRIOR REQ.]",
-                "GIVES +3 ATK."
-            ],
-            IronSword => &[
-                "[1 HAND, WARRIOR REQ.]",
-                "GIVES +5 ATK."
-            ],
-            SteelSword => &[
-                "[1 HAND, WARRIOR REQ.]",
-                "GIVES +7 ATK."
-            ]
+/// Lists `items` separated by commas between `open` and `close`, in lines of
+/// at most `DESC_WIDTH` characters.
+fn list(open: &str, items: &[String], close: &str) -> Vec<String> {
+    let last = items.len() - 1;
+    let parts = items.iter().enumerate()
+        .map(|(i, item)| format!(
+            "{}{}{}",
+            if i == 0 { open } else { "" },
+            item,
+            if i == last { close } else { "," }
+        ))
+        .collect::<Vec<_>>();
+    wrap(&parts)
+}
+
+/// Joins `parts` with spaces into lines of at most `DESC_WIDTH` characters,
+/// breaking lines between parts where possible and within them otherwise.
+fn wrap(parts: &[String]) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for word in parts.iter().flat_map(|part| {
+        match part.len() > DESC_WIDTH {
+            true => part.split(' ').map(str::to_string).collect(),
+            false => vec![part.clone()]
+        }
+    }) {
+        match lines.last_mut() {
+            Some(line) if line.len() + 1 + word.len() <= DESC_WIDTH => {
+                line.push(' ');
+                line.push_str(&word);
+            },
+            _ => lines.push(word)
         }
     }
+    lines
 }
@@ -0,0 +1,272 @@
+//! Text format for the item catalog.
+//!
+//! The catalog lists one block per item, starting with `item NAME` and
+//! ending with `end`. Lines starting with `#` are comments.
+//!
+//! ```text
+//! item BRONZE SWORD
+//! slot weapon
+//! hands 1
+//! class WARRIOR
+//! atk +3
+//! price 30
+//! text A PLAIN BLADE.
+//! end
+//! ```
+//!
+//! `slot weapon|shield|armor|helmet|gloves|boots|accessory` makes the item
+//! equipment, and `hands N` sets how many hands a weapon takes, 1 or 2, 1
+//! if left out. A weapon taking 2 hands leaves no room for a shield. `class
+//! CLASS...` restricts the item to the given classes. `atk`, `def`, `matk`,
+//! `mdef`, `agi` and `luck` followed by a signed number like `+3` or a
+//! percentage like `+10%` modify that stat while the item is equipped. `use
+//! heal N` and `use mp N` make the item usable, restoring HP or MP, `use
+//! light N` and `use ward N` light the way or keep monsters away for N steps,
+//! `use cure STATUS` cures a status like `poison` or `stone` and `use escape`
+//! takes the party back to the entrance. Usable items are
+//! used up, so identical ones stack in the inventory. `key N` makes the item
+//! a key that unlocks doors locked with number N in a level file. Every
+//! `text LINE` adds a line of flavor text to the description, which
+//! otherwise is generated from the other directives.
+
+use ::std::{
+    error::Error,
+    fmt::{
+        self,
+        Display,
+        Formatter
+    },
+    rc::Rc,
+    str::FromStr
+};
+use crate::model::*;
+
+/// Every item in the game, by name.
+#[derive(Clone, Debug, Default)]
+pub struct ItemCatalog {
+    items: Vec<Item>
+}
+
+impl ItemCatalog {
+    pub fn get(&self, name: &str) -> Option<Item> {
+        self.items.iter().find(|item| item.name == name).cloned()
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ItemParseError {
+    /// 1-based line of the offending text.
+    pub line: usize,
+    /// 1-based column of the offending text.
+    pub column: usize,
+    pub message: String
+}
+
+impl Display for ItemParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl Error for ItemParseError {}
+
+struct ItemParser<'a> {
+    lines: Vec<&'a str>,
+    line: usize
+}
+
+impl<'a> ItemParser<'a> {
+    fn error(&self, column: usize, message: impl Into<String>) -> ItemParseError {
+        ItemParseError {
+            line: self.line + 1,
+            column,
+            message: message.into()
+        }
+    }
+
+    fn end_column(&self) -> usize {
+        self.lines[self.line].chars().count() + 1
+    }
+
+    fn number<T: FromStr>(&self, word: Option<&(usize, &str)>, what: &str)
+        -> Result<T, ItemParseError>
+    {
+        match word {
+            Some(&(column, text)) => text.parse().map_err(|_| {
+                self.error(column, format!("expected {}, found `{}`", what, text))
+            }),
+            None => Err(self.error(self.end_column(), format!("expected {}", what)))
+        }
+    }
+
+    /// Returns the text following the first word of the current line.
+    fn rest(&self, words: &[(usize, &str)]) -> Result<String, ItemParseError> {
+        let (column, word) = words[0];
+        let rest = self.lines[self.line].chars()
+            .skip(column - 1 + word.chars().count())
+            .collect::<String>()
+            .trim()
+            .to_string();
+        match rest.is_empty() {
+            true => Err(self.error(self.end_column(), "expected text")),
+            false => Ok(rest)
+        }
+    }
+
+    fn expect_end(&self, words: &[(usize, &str)], len: usize)
+        -> Result<(), ItemParseError>
+    {
+        match words.get(len) {
+            Some(&(column, text)) => Err(self.error(
+                column,
+                format!("unexpected `{}`", text)
+            )),
+            None => Ok(())
+        }
+    }
+
+    /// Parses an `item` block, leaving `self.line` on its `end`.
+    fn parse_item(&mut self, name: String) -> Result<ItemDef, ItemParseError> {
+        let start = self.line;
+        let mut item = ItemDef {
+            name,
+            text: Vec::new(),
+            slot: None,
+            classes: Vec::new(),
+            mods: Vec::new(),
+            hands: 1,
+            price: 0,
+            effect: None,
+            key: None
+        };
+        loop {
+            self.line += 1;
+            let Some(text) = self.lines.get(self.line) else {
+                self.line = start;
+                return Err(self.error(1, "`item` without `end`"));
+            };
+            let words = words(text);
+            match words.first() {
+                None => {},
+                Some(&(_, word)) if word.starts_with('#') => {},
+                Some(&(_, "end")) => {
+                    self.expect_end(&words, 1)?;
+                    return Ok(item);
+                },
+                Some(&(_, "text")) => item.text.push(self.rest(&words)?),
+                Some(&(_, "slot")) => {
+                    let Some(&(column, word)) = words.get(1) else {
+                        return Err(self.error(self.end_column(), "expected slot"));
+                    };
+                    let slot = ItemSlot::ALL.iter().copied().find(|slot| slot.key() == word);
+                    item.slot = Some(slot.ok_or_else(|| {
+                        self.error(column, format!("unknown slot `{}`", word))
+                    })?);
+                    self.expect_end(&words, 2)?;
+                },
+                Some(&(_, "hands")) => {
+                    item.hands = self.number(words.get(1), "number of hands")?;
+                    if !(1..=2).contains(&item.hands) {
+                        return Err(self.error(words[1].0, "items take 1 or 2 hands"));
+                    }
+                    self.expect_end(&words, 2)?;
+                },
+                Some(&(_, "key")) => {
+                    item.key = Some(self.number(words.get(1), "key number")?);
+                    self.expect_end(&words, 2)?;
+                },
+                Some(&(_, "price")) => {
+                    item.price = self.number(words.get(1), "price")?;
+                    self.expect_end(&words, 2)?;
+                },
+                Some(&(_, "class")) => {
+                    if words.len() < 2 {
+                        return Err(self.error(self.end_column(), "expected class"));
+                    }
+                    for &(column, word) in &words[1..] {
+                        let class = CharacterClass::ALL.iter().copied()
+                            .find(|class| class.name() == word)
+                            .ok_or_else(|| {
+                                self.error(column, format!("unknown class `{}`", word))
+                            })?;
+                        item.classes.push(class);
+                    }
+                },
+                Some(&(_, "use")) => {
+                    let amount = || self.number(words.get(2), "amount");
+                    let (effect, len) = match words.get(1) {
+                        Some(&(_, "heal")) => (ItemEffect::Heal(amount()?), 3),
+                        Some(&(_, "mp")) => (ItemEffect::RestoreMp(amount()?), 3),
+                        Some(&(_, "light")) => (ItemEffect::Light(amount()?), 3),
+                        Some(&(_, "ward")) => (ItemEffect::Ward(amount()?), 3),
+                        Some(&(_, "cure")) => {
+                            let Some(&(column, word)) = words.get(2) else {
+                                return Err(self.error(self.end_column(), "expected status"));
+                            };
+                            let status = Status::ALL.iter().copied()
+                                .find(|status| status.key() == word)
+                                .ok_or_else(|| {
+                                    self.error(column, format!("unknown status `{}`", word))
+                                })?;
+                            (ItemEffect::Cure(status), 3)
+                        },
+                        Some(&(_, "escape")) => (ItemEffect::Escape, 2),
+                        Some(&(column, word)) => return Err(self.error(
+                            column,
+                            format!("unknown effect `{}`", word)
+                        )),
+                        None => return Err(self.error(self.end_column(), "expected effect"))
+                    };
+                    item.effect = Some(effect);
+                    self.expect_end(&words, len)?;
+                },
+                Some(&(column, word)) => {
+                    let Some(stat) = Stat::ALL.iter().copied().find(|stat| stat.key() == word)
+                    else {
+                        return Err(self.error(column, format!("unknown key `{}`", word)));
+                    };
+                    let modifier = match words.get(1) {
+                        Some(&(column, word)) => Modifier::parse(word).ok_or_else(|| {
+                            self.error(column, format!("expected modifier, found `{}`", word))
+                        })?,
+                        None => return Err(self.error(self.end_column(), "expected modifier"))
+                    };
+                    item.mods.push((stat, modifier));
+                    self.expect_end(&words, 2)?;
+                }
+            }
+        }
+    }
+
+    fn parse(mut self) -> Result<ItemCatalog, ItemParseError> {
+        let mut catalog = ItemCatalog::default();
+        while self.line < self.lines.len() {
+            let words = words(self.lines[self.line]);
+            match words.first() {
+                None => {},
+                Some(&(_, word)) if word.starts_with('#') => {},
+                Some(&(column, "item")) => {
+                    let name = self.rest(&words)?;
+                    if catalog.get(&name).is_some() {
+                        return Err(self.error(column, format!("duplicate item `{}`", name)));
+                    }
+                    let item = self.parse_item(name)?;
+                    catalog.items.push(Item(Rc::new(item)));
+                },
+                Some(&(column, word)) => {
+                    return Err(self.error(column, format!("unknown key `{}`", word)));
+                }
+            }
+            self.line += 1;
+        }
+        Ok(catalog)
+    }
+}
+
+impl FromStr for ItemCatalog {
+    type Err = ItemParseError;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        ItemParser { lines: src.lines().collect(), line: 0 }.parse()
+    }
+}
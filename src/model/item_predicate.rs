@@ -9,11 +9,14 @@ use crate::{
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum ItemPredicate {
     Equippable(usize, ItemSlot),
-    Usable
+    /// Items that can be used while exploring, including those that an
+    /// event on the party's cell is waiting for.
+    Usable,
+    UsableInBattle
 }
 
 impl ItemPredicate {
-    pub fn matches(&self, item: Item, game: &Game) -> bool {
+    pub fn matches(&self, item: &Item, game: &Game) -> bool {
         match self {
             &Equippable(i, slot) => {
                 if let Some(character) = game.state.party.get(i) {
@@ -22,7 +25,9 @@ impl ItemPredicate {
                     false
                 }
             },
-            Usable => false
+            Usable => item.effect.is_some()
+                || game.state.has_events(&Trigger::Item(item.name().to_string())),
+            UsableInBattle => item.effect.is_some_and(|effect| effect.in_battle())
         }
     }
 }
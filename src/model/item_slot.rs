@@ -1,6 +1,49 @@
+use crate::model::ItemSlot::*;
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum ItemSlot {
     Weapon,
     Shield,
-    Armor
+    Armor,
+    Helmet,
+    Gloves,
+    Boots,
+    Accessory
+}
+
+impl ItemSlot {
+    pub const ALL: &'static [ItemSlot] = &[
+        Weapon,
+        Shield,
+        Armor,
+        Helmet,
+        Gloves,
+        Boots,
+        Accessory
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Weapon => "WEAPON",
+            Shield => "SHIELD",
+            Armor => "ARMOR",
+            Helmet => "HELMET",
+            Gloves => "GLOVES",
+            Boots => "BOOTS",
+            Accessory => "ACCESSORY"
+        }
+    }
+
+    /// The key used in data files.
+    pub fn key(&self) -> &'static str {
+        match self {
+            Weapon => "weapon",
+            Shield => "shield",
+            Armor => "armor",
+            Helmet => "helmet",
+            Gloves => "gloves",
+            Boots => "boots",
+            Accessory => "accessory"
+        }
+    }
 }
@@ -1,25 +1,112 @@
+use ::std::collections::BTreeMap;
 use crate::model::{
     *,
     Direction::*
 };
 
-pub const LEVEL_WIDTH: usize = 20;
-pub const LEVEL_HEIGHT: usize = 20;
-
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Level {
-    pub horiz_walls: [[Wall; LEVEL_HEIGHT + 1]; LEVEL_WIDTH],
-    pub vert_walls: [[Wall; LEVEL_HEIGHT]; LEVEL_WIDTH + 1]
+    pub name: String,
+    pub width: usize,
+    pub height: usize,
+    pub edges: Edges,
+    pub start: Position,
+    pub start_dir: Direction,
+    /// Walls on the north side of each cell, indexed by `[x][y]`, with an
+    /// extra row for the south side of the last row of cells.
+    pub horiz_walls: Vec<Vec<Wall>>,
+    /// Walls on the west side of each cell, indexed by `[x][y]`, with an
+    /// extra column for the east side of the last column of cells.
+    pub vert_walls: Vec<Vec<Wall>>,
+    pub features: BTreeMap<Position, Feature>,
+    pub automap: Automap,
+    /// Encounter zones, later ones taking precedence where they overlap.
+    pub zones: Vec<Zone>,
+    /// Scripts attached to cells, in the order they run.
+    pub events: Vec<Event>,
+    /// Walls waiting for a condition to hold before they change.
+    pub wall_changes: Vec<WallChange>,
+    /// Free-form `key value` pairs attached to individual cells.
+    pub meta: BTreeMap<Position, BTreeMap<String, String>>
+}
+
+/// What happens when the party walks off the edge of a level.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Edges {
+    /// The party reappears on the opposite side.
+    Wrap,
+    /// The edge cannot be crossed, as if there was a wall.
+    Hard
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum Wall {
     None,
     Some,
-    Door
+    Door(Door),
+    /// Looks like a solid wall, but turns out to be a closed door once
+    /// searched.
+    Secret,
+    /// Can only be crossed going in the given direction, and looks solid from
+    /// the other side.
+    OneWay(Direction)
+}
+
+/// A wall that turns into `wall` once `condition` holds, like a door that
+/// appears when the story has progressed far enough.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WallChange {
+    pub pos: Position,
+    pub dir: Direction,
+    pub wall: Wall,
+    pub condition: Condition
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Door {
+    pub open: bool,
+    /// The ID of the key that unlocks the door, `None` if it is not locked.
+    pub lock: Option<u32>
+}
+
+impl Door {
+    pub const CLOSED: Door = Door { open: false, lock: None };
+    pub const OPEN: Door = Door { open: true, lock: None };
 }
 
 impl Level {
+    /// Creates a level of the given size without any walls, starting in the
+    /// top left corner.
+    pub fn new(width: usize, height: usize, edges: Edges) -> Self {
+        Level {
+            name: String::new(),
+            width,
+            height,
+            edges,
+            start: (0, 0).into(),
+            start_dir: South,
+            horiz_walls: vec![vec![Wall::None; height + 1]; width],
+            vert_walls: vec![vec![Wall::None; height]; width + 1],
+            features: BTreeMap::new(),
+            automap: Automap::new(width, height),
+            zones: Vec::new(),
+            events: Vec::new(),
+            wall_changes: Vec::new(),
+            meta: BTreeMap::new()
+        }
+    }
+
+    pub fn contains(&self, x: isize, y: isize) -> bool {
+        (0..self.width as isize).contains(&x)
+            && (0..self.height as isize).contains(&y)
+    }
+
+    /// Whether the party can take a step from `pos` towards `dir`.
+    pub fn can_move(&self, pos: Position, dir: Direction) -> bool {
+        self.wall_towards(pos, dir).is_passable(dir)
+            && pos.move_by(dir, 1, self).is_some()
+    }
+
     pub fn wall_towards(&self, pos: Position, dir: Direction) -> Wall {
         match dir {
             East => self.vert_walls[pos.x + 1][pos.y],
@@ -28,35 +115,41 @@ impl Level {
             North => self.horiz_walls[pos.x][pos.y]
         }
     }
+
+    pub fn wall_towards_mut(&mut self, pos: Position, dir: Direction) -> &mut Wall {
+        match dir {
+            East => &mut self.vert_walls[pos.x + 1][pos.y],
+            South => &mut self.horiz_walls[pos.x][pos.y + 1],
+            West => &mut self.vert_walls[pos.x][pos.y],
+            North => &mut self.horiz_walls[pos.x][pos.y]
+        }
+    }
+
+    /// What the wall from `pos` towards `dir` looks like from there, which
+    /// hides secret and one-way walls.
+    pub fn looks_towards(&self, pos: Position, dir: Direction) -> Wall {
+        match self.wall_towards(pos, dir) {
+            Wall::Secret => Wall::Some,
+            Wall::OneWay(way) if way == dir => Wall::None,
+            Wall::OneWay(_) => Wall::Some,
+            wall => wall
+        }
+    }
 }
 
 impl Wall {
-    pub fn is_passable(&self) -> bool {
+    /// Whether the wall can be crossed going in `dir`.
+    pub fn is_passable(&self, dir: Direction) -> bool {
         match self {
-            Wall::None | Wall::Door => true,
-            Wall::Some => false
+            Wall::None => true,
+            Wall::Door(door) => door.open,
+            Wall::Some | Wall::Secret => false,
+            Wall::OneWay(way) => *way == dir
         }
     }
-}
 
-impl Level {
-    pub fn example_level() -> Self {
-        let mut horiz_walls = [[Wall::None; LEVEL_HEIGHT + 1]; LEVEL_WIDTH];
-        for y in [0, LEVEL_HEIGHT] {
-            for x in 0..LEVEL_WIDTH {
-                horiz_walls[x][y] = Wall::Some;
-            }
-        }
-        let mut vert_walls = [[Wall::None; LEVEL_HEIGHT]; LEVEL_WIDTH + 1];
-        for x in [0, LEVEL_WIDTH] {
-            for y in 0..LEVEL_HEIGHT {
-                vert_walls[x][y] = Wall::Some;
-            }
-        }
-        vert_walls[4][4] = Wall::Door;
-        Level {
-            horiz_walls,
-            vert_walls
-        }
+    /// Whether the party can see past the wall.
+    pub fn is_see_through(&self) -> bool {
+        matches!(self, Wall::None | Wall::Door(Door { open: true, .. }))
     }
 }
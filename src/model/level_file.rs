@@ -0,0 +1,1154 @@
+//! Text format for levels.
+//!
+//! A level file consists of directives, one per line, and a map drawn in
+//! ASCII. Lines starting with `#` outside of the map are comments.
+//!
+//! ```text
+//! name ENTRANCE
+//! size 2 2
+//! edges hard
+//! start 0 0 south
+//! map
+//! +-+-+
+//! |.D.|
+//! + +-+
+//! |. .|
+//! +-+-+
+//! stairs 1 0 down 3 3
+//! meta 1 1 note treasure was here
+//! ```
+//!
+//! In the map, `+` marks the corners between cells and `.` the cells
+//! themselves. The characters in between are the walls: a space for no wall,
+//! `-` or `|` for a solid wall, `D` for a closed door, `O` for an open one and
+//! `S` for a secret door, which looks like a solid wall until searched. `^`,
+//! `v`, `<` and `>` are one-way walls that can only be crossed going the way
+//! they point. Trailing spaces on a map row may be omitted.
+//!
+//! `lock X Y DIR KEY` locks the door on the `DIR` side of a cell, so that it
+//! only opens for a key item with the same `KEY`.
+//!
+//! `edges` is either `hard` (the default) or `wrap`, see `Edges`.
+//!
+//! An optional `seen` grid of the same shape as the map records which walls
+//! and cells are shown on the automap, with `x` for seen and a space for
+//! unseen. Saved games use it to keep track of what the party explored.
+//!
+//! Features are placed with one directive each, naming the cell first:
+//! `stairs X Y up|down TO_X TO_Y`, `ladder X Y up|down TO_X TO_Y` and
+//! `pit X Y TO_X TO_Y`. The destination is on the adjacent floor, so it is
+//! only checked once the level is part of a `Dungeon`.
+//!
+//! Other features are set off when the party steps onto them. `chest X Y
+//! TRAP ITEM` holds an item from the item catalog and springs a trap with a
+//! chance of `TRAP` percent when opened. `trap X Y DAMAGE` hurts the party,
+//! `fountain X Y` heals it, `teleport X Y TO_X TO_Y` moves it elsewhere on
+//! the same floor and `spinner X Y` turns it around at random. On cells marked
+//! with `antimagic X Y` spells fizzle, and on those marked with `dark X Y`
+//! nothing can be seen. A cell holds at most one feature.
+//!
+//! Events script what happens on a cell. Each one is a block starting with
+//! `event X Y TRIGGER` and ending with `end`, which runs when the party
+//! steps onto the cell for the trigger `step`, interacts with the wall on the
+//! `DIR` side of it for `wall DIR`, uses an item there for `item NAME` or
+//! wins a battle there for `victory`:
+//!
+//! ```text
+//! event 3 4 wall north
+//! once
+//! text A LEVER CREAKS.
+//! open 3 4 north
+//! give BRONZE KEY
+//! flag lever_pulled
+//! end
+//! ```
+//!
+//! A `once` line removes the event after it ran, and the event only runs
+//! while every `if CONDITION` line holds. The other lines are commands, run
+//! in order: `text LINE` shows a line, `give ITEM` and `take ITEM` put an
+//! item into the inventory or take it out, `flag NAME` and `unflag NAME` set
+//! and clear story flags, `set NAME VALUE` and `add NAME N` set story
+//! variables or add to them, `move X Y DIR` moves the party elsewhere on the
+//! floor, `open X Y DIR` turns a wall into an open door and `battle MONSTER,
+//! MONSTER...` starts a battle, except after a victory. `choose NAME OPTION,
+//! OPTION...` shows the text so far in a message box and asks the party to
+//! pick one of the options, which is stored in the story variable `NAME`
+//! before the commands after it run. Questions are not asked after a
+//! victory either.
+//!
+//! Story variables hold `true` or `false`, a whole number or any other text,
+//! which can be put in double quotes to keep it text, as in `"42"` or `""`.
+//! Flags are variables set to `true`. A condition is either `NAME`,
+//! which holds if the variable is set to something other than `false`, 0 or
+//! empty text, `not NAME`, `NAME = VALUE`, `NAME != VALUE` or a comparison
+//! like `NAME >= N` with `<`, `<=`, `>` or `>=`.
+//!
+//! `wall X Y DIR WALL if CONDITION` changes the wall on the `DIR` side of a
+//! cell to the map character `WALL`, or to no wall for `none`, once the
+//! condition holds, for doors that only appear as the story goes on.
+//!
+//! Random encounters are set up per zone. `zone NAME RATE` declares a zone
+//! covering the whole level, `zone NAME RATE X1 Y1 X2 Y2` one covering the
+//! cells from `(X1, Y1)` to `(X2, Y2)`. `RATE` is the chance of an encounter
+//! per step in percent, and zones declared later take precedence. Each
+//! `encounter ZONE WEIGHT MONSTER, MONSTER...` line adds a group of monsters
+//! to a zone declared before it.
+
+use ::std::{
+    collections::BTreeMap,
+    error::Error,
+    fmt::{
+        self,
+        Display,
+        Formatter
+    },
+    str::FromStr
+};
+use crate::model::{
+    *,
+    Direction::*
+};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LevelParseError {
+    /// 1-based line of the offending text.
+    pub line: usize,
+    /// 1-based column of the offending text.
+    pub column: usize,
+    pub message: String
+}
+
+impl LevelParseError {
+    fn new(line: usize, column: usize, message: impl Into<String>) -> Self {
+        LevelParseError {
+            line,
+            column,
+            message: message.into()
+        }
+    }
+}
+
+impl Display for LevelParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl Error for LevelParseError {}
+
+pub(crate) fn dir_name(dir: Direction) -> &'static str {
+    match dir {
+        East => "east",
+        South => "south",
+        West => "west",
+        North => "north"
+    }
+}
+
+pub(crate) fn parse_dir(word: &str) -> Option<Direction> {
+    match word {
+        "east" => Some(East),
+        "south" => Some(South),
+        "west" => Some(West),
+        "north" => Some(North),
+        _ => None
+    }
+}
+
+fn edges_name(edges: Edges) -> &'static str {
+    match edges {
+        Edges::Wrap => "wrap",
+        Edges::Hard => "hard"
+    }
+}
+
+fn parse_edges(word: &str) -> Option<Edges> {
+    match word {
+        "wrap" => Some(Edges::Wrap),
+        "hard" => Some(Edges::Hard),
+        _ => None
+    }
+}
+
+fn vertical_name(vertical: Vertical) -> &'static str {
+    match vertical {
+        Vertical::Up => "up",
+        Vertical::Down => "down"
+    }
+}
+
+fn parse_vertical(word: &str) -> Option<Vertical> {
+    match word {
+        "up" => Some(Vertical::Up),
+        "down" => Some(Vertical::Down),
+        _ => None
+    }
+}
+
+fn feature_directive(pos: Position, feature: &Feature) -> String {
+    match feature {
+        Feature::Stairs(vertical, to) => format!(
+            "stairs {} {} {} {} {}",
+            pos.x, pos.y, vertical_name(*vertical), to.x, to.y
+        ),
+        Feature::Ladder(vertical, to) => format!(
+            "ladder {} {} {} {} {}",
+            pos.x, pos.y, vertical_name(*vertical), to.x, to.y
+        ),
+        Feature::Pit(to) => format!(
+            "pit {} {} {} {}",
+            pos.x, pos.y, to.x, to.y
+        ),
+        Feature::Chest(chest) => format!(
+            "chest {} {} {} {}",
+            pos.x, pos.y, chest.trap, chest.item
+        ),
+        Feature::Trap(damage) => format!("trap {} {} {}", pos.x, pos.y, damage),
+        Feature::Fountain => format!("fountain {} {}", pos.x, pos.y),
+        Feature::Teleporter(to) => format!(
+            "teleport {} {} {} {}",
+            pos.x, pos.y, to.x, to.y
+        ),
+        Feature::Spinner => format!("spinner {} {}", pos.x, pos.y),
+        Feature::AntiMagic => format!("antimagic {} {}", pos.x, pos.y),
+        Feature::Darkness => format!("dark {} {}", pos.x, pos.y)
+    }
+}
+
+fn trigger_words(trigger: &Trigger) -> String {
+    match trigger {
+        Trigger::Step => "step".to_string(),
+        Trigger::Wall(dir) => format!("wall {}", dir_name(*dir)),
+        Trigger::Item(name) => format!("item {}", name),
+        Trigger::Victory => "victory".to_string()
+    }
+}
+
+fn command_line(command: &Command) -> String {
+    match command {
+        Command::Text(line) => format!("text {}", line),
+        Command::Give(name) => format!("give {}", name),
+        Command::Take(name) => format!("take {}", name),
+        Command::Flag(flag) => format!("flag {}", flag),
+        Command::Unflag(flag) => format!("unflag {}", flag),
+        Command::Set(var, value) => format!("set {} {}", var, value),
+        Command::Add(var, n) => format!("add {} {}", var, n),
+        Command::Move(pos, dir) => format!("move {} {} {}", pos.x, pos.y, dir_name(*dir)),
+        Command::Open(pos, dir) => format!("open {} {} {}", pos.x, pos.y, dir_name(*dir)),
+        Command::Battle(monsters) => {
+            let monsters = monsters.iter()
+                .map(|monster| monster.name())
+                .collect::<Vec<_>>();
+            format!("battle {}", monsters.join(", "))
+        },
+        Command::Choose(var, options) => format!("choose {} {}", var, options.join(", "))
+    }
+}
+
+/// The map character that stands for a wall on the `dir` side of a cell.
+fn side_solid(dir: Direction) -> char {
+    match dir {
+        North | South => '-',
+        East | West => '|'
+    }
+}
+
+fn wall_char(wall: Wall, solid: char) -> char {
+    match wall {
+        Wall::None => ' ',
+        Wall::Some => solid,
+        Wall::Door(door) if door.open => 'O',
+        Wall::Door(_) => 'D',
+        Wall::Secret => 'S',
+        Wall::OneWay(North) => '^',
+        Wall::OneWay(South) => 'v',
+        Wall::OneWay(West) => '<',
+        Wall::OneWay(East) => '>'
+    }
+}
+
+/// Parses a wall of the map, where `solid` is `-` or `|` depending on which
+/// way the wall runs. One-way walls must point across it.
+fn parse_wall(ch: char, solid: char) -> Option<Wall> {
+    let way = match (ch, solid) {
+        ('^', '-') => North,
+        ('v', '-') => South,
+        ('<', '|') => West,
+        ('>', '|') => East,
+        _ => return match ch {
+            ' ' => Some(Wall::None),
+            'D' => Some(Wall::Door(Door::CLOSED)),
+            'O' => Some(Wall::Door(Door::OPEN)),
+            'S' => Some(Wall::Secret),
+            _ if ch == solid => Some(Wall::Some),
+            _ => None
+        }
+    };
+    Some(Wall::OneWay(way))
+}
+
+/// The cell and side that name a wall in directives, preferring the north
+/// and west sides of cells.
+fn wall_side(level: &Level, horiz: bool, x: usize, y: usize) -> (usize, usize, Direction) {
+    match horiz {
+        true if y < level.height => (x, y, North),
+        true => (x, y - 1, South),
+        false if x < level.width => (x, y, West),
+        false => (x - 1, y, East)
+    }
+}
+
+/// Splits a line into words, each paired with its 1-based column.
+pub(crate) fn words(line: &str) -> Vec<(usize, &str)> {
+    let mut words = Vec::new();
+    let mut start = None;
+    for (i, ch) in line.char_indices().chain([(line.len(), ' ')]) {
+        match (start, ch.is_whitespace()) {
+            (None, false) => start = Some(i),
+            (Some(s), true) => {
+                words.push((line[..s].chars().count() + 1, &line[s..i]));
+                start = None;
+            },
+            _ => {}
+        }
+    }
+    words
+}
+
+type Walls = Vec<Vec<Wall>>;
+
+struct LevelParser<'a> {
+    lines: Vec<&'a str>,
+    line: usize,
+    name: Option<String>,
+    size: Option<(usize, usize)>,
+    edges: Option<Edges>,
+    start: Option<(Position, Direction)>,
+    walls: Option<(Walls, Walls)>,
+    features: BTreeMap<Position, Feature>,
+    automap: Option<Automap>,
+    zones: Vec<Zone>,
+    events: Vec<Event>,
+    wall_changes: Vec<WallChange>,
+    meta: BTreeMap<Position, BTreeMap<String, String>>
+}
+
+impl<'a> LevelParser<'a> {
+    fn error(&self, column: usize, message: impl Into<String>) -> LevelParseError {
+        LevelParseError::new(self.line + 1, column, message)
+    }
+
+    fn number(&self, word: Option<&(usize, &str)>, what: &str)
+        -> Result<usize, LevelParseError>
+    {
+        self.parse_word(word, what)
+    }
+
+    /// Like `number`, but for story variables, which can go below 0.
+    fn integer(&self, word: Option<&(usize, &str)>, what: &str)
+        -> Result<i64, LevelParseError>
+    {
+        self.parse_word(word, what)
+    }
+
+    fn parse_word<T: FromStr>(&self, word: Option<&(usize, &str)>, what: &str)
+        -> Result<T, LevelParseError>
+    {
+        match word {
+            Some(&(column, text)) => text.parse().map_err(|_| {
+                self.error(column, format!("expected {}, found `{}`", what, text))
+            }),
+            None => Err(self.error(
+                self.lines[self.line].chars().count() + 1,
+                format!("expected {}", what)
+            ))
+        }
+    }
+
+    fn position(&self, words: &[(usize, &str)]) -> Result<Position, LevelParseError> {
+        let Some((width, height)) = self.size else {
+            return Err(self.error(words[0].0, "`size` must come first"));
+        };
+        let x = self.number(words.get(1), "x coordinate")?;
+        if x >= width {
+            return Err(self.error(words[1].0, "x coordinate outside the level"));
+        }
+        let y = self.number(words.get(2), "y coordinate")?;
+        if y >= height {
+            return Err(self.error(words[2].0, "y coordinate outside the level"));
+        }
+        Ok(Position { x, y })
+    }
+
+    /// Parses the destination of a feature, starting at `words[i]`.
+    fn destination(&self, words: &[(usize, &str)], i: usize)
+        -> Result<Position, LevelParseError>
+    {
+        let x = self.number(words.get(i), "destination x coordinate")?;
+        let y = self.number(words.get(i + 1), "destination y coordinate")?;
+        self.expect_end(words, i + 2)?;
+        Ok(Position { x, y })
+    }
+
+    fn direction(&self, words: &[(usize, &str)], i: usize)
+        -> Result<Direction, LevelParseError>
+    {
+        match words.get(i) {
+            Some(&(column, word)) => parse_dir(word).ok_or_else(|| {
+                self.error(column, format!("unknown direction `{}`", word))
+            }),
+            None => Err(self.error(
+                self.lines[self.line].chars().count() + 1,
+                "expected direction"
+            ))
+        }
+    }
+
+    /// Returns the text following the first `skip` words of the current
+    /// line.
+    fn rest(&self, words: &[(usize, &str)], skip: usize, what: &str)
+        -> Result<String, LevelParseError>
+    {
+        let text = self.lines[self.line];
+        let Some(&(column, _)) = words.get(skip) else {
+            return Err(self.error(text.chars().count() + 1, format!("expected {}", what)));
+        };
+        Ok(text.chars().skip(column - 1).collect::<String>().trim().to_string())
+    }
+
+    /// Parses the condition starting at `words[i]`, which runs to the end of
+    /// the line.
+    fn condition(&self, words: &[(usize, &str)], i: usize)
+        -> Result<Condition, LevelParseError>
+    {
+        let end_column = self.lines[self.line].chars().count() + 1;
+        let (negated, i) = match words.get(i) {
+            Some(&(_, "not")) => (true, i + 1),
+            _ => (false, i)
+        };
+        let Some(&(_, var)) = words.get(i) else {
+            return Err(self.error(end_column, "expected variable"));
+        };
+        let var = var.to_string();
+        let Some(&(column, op)) = words.get(i + 1) else {
+            let test = if negated { Test::IsUnset } else { Test::IsSet };
+            return Ok(Condition { var, test });
+        };
+        if negated {
+            return Err(self.error(column, format!("unexpected `{}`", op)));
+        }
+        let number = || -> Result<i64, LevelParseError> {
+            let n = self.integer(words.get(i + 2), "number")?;
+            self.expect_end(words, i + 3)?;
+            Ok(n)
+        };
+        let test = match op {
+            "=" => Test::Eq(Value::parse(&self.rest(words, i + 2, "value")?)),
+            "!=" => Test::Ne(Value::parse(&self.rest(words, i + 2, "value")?)),
+            "<" => Test::Lt(number()?),
+            "<=" => Test::Le(number()?),
+            ">" => Test::Gt(number()?),
+            ">=" => Test::Ge(number()?),
+            _ => return Err(self.error(column, format!("unknown operator `{}`", op)))
+        };
+        Ok(Condition { var, test })
+    }
+
+    /// Parses an `event` block, leaving `self.line` on its `end`.
+    fn parse_event(&mut self, words: &[(usize, &str)]) -> Result<Event, LevelParseError> {
+        let start = self.line;
+        let pos = self.position(words)?;
+        let trigger = match words.get(3) {
+            Some(&(_, "step")) => Trigger::Step,
+            Some(&(_, "wall")) => Trigger::Wall(self.direction(words, 4)?),
+            Some(&(_, "item")) => Trigger::Item(self.rest(words, 4, "item")?),
+            Some(&(_, "victory")) => Trigger::Victory,
+            Some(&(column, word)) => return Err(self.error(
+                column,
+                format!("unknown trigger `{}`", word)
+            )),
+            None => return Err(self.error(
+                self.lines[self.line].chars().count() + 1,
+                "expected trigger"
+            ))
+        };
+        match trigger {
+            Trigger::Step | Trigger::Victory => self.expect_end(words, 4)?,
+            Trigger::Wall(_) => self.expect_end(words, 5)?,
+            Trigger::Item(_) => {}
+        }
+        let mut event = Event {
+            pos,
+            trigger,
+            once: false,
+            conditions: Vec::new(),
+            commands: Vec::new()
+        };
+        loop {
+            self.line += 1;
+            let Some(text) = self.lines.get(self.line) else {
+                self.line = start;
+                return Err(self.error(1, "`event` without `end`"));
+            };
+            let words = self::words(text);
+            let command = match words.first() {
+                None => continue,
+                Some(&(_, word)) if word.starts_with('#') => continue,
+                Some(&(_, "end")) => {
+                    self.expect_end(&words, 1)?;
+                    return Ok(event);
+                },
+                Some(&(_, "once")) => {
+                    self.expect_end(&words, 1)?;
+                    event.once = true;
+                    continue;
+                },
+                Some(&(_, "if")) => {
+                    event.conditions.push(self.condition(&words, 1)?);
+                    continue;
+                },
+                Some(&(_, "text")) => Command::Text(self.rest(&words, 1, "text")?),
+                Some(&(_, "give")) => Command::Give(self.rest(&words, 1, "item")?),
+                Some(&(_, "take")) => Command::Take(self.rest(&words, 1, "item")?),
+                Some(&(_, kind @ ("flag" | "unflag"))) => {
+                    let Some(&(_, flag)) = words.get(1) else {
+                        return Err(self.error(text.chars().count() + 1, "expected flag"));
+                    };
+                    self.expect_end(&words, 2)?;
+                    match kind {
+                        "flag" => Command::Flag(flag.to_string()),
+                        _ => Command::Unflag(flag.to_string())
+                    }
+                },
+                Some(&(_, "set")) => {
+                    let Some(&(_, var)) = words.get(1) else {
+                        return Err(self.error(text.chars().count() + 1, "expected variable"));
+                    };
+                    Command::Set(var.to_string(), Value::parse(&self.rest(&words, 2, "value")?))
+                },
+                Some(&(_, "add")) => {
+                    let Some(&(_, var)) = words.get(1) else {
+                        return Err(self.error(text.chars().count() + 1, "expected variable"));
+                    };
+                    let n = self.integer(words.get(2), "number")?;
+                    self.expect_end(&words, 3)?;
+                    Command::Add(var.to_string(), n)
+                },
+                Some(&(_, kind @ ("move" | "open"))) => {
+                    let pos = self.position(&words)?;
+                    let dir = self.direction(&words, 3)?;
+                    self.expect_end(&words, 4)?;
+                    match kind {
+                        "move" => Command::Move(pos, dir),
+                        _ => Command::Open(pos, dir)
+                    }
+                },
+                Some(&(_, "battle")) => {
+                    let Some(&(column, _)) = words.get(1) else {
+                        return Err(self.error(text.chars().count() + 1, "expected monster"));
+                    };
+                    Command::Battle(self.monsters(text, column)?)
+                },
+                Some(&(_, "choose")) => {
+                    let Some(&(_, var)) = words.get(1) else {
+                        return Err(self.error(text.chars().count() + 1, "expected variable"));
+                    };
+                    let Some(&(column, _)) = words.get(2) else {
+                        return Err(self.error(text.chars().count() + 1, "expected option"));
+                    };
+                    Command::Choose(var.to_string(), self.options(text, column)?)
+                },
+                Some(&(column, word)) => {
+                    return Err(self.error(column, format!("unknown command `{}`", word)));
+                }
+            };
+            event.commands.push(command);
+        }
+    }
+
+    fn vertical(&self, words: &[(usize, &str)], i: usize)
+        -> Result<Vertical, LevelParseError>
+    {
+        match words.get(i) {
+            Some(&(column, word)) => parse_vertical(word).ok_or_else(|| {
+                self.error(column, format!("expected `up` or `down`, found `{}`", word))
+            }),
+            None => Err(self.error(
+                self.lines[self.line].chars().count() + 1,
+                "expected `up` or `down`"
+            ))
+        }
+    }
+
+    /// Parses the comma-separated list of monsters starting at `column`.
+    fn monsters(&self, text: &str, mut column: usize)
+        -> Result<Vec<Monster>, LevelParseError>
+    {
+        let rest = text.chars().skip(column - 1).collect::<String>();
+        let mut monsters = Vec::new();
+        for part in rest.split(',') {
+            let name = part.trim();
+            let name_column = column
+                + part.chars().count() - part.trim_start().chars().count();
+            let monster = Monster::ALL.iter().copied()
+                .find(|monster| monster.name() == name)
+                .ok_or_else(|| match name {
+                    "" => self.error(name_column, "expected monster"),
+                    _ => self.error(name_column, format!("unknown monster `{}`", name))
+                })?;
+            monsters.push(monster);
+            column += part.chars().count() + 1;
+        }
+        Ok(monsters)
+    }
+
+    /// Parses the comma-separated list of answers starting at `column`.
+    fn options(&self, text: &str, mut column: usize)
+        -> Result<Vec<String>, LevelParseError>
+    {
+        let rest = text.chars().skip(column - 1).collect::<String>();
+        let mut options = Vec::new();
+        for part in rest.split(',') {
+            let option = part.trim();
+            if option.is_empty() {
+                let option_column = column + part.chars().count();
+                return Err(self.error(option_column, "expected option"));
+            }
+            options.push(option.to_string());
+            column += part.chars().count() + 1;
+        }
+        Ok(options)
+    }
+
+    fn expect_end(&self, words: &[(usize, &str)], len: usize)
+        -> Result<(), LevelParseError>
+    {
+        match words.get(len) {
+            Some(&(column, text)) => Err(self.error(
+                column,
+                format!("unexpected `{}`", text)
+            )),
+            None => Ok(())
+        }
+    }
+
+    fn parse(mut self) -> Result<Level, LevelParseError> {
+        while self.line < self.lines.len() {
+            let text = self.lines[self.line];
+            let words = words(text);
+            match words.first() {
+                None => {},
+                Some((_, word)) if word.starts_with('#') => {},
+                Some(&(column, "name")) => {
+                    if self.name.is_some() {
+                        return Err(self.error(column, "duplicate `name`"));
+                    }
+                    self.name = Some(text.trim_start()["name".len()..].trim().to_string());
+                },
+                Some(&(column, "size")) => {
+                    if self.size.is_some() {
+                        return Err(self.error(column, "duplicate `size`"));
+                    }
+                    let width = self.number(words.get(1), "width")?;
+                    let height = self.number(words.get(2), "height")?;
+                    self.expect_end(&words, 3)?;
+                    if width == 0 || height == 0 {
+                        return Err(self.error(column, "levels cannot be empty"));
+                    }
+                    self.size = Some((width, height));
+                },
+                Some(&(column, "edges")) => {
+                    if self.edges.is_some() {
+                        return Err(self.error(column, "duplicate `edges`"));
+                    }
+                    let edges = match words.get(1) {
+                        Some(&(column, word)) => parse_edges(word).ok_or_else(|| {
+                            self.error(column, format!("unknown edges `{}`", word))
+                        })?,
+                        None => return Err(self.error(
+                            text.chars().count() + 1,
+                            "expected `hard` or `wrap`"
+                        ))
+                    };
+                    self.expect_end(&words, 2)?;
+                    self.edges = Some(edges);
+                },
+                Some(&(column, "start")) => {
+                    if self.start.is_some() {
+                        return Err(self.error(column, "duplicate `start`"));
+                    }
+                    let pos = self.position(&words)?;
+                    let dir = match words.get(3) {
+                        Some(&(column, word)) => parse_dir(word).ok_or_else(|| {
+                            self.error(column, format!("unknown direction `{}`", word))
+                        })?,
+                        None => return Err(self.error(
+                            text.chars().count() + 1,
+                            "expected direction"
+                        ))
+                    };
+                    self.expect_end(&words, 4)?;
+                    self.start = Some((pos, dir));
+                },
+                Some(&(column, "map")) => {
+                    if self.walls.is_some() {
+                        return Err(self.error(column, "duplicate `map`"));
+                    }
+                    if self.size.is_none() {
+                        return Err(self.error(column, "`size` must come first"));
+                    }
+                    self.expect_end(&words, 1)?;
+                    self.walls = Some(self.parse_map()?);
+                    continue;
+                },
+                Some(&(column, "seen")) => {
+                    if self.automap.is_some() {
+                        return Err(self.error(column, "duplicate `seen`"));
+                    }
+                    if self.size.is_none() {
+                        return Err(self.error(column, "`size` must come first"));
+                    }
+                    self.expect_end(&words, 1)?;
+                    self.automap = Some(self.parse_seen()?);
+                    continue;
+                },
+                Some(&(column, kind @ (
+                    "stairs" | "ladder" | "pit" | "chest" | "trap" | "fountain"
+                    | "teleport" | "spinner" | "antimagic" | "dark"
+                ))) => {
+                    let pos = self.position(&words)?;
+                    let feature = match kind {
+                        "stairs" => Feature::Stairs(
+                            self.vertical(&words, 3)?,
+                            self.destination(&words, 4)?
+                        ),
+                        "ladder" => Feature::Ladder(
+                            self.vertical(&words, 3)?,
+                            self.destination(&words, 4)?
+                        ),
+                        "pit" => Feature::Pit(self.destination(&words, 3)?),
+                        "chest" => {
+                            let trap = self.number(words.get(3), "trap chance")?;
+                            if trap > 100 {
+                                return Err(self.error(words[3].0, "trap chance above 100"));
+                            }
+                            let Some(&(column, _)) = words.get(4) else {
+                                return Err(self.error(text.chars().count() + 1, "expected item"));
+                            };
+                            let item = text.chars().skip(column - 1).collect::<String>();
+                            Feature::Chest(Chest {
+                                item: item.trim().to_string(),
+                                trap: trap as u32
+                            })
+                        },
+                        "trap" => {
+                            let damage = self.number(words.get(3), "damage")?;
+                            self.expect_end(&words, 4)?;
+                            Feature::Trap(damage as u32)
+                        },
+                        "teleport" => Feature::Teleporter(self.destination(&words, 3)?),
+                        _ => {
+                            self.expect_end(&words, 3)?;
+                            match kind {
+                                "fountain" => Feature::Fountain,
+                                "spinner" => Feature::Spinner,
+                                "antimagic" => Feature::AntiMagic,
+                                _ => Feature::Darkness
+                            }
+                        }
+                    };
+                    if self.features.insert(pos, feature).is_some() {
+                        return Err(self.error(column, "cell already has a feature"));
+                    }
+                },
+                Some(&(column, "lock")) => {
+                    let pos = self.position(&words)?;
+                    let dir = match words.get(3) {
+                        Some(&(column, word)) => parse_dir(word).ok_or_else(|| {
+                            self.error(column, format!("unknown direction `{}`", word))
+                        })?,
+                        None => return Err(self.error(
+                            text.chars().count() + 1,
+                            "expected direction"
+                        ))
+                    };
+                    let key = self.number(words.get(4), "key")? as u32;
+                    self.expect_end(&words, 5)?;
+                    let Some((horiz_walls, vert_walls)) = self.walls.as_mut() else {
+                        return Err(self.error(column, "`map` must come first"));
+                    };
+                    let wall = match dir {
+                        East => &mut vert_walls[pos.x + 1][pos.y],
+                        South => &mut horiz_walls[pos.x][pos.y + 1],
+                        West => &mut vert_walls[pos.x][pos.y],
+                        North => &mut horiz_walls[pos.x][pos.y]
+                    };
+                    let Wall::Door(door) = wall else {
+                        return Err(self.error(words[3].0, "no door there"));
+                    };
+                    *door = Door { open: false, lock: Some(key) };
+                },
+                Some(&(_, "zone")) => {
+                    let Some(&(column, name)) = words.get(1) else {
+                        return Err(self.error(text.chars().count() + 1, "expected zone name"));
+                    };
+                    if self.zones.iter().any(|zone| zone.name == name) {
+                        return Err(self.error(column, format!("duplicate zone `{}`", name)));
+                    }
+                    let rate = self.number(words.get(2), "encounter rate")?;
+                    if rate > 100 {
+                        return Err(self.error(words[2].0, "encounter rate above 100"));
+                    }
+                    let area = if words.len() > 3 {
+                        let from = self.position(&words[2..])?;
+                        let to = self.position(&words[4..])?;
+                        self.expect_end(&words, 7)?;
+                        if to.x < from.x || to.y < from.y {
+                            return Err(self.error(words[5].0, "zone ends before it starts"));
+                        }
+                        Some((from, to))
+                    } else {
+                        None
+                    };
+                    self.zones.push(Zone {
+                        name: name.to_string(),
+                        rate: rate as u32,
+                        area,
+                        groups: Vec::new()
+                    });
+                },
+                Some(&(_, "encounter")) => {
+                    let Some(&(column, name)) = words.get(1) else {
+                        return Err(self.error(text.chars().count() + 1, "expected zone name"));
+                    };
+                    let i = self.zones.iter().position(|zone| zone.name == name)
+                        .ok_or_else(|| self.error(column, format!("unknown zone `{}`", name)))?;
+                    let weight = self.number(words.get(2), "weight")?;
+                    if weight == 0 {
+                        return Err(self.error(words[2].0, "weight must be positive"));
+                    }
+                    let Some(&(column, _)) = words.get(3) else {
+                        return Err(self.error(text.chars().count() + 1, "expected monster"));
+                    };
+                    let monsters = self.monsters(text, column)?;
+                    self.zones[i].groups.push(EncounterGroup {
+                        weight: weight as u32,
+                        monsters
+                    });
+                },
+                Some(&(_, "event")) => {
+                    let event = self.parse_event(&words)?;
+                    self.events.push(event);
+                },
+                Some(&(_, "wall")) => {
+                    let pos = self.position(&words)?;
+                    let dir = self.direction(&words, 3)?;
+                    let wall = match words.get(4) {
+                        Some(&(_, "none")) => Wall::None,
+                        Some(&(column, word)) => {
+                            let mut chars = word.chars();
+                            let (Some(ch), None) = (chars.next(), chars.next()) else {
+                                return Err(self.error(column, format!("expected wall, found `{}`", word)));
+                            };
+                            parse_wall(ch, side_solid(dir)).ok_or_else(|| {
+                                self.error(column, format!("expected wall, found `{}`", word))
+                            })?
+                        },
+                        None => return Err(self.error(text.chars().count() + 1, "expected wall"))
+                    };
+                    match words.get(5) {
+                        Some(&(_, "if")) => {},
+                        Some(&(column, word)) => {
+                            return Err(self.error(column, format!("expected `if`, found `{}`", word)));
+                        },
+                        None => return Err(self.error(text.chars().count() + 1, "expected `if`"))
+                    }
+                    let condition = self.condition(&words, 6)?;
+                    self.wall_changes.push(WallChange { pos, dir, wall, condition });
+                },
+                Some(&(_, "meta")) => {
+                    let pos = self.position(&words)?;
+                    let Some(&(column, key)) = words.get(3) else {
+                        return Err(self.error(text.chars().count() + 1, "expected key"));
+                    };
+                    let value = text.chars().skip(column - 1 + key.chars().count())
+                        .collect::<String>();
+                    let cell = self.meta.entry(pos).or_default();
+                    if cell.contains_key(key) {
+                        return Err(self.error(column, format!("duplicate key `{}`", key)));
+                    }
+                    cell.insert(key.to_string(), value.trim().to_string());
+                },
+                Some(&(column, word)) => {
+                    return Err(self.error(column, format!("unknown directive `{}`", word)));
+                }
+            }
+            self.line += 1;
+        }
+
+        let (Some((width, height)), Some((horiz_walls, vert_walls)))
+            = (self.size, self.walls.take()) else
+        {
+            return Err(LevelParseError::new(1, 1, "missing `map`"));
+        };
+        let Some((start, start_dir)) = self.start else {
+            return Err(LevelParseError::new(1, 1, "missing `start`"));
+        };
+        Ok(Level {
+            name: self.name.unwrap_or_default(),
+            width,
+            height,
+            edges: self.edges.unwrap_or(Edges::Hard),
+            start,
+            start_dir,
+            horiz_walls,
+            vert_walls,
+            features: self.features,
+            automap: self.automap.unwrap_or_else(|| Automap::new(width, height)),
+            zones: self.zones,
+            events: self.events,
+            wall_changes: self.wall_changes,
+            meta: self.meta
+        })
+    }
+
+    /// Reads the `2 * height + 1` rows of the grid following a `map` or
+    /// `seen` line, leaving `self.line` on the line after the grid. `cell` is
+    /// called with the row, column and character of every position in the
+    /// grid and returns the characters it expected if the one it got is not
+    /// valid there.
+    fn parse_grid(
+        &mut self,
+        what: &str,
+        mut cell: impl FnMut(usize, usize, char) -> Result<(), &'static str>
+    ) -> Result<(), LevelParseError> {
+        let (width, height) = self.size.unwrap_or_default();
+        for row in 0..(2 * height + 1) {
+            self.line += 1;
+            let Some(text) = self.lines.get(self.line) else {
+                return Err(self.error(1, format!(
+                    "{} ends after {} rows, expected {}",
+                    what,
+                    row,
+                    2 * height + 1
+                )));
+            };
+            let chars = text.chars().collect::<Vec<_>>();
+            if let Some(i) = (2 * width + 1..chars.len()).find(|&i| !chars[i].is_whitespace()) {
+                return Err(self.error(
+                    i + 1,
+                    format!("unexpected `{}` after {} row", chars[i], what)
+                ));
+            }
+            for i in 0..(2 * width + 1) {
+                let ch = chars.get(i).copied().unwrap_or(' ');
+                if let Err(expected) = cell(row, i, ch) {
+                    return Err(self.error(i + 1, format!(
+                        "expected {}, found `{}`",
+                        expected,
+                        ch
+                    )));
+                }
+            }
+        }
+        self.line += 1;
+        Ok(())
+    }
+
+    fn parse_map(&mut self) -> Result<(Walls, Walls), LevelParseError> {
+        let (width, height) = self.size.unwrap_or_default();
+        let mut horiz_walls = vec![vec![Wall::None; height + 1]; width];
+        let mut vert_walls = vec![vec![Wall::None; height]; width + 1];
+        self.parse_grid("map", |row, i, ch| {
+            let (x, y) = (i / 2, row / 2);
+            match (row % 2, i % 2) {
+                (0, 0) => (ch == '+').then_some(()).ok_or("`+`"),
+                (0, _) => parse_wall(ch, '-')
+                    .map(|wall| horiz_walls[x][y] = wall)
+                    .ok_or("` `, `-`, `D`, `O`, `S`, `^` or `v`"),
+                (_, 0) => parse_wall(ch, '|')
+                    .map(|wall| vert_walls[x][y] = wall)
+                    .ok_or("` `, `|`, `D`, `O`, `S`, `<` or `>`"),
+                (_, _) => (ch == '.').then_some(()).ok_or("`.`")
+            }
+        })?;
+        Ok((horiz_walls, vert_walls))
+    }
+
+    fn parse_seen(&mut self) -> Result<Automap, LevelParseError> {
+        let (width, height) = self.size.unwrap_or_default();
+        let mut automap = Automap::new(width, height);
+        self.parse_grid("seen", |row, i, ch| {
+            let seen = match (row % 2, i % 2, ch) {
+                (0, 0, '+') => return Ok(()),
+                (0, 0, _) => return Err("`+`"),
+                (_, _, 'x') => true,
+                (_, _, ' ') => false,
+                _ => return Err("` ` or `x`")
+            };
+            let (x, y) = (i / 2, row / 2);
+            match (row % 2, i % 2) {
+                (0, _) => automap.horiz_walls[x][y] = seen,
+                (_, 0) => automap.vert_walls[x][y] = seen,
+                (_, _) => automap.cells[x][y] = seen
+            }
+            Ok(())
+        })?;
+        Ok(automap)
+    }
+}
+
+impl FromStr for Level {
+    type Err = LevelParseError;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        LevelParser {
+            lines: src.lines().collect(),
+            line: 0,
+            name: None,
+            size: None,
+            edges: None,
+            start: None,
+            walls: None,
+            features: BTreeMap::new(),
+            automap: None,
+            zones: Vec::new(),
+            events: Vec::new(),
+            wall_changes: Vec::new(),
+            meta: BTreeMap::new()
+        }.parse()
+    }
+}
+
+impl Display for Level {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if !self.name.is_empty() {
+            writeln!(f, "name {}", self.name)?;
+        }
+        writeln!(f, "size {} {}", self.width, self.height)?;
+        writeln!(f, "edges {}", edges_name(self.edges))?;
+        writeln!(
+            f,
+            "start {} {} {}",
+            self.start.x,
+            self.start.y,
+            dir_name(self.start_dir)
+        )?;
+        writeln!(f, "map")?;
+        for y in 0..=self.height {
+            let mut line = String::new();
+            for x in 0..self.width {
+                line.push('+');
+                line.push(wall_char(self.horiz_walls[x][y], '-'));
+            }
+            line.push('+');
+            writeln!(f, "{}", line)?;
+            if y < self.height {
+                let mut line = String::new();
+                for x in 0..=self.width {
+                    line.push(wall_char(self.vert_walls[x][y], '|'));
+                    if x < self.width {
+                        line.push('.');
+                    }
+                }
+                writeln!(f, "{}", line.trim_end())?;
+            }
+        }
+        if !self.automap.is_empty() {
+            writeln!(f, "seen")?;
+            let mark = |seen: bool| if seen { 'x' } else { ' ' };
+            for y in 0..=self.height {
+                let mut line = String::new();
+                for x in 0..self.width {
+                    line.push('+');
+                    line.push(mark(self.automap.horiz_walls[x][y]));
+                }
+                line.push('+');
+                writeln!(f, "{}", line)?;
+                if y < self.height {
+                    let mut line = String::new();
+                    for x in 0..=self.width {
+                        line.push(mark(self.automap.vert_walls[x][y]));
+                        if x < self.width {
+                            line.push(mark(self.automap.cells[x][y]));
+                        }
+                    }
+                    writeln!(f, "{}", line.trim_end())?;
+                }
+            }
+        }
+        for (pos, feature) in self.features.iter() {
+            writeln!(f, "{}", feature_directive(*pos, feature))?;
+        }
+        let horiz = self.horiz_walls.iter().enumerate()
+            .flat_map(|(x, walls)| walls.iter().enumerate().map(move |(y, w)| (true, x, y, w)));
+        let vert = self.vert_walls.iter().enumerate()
+            .flat_map(|(x, walls)| walls.iter().enumerate().map(move |(y, w)| (false, x, y, w)));
+        for (horiz, x, y, wall) in horiz.chain(vert) {
+            if let Wall::Door(Door { lock: Some(key), .. }) = wall {
+                let (x, y, dir) = wall_side(self, horiz, x, y);
+                writeln!(f, "lock {} {} {} {}", x, y, dir_name(dir), key)?;
+            }
+        }
+        for zone in self.zones.iter() {
+            write!(f, "zone {} {}", zone.name, zone.rate)?;
+            if let Some((from, to)) = zone.area {
+                write!(f, " {} {} {} {}", from.x, from.y, to.x, to.y)?;
+            }
+            writeln!(f)?;
+            for group in zone.groups.iter() {
+                let monsters = group.monsters.iter()
+                    .map(|monster| monster.name())
+                    .collect::<Vec<_>>();
+                writeln!(
+                    f,
+                    "encounter {} {} {}",
+                    zone.name,
+                    group.weight,
+                    monsters.join(", ")
+                )?;
+            }
+        }
+        for change in self.wall_changes.iter() {
+            let wall = match change.wall {
+                Wall::None => "none".to_string(),
+                wall => wall_char(wall, side_solid(change.dir)).to_string()
+            };
+            writeln!(
+                f,
+                "wall {} {} {} {} if {}",
+                change.pos.x,
+                change.pos.y,
+                dir_name(change.dir),
+                wall,
+                change.condition
+            )?;
+        }
+        for event in self.events.iter() {
+            writeln!(
+                f,
+                "event {} {} {}",
+                event.pos.x,
+                event.pos.y,
+                trigger_words(&event.trigger)
+            )?;
+            if event.once {
+                writeln!(f, "once")?;
+            }
+            for condition in event.conditions.iter() {
+                writeln!(f, "if {}", condition)?;
+            }
+            for command in event.commands.iter() {
+                writeln!(f, "{}", command_line(command))?;
+            }
+            writeln!(f, "end")?;
+        }
+        for (pos, cell) in self.meta.iter() {
+            for (key, value) in cell.iter() {
+                let line = format!("meta {} {} {} {}", pos.x, pos.y, key, value);
+                writeln!(f, "{}", line.trim_end())?;
+            }
+        }
+        Ok(())
+    }
+}
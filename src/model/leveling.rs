@@ -0,0 +1,180 @@
+use crate::model::{
+    *,
+    CharacterClass::*
+};
+
+/// The highest level a character can reach.
+pub const MAX_LVL: u32 = 50;
+/// The most a newly rolled stat can exceed the class's starting value by.
+pub const ROLL_BONUS: u32 = 4;
+
+impl CharacterClass {
+    /// Total XP needed to reach `lvl`.
+    pub fn xp_for_lvl(&self, lvl: u32) -> u32 {
+        let factor = match self {
+            Warrior => 10,
+            Priest => 11,
+            Magician => 12
+        };
+        factor * lvl.saturating_sub(1).pow(2)
+    }
+
+    // Average gains per level. The actual gain is anywhere from nothing to
+    // twice the average.
+
+    pub fn hp_growth(&self) -> u32 {
+        match self {
+            Warrior => 3,
+            Priest => 2,
+            Magician => 1
+        }
+    }
+
+    pub fn mp_growth(&self) -> u32 {
+        match self {
+            Warrior => 0,
+            Priest => 2,
+            Magician => 3
+        }
+    }
+
+    pub fn atk_growth(&self) -> u32 {
+        match self {
+            Warrior => 2,
+            Priest => 1,
+            Magician => 0
+        }
+    }
+
+    pub fn def_growth(&self) -> u32 {
+        match self {
+            Warrior => 1,
+            Priest => 1,
+            Magician => 0
+        }
+    }
+
+    pub fn matk_growth(&self) -> u32 {
+        match self {
+            Warrior => 0,
+            Priest => 1,
+            Magician => 2
+        }
+    }
+
+    pub fn mdef_growth(&self) -> u32 {
+        match self {
+            Warrior => 0,
+            Priest => 1,
+            Magician => 1
+        }
+    }
+
+    pub fn agi_growth(&self) -> u32 {
+        1
+    }
+
+    pub fn luck_growth(&self) -> u32 {
+        1
+    }
+}
+
+fn roll_growth(rng: &mut Rng, growth: u32) -> u32 {
+    rng.below(2 * growth + 1)
+}
+
+impl Character {
+    /// Rolls a new level 1 character. Every stat is the class's `start_base_*`
+    /// value plus a random bonus of up to `ROLL_BONUS`, except for MP, which
+    /// stays at nothing for classes without magic.
+    pub fn roll(name: String, class: CharacterClass, rng: &mut Rng) -> Self {
+        let mut roll = |base: u32| base + rng.below(ROLL_BONUS + 1);
+        let max_hp = roll(class.start_base_hp());
+        let max_mp = match class.start_base_mp() {
+            0 => 0,
+            base => roll(base)
+        };
+        Character {
+            name,
+            class,
+            lvl: 1,
+            xp: 0,
+            hp: max_hp,
+            max_hp,
+            mp: max_mp,
+            max_mp,
+            base_atk: roll(class.start_base_atk()),
+            base_def: roll(class.start_base_def()),
+            base_matk: roll(class.start_base_matk()),
+            base_mdef: roll(class.start_base_mdef()),
+            base_agi: roll(class.start_base_agi()),
+            base_luck: roll(class.start_base_luck()),
+            weapon: None,
+            shield: None,
+            armor: None,
+            helmet: None,
+            gloves: None,
+            boots: None,
+            accessory: None,
+            buffs: Vec::new(),
+            statuses: Vec::new()
+        }
+    }
+
+    /// Total XP needed for the next level, `None` at the maximum level.
+    pub fn xp_for_next_lvl(&self) -> Option<u32> {
+        (self.lvl < MAX_LVL).then(|| self.class.xp_for_lvl(self.lvl + 1))
+    }
+
+    /// Adds `xp` and levels up as often as it allows. Returns whether the
+    /// character leveled up.
+    pub fn gain_xp(&mut self, xp: u32, rng: &mut Rng) -> bool {
+        self.xp = self.xp.saturating_add(xp);
+        let lvl = self.lvl;
+        while self.xp_for_next_lvl().is_some_and(|next| self.xp >= next) {
+            self.level_up(rng);
+        }
+        self.lvl > lvl
+    }
+
+    fn level_up(&mut self, rng: &mut Rng) {
+        let class = self.class;
+        self.lvl += 1;
+        let hp = roll_growth(rng, class.hp_growth()).max(1);
+        let mp = roll_growth(rng, class.mp_growth());
+        self.max_hp += hp;
+        self.hp += hp;
+        self.max_mp += mp;
+        self.mp += mp;
+        self.base_atk += roll_growth(rng, class.atk_growth());
+        self.base_def += roll_growth(rng, class.def_growth());
+        self.base_matk += roll_growth(rng, class.matk_growth());
+        self.base_mdef += roll_growth(rng, class.mdef_growth());
+        self.base_agi += roll_growth(rng, class.agi_growth());
+        self.base_luck += roll_growth(rng, class.luck_growth());
+    }
+}
+
+impl State {
+    /// Shares `xp` between the living party members. Returns the XP each of
+    /// them got and, for those who leveled up, their index in the party and
+    /// what they were like before.
+    pub fn award_xp(&mut self, xp: u32) -> (u32, Vec<(usize, Character)>) {
+        let alive = self.party.iter().filter(|c| c.is_alive()).count() as u32;
+        if alive == 0 {
+            return (0, Vec::new());
+        }
+        let share = xp.div_ceil(alive);
+        let mut level_ups = Vec::new();
+        for (i, character) in self.party.iter_mut().enumerate() {
+            if !character.is_alive() {
+                continue;
+            }
+            let before = character.clone();
+            if character.gain_xp(share, &mut self.rng.stats) {
+                level_ups.push((i, before));
+            }
+        }
+        (share, level_ups)
+    }
+}
@@ -1,19 +1,55 @@
+mod automap;
 mod character;
 mod character_class;
+mod combat;
 mod direction;
+mod door;
+mod dungeon;
+mod encounter;
+mod enemy;
+mod event;
+mod equipment;
+mod feature;
+mod inventory;
 mod item;
+mod item_file;
 mod item_predicate;
 mod item_slot;
 mod level;
+mod level_file;
+mod leveling;
 mod position;
+mod rng;
+mod save_file;
+mod spell;
+mod stat;
+mod status;
 mod state;
+mod vars;
 
+pub use automap::*;
 pub use character::*;
 pub use character_class::*;
+pub use combat::*;
 pub use direction::*;
+pub use dungeon::*;
+pub use encounter::*;
+pub use enemy::*;
+pub use event::*;
+pub use feature::*;
+pub use inventory::*;
 pub use item::*;
+pub use item_file::*;
 pub use item_predicate::*;
 pub use item_slot::*;
 pub use level::*;
+pub use level_file::*;
+pub use leveling::*;
 pub use position::*;
+pub use rng::*;
+pub use save_file::*;
+pub use spell::*;
+pub use stat::*;
+pub use status::*;
 pub use state::*;
+pub use vars::*;
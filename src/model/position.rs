@@ -1,25 +1,41 @@
 use crate::model::*;
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct Position {
     pub x: usize,
     pub y: usize
 }
 
 impl Position {
-    pub fn move_by(&self, dir: Direction, d: isize) -> Self {
+    /// Moves `d` cells towards `dir` within `level`. Returns `None` if that
+    /// would cross a hard edge of the level.
+    pub fn move_by(&self, dir: Direction, d: isize, level: &Level) -> Option<Self> {
         let (dx, dy) = dir.offset();
-        let x = (self.x as isize + dx * d)
-            .rem_euclid(LEVEL_WIDTH as isize) as usize;
-        let y = (self.y as isize + dy * d)
-            .rem_euclid(LEVEL_HEIGHT as isize) as usize;
-        Self { x, y }
+        let x = self.x as isize + dx * d;
+        let y = self.y as isize + dy * d;
+        match level.edges {
+            Edges::Wrap => Some(Self {
+                x: x.rem_euclid(level.width as isize) as usize,
+                y: y.rem_euclid(level.height as isize) as usize
+            }),
+            Edges::Hard if level.contains(x, y) => Some(Self {
+                x: x as usize,
+                y: y as usize
+            }),
+            Edges::Hard => None
+        }
     }
 
     /// Translates the given position by the vector `(dx, dy)` in the relative
     /// frame where `dir` is forwards (i.e. positive y).
-    pub fn translate(&self, dir: Direction, dx: isize, dy: isize) -> Self {
-        self.move_by(dir, dy).move_by(dir.right(), dx)
+    pub fn translate(
+        &self,
+        dir: Direction,
+        dx: isize,
+        dy: isize,
+        level: &Level
+    ) -> Option<Self> {
+        self.move_by(dir, dy, level)?.move_by(dir.right(), dx, level)
     }
 }
 
@@ -0,0 +1,99 @@
+/// A small deterministic random number generator (SplitMix64). It is part of
+/// the saved game, so a game continues with the same dice after loading.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Rng {
+    pub state: u64
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng {
+            state: seed
+        }
+    }
+
+    /// The generator named `name` among those started from `seed`. It
+    /// depends only on the seed and the name, so adding generators does not
+    /// change the others.
+    pub fn stream(seed: u64, name: &str) -> Self {
+        // FNV-1a
+        let hash = name.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+        Rng::new(Rng::new(seed ^ hash).next_u64())
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number in `0..n`. `n` must not be 0.
+    pub fn below(&mut self, n: u32) -> u32 {
+        (((self.next_u64() >> 32) * n as u64) >> 32) as u32
+    }
+
+    /// Returns `true` with a chance of `percent` in 100.
+    pub fn chance(&mut self, percent: u32) -> bool {
+        self.below(100) < percent
+    }
+}
+
+/// The random number generators of a game, all started from one seed. Each
+/// part of the game rolls its own dice, so that rolling more in one part
+/// does not change what happens in another.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Rngs {
+    pub seed: u64,
+    /// Hits, statuses and anything else rolled in battle.
+    pub combat: Rng,
+    /// Chests and what they hold.
+    pub loot: Rng,
+    /// Random encounters and the monsters met.
+    pub encounters: Rng,
+    /// Stats of new characters and their growth when leveling up.
+    pub stats: Rng,
+    /// Spinners and anything else the dungeon does to the party.
+    pub dungeon: Rng
+}
+
+impl Rngs {
+    pub const NAMES: [&'static str; 5] = ["combat", "loot", "encounters", "stats", "dungeon"];
+
+    pub fn new(seed: u64) -> Self {
+        Rngs {
+            seed,
+            combat: Rng::stream(seed, "combat"),
+            loot: Rng::stream(seed, "loot"),
+            encounters: Rng::stream(seed, "encounters"),
+            stats: Rng::stream(seed, "stats"),
+            dungeon: Rng::stream(seed, "dungeon")
+        }
+    }
+
+    /// The generator named `name`, one of `NAMES`.
+    pub fn get(&self, name: &str) -> Option<&Rng> {
+        match name {
+            "combat" => Some(&self.combat),
+            "loot" => Some(&self.loot),
+            "encounters" => Some(&self.encounters),
+            "stats" => Some(&self.stats),
+            "dungeon" => Some(&self.dungeon),
+            _ => None
+        }
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Rng> {
+        match name {
+            "combat" => Some(&mut self.combat),
+            "loot" => Some(&mut self.loot),
+            "encounters" => Some(&mut self.encounters),
+            "stats" => Some(&mut self.stats),
+            "dungeon" => Some(&mut self.dungeon),
+            _ => None
+        }
+    }
+}
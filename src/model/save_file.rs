@@ -0,0 +1,563 @@
+//! Text format for saved games.
+//!
+//! The first line identifies the file and its version, e.g.
+//! `blackbox-save 10`. It is followed by `floor N`, `pos X Y` and
+//! `dir DIR`, the seed the game was started with as `seed N`, the state of
+//! each of its random number generators as `rng NAME N`, the steps since
+//! the last encounter as `steps N`, the steps left on a ward as `ward N`
+//! and on the `Light` spell as `light N`, one `var NAME VALUE` line per
+//! story variable with strings in double quotes, one `item NAME` line per
+//! item in the inventory, one block per party member, one block per
+//! character waiting in the guild, which is the same but starts with
+//! `recruit` instead of `character`, and one block per floor of the
+//! dungeon. Each `status` line in a character block holds a status and, for
+//! those that wear off, the steps or turns left:
+//!
+//! ```text
+//! character
+//! name TELLURIUS
+//! class WARRIOR
+//! lvl 1
+//! xp 0
+//! hp 9 9
+//! mp 0 0
+//! atk 6
+//! ...
+//! weapon IRON SWORD
+//! status paralysis 12
+//! end
+//! level
+//! (the floor in the level format)
+//! end
+//! ```
+//!
+//! Saves written by older versions of the game are upgraded line by line
+//! through `MIGRATIONS` before being parsed.
+
+use ::std::{
+    collections::BTreeMap,
+    error::Error,
+    fmt::{
+        self,
+        Display,
+        Formatter
+    },
+    str::FromStr
+};
+use crate::model::*;
+
+pub const SAVE_MAGIC: &str = "blackbox-save";
+pub const SAVE_VERSION: u32 = 10;
+
+/// Upgrades the lines of a save, without the header, by one version. The
+/// entry at index `i` turns a save of version `i + 1` into one of version
+/// `i + 2`, so there must always be `SAVE_VERSION - 1` of them.
+pub type Migration = fn(&mut Vec<String>);
+pub const MIGRATIONS: &[Migration] = &[
+    add_encounter_state,
+    add_light,
+    add_xp,
+    add_roster,
+    add_equipment_slots,
+    add_statuses,
+    add_flags,
+    add_vars,
+    split_rng
+];
+
+/// Version 2 added random encounters.
+fn add_encounter_state(lines: &mut Vec<String>) {
+    lines.splice(0..0, ["rng 0", "steps 0", "ward 0"].map(str::to_string));
+}
+
+/// Version 3 added the `Light` spell.
+fn add_light(lines: &mut Vec<String>) {
+    lines.insert(0, "light 0".to_string());
+}
+
+/// Version 4 added experience points.
+fn add_xp(lines: &mut Vec<String>) {
+    for i in (0..lines.len()).rev() {
+        if lines[i].trim() == "character" {
+            lines.insert(i + 1, "xp 0".to_string());
+        }
+    }
+}
+
+/// Version 5 added characters waiting in the guild, of which older saves
+/// have none.
+fn add_roster(_lines: &mut Vec<String>) {}
+
+/// Version 6 added the helmet, gloves, boots and accessory slots, which are
+/// empty in older saves.
+fn add_equipment_slots(_lines: &mut Vec<String>) {}
+
+/// Version 7 added statuses. Characters at 0 HP in older saves are dead.
+fn add_statuses(lines: &mut Vec<String>) {
+    for i in (0..lines.len()).rev() {
+        let words = lines[i].split_whitespace().collect::<Vec<_>>();
+        if words.len() == 3 && words[0] == "hp" && words[1] == "0" {
+            lines.insert(i + 1, "status dead".to_string());
+        }
+    }
+}
+
+/// Version 8 added story flags, of which older saves have none.
+fn add_flags(_lines: &mut Vec<String>) {}
+
+/// Version 9 turned story flags into variables set to `true`. The `flag`
+/// commands of events in the saved levels stay as they are.
+fn add_vars(lines: &mut Vec<String>) {
+    // events in levels are blocks ending with `end` too, see `parse_level`
+    let mut depth = 0;
+    *lines = lines.iter()
+        .map(|line| match line.split_whitespace().collect::<Vec<_>>()[..] {
+            ["flag", flag] if depth == 0 => format!("var {} true", flag),
+            ["level"] => {
+                depth += 1;
+                line.clone()
+            },
+            ["event", ..] if depth > 0 => {
+                depth += 1;
+                line.clone()
+            },
+            ["end"] if depth > 0 => {
+                depth -= 1;
+                line.clone()
+            },
+            _ => line.clone()
+        })
+        .collect();
+}
+
+/// Version 10 split the random number generator into one per part of the
+/// game, which older games continue with as if started from its state.
+fn split_rng(lines: &mut Vec<String>) {
+    let Some(i) = lines.iter().position(|line| line.trim_start().starts_with("rng ")) else {
+        return;
+    };
+    let seed = lines[i].split_whitespace().nth(1).unwrap_or_default().to_string();
+    let Ok(n) = seed.parse::<u64>() else {
+        return;
+    };
+    let rngs = Rngs::new(n);
+    let streams = Rngs::NAMES.iter()
+        .filter_map(|name| rngs.get(name).map(|rng| format!("rng {} {}", name, rng.state)));
+    lines.splice(i..=i, [format!("seed {}", seed)].into_iter().chain(streams));
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SaveError {
+    /// The save is broken. `line` and `column` are 1-based and refer to the
+    /// save after migration.
+    Malformed { line: usize, column: usize, message: String },
+    /// The save was written by a version of the game we cannot read.
+    IncompatibleVersion(u32)
+}
+
+impl Display for SaveError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::Malformed { line, column, message }
+                => write!(f, "line {}, column {}: {}", line, column, message),
+            SaveError::IncompatibleVersion(version) => write!(
+                f,
+                "save version {} is not supported (expected at most {})",
+                version,
+                SAVE_VERSION
+            )
+        }
+    }
+}
+
+impl Error for SaveError {}
+
+fn malformed(line: usize, column: usize, message: impl Into<String>) -> SaveError {
+    SaveError::Malformed {
+        line: line + 1,
+        column,
+        message: message.into()
+    }
+}
+
+/// Returns the text following the first word of a line.
+fn rest(text: &str, words: &[(usize, &str)]) -> String {
+    let (column, word) = words[0];
+    text.chars().skip(column - 1 + word.chars().count()).collect::<String>()
+        .trim().to_string()
+}
+
+fn number<T: FromStr>(
+    line: usize,
+    text: &str,
+    words: &[(usize, &str)],
+    i: usize
+) -> Result<T, SaveError> {
+    match words.get(i) {
+        Some(&(column, word)) => word.parse().map_err(|_| {
+            malformed(line, column, format!("expected number, found `{}`", word))
+        }),
+        None => Err(malformed(line, text.chars().count() + 1, "expected number"))
+    }
+}
+
+struct SaveParser<'a> {
+    lines: Vec<String>,
+    line: usize,
+    items: &'a ItemCatalog
+}
+
+impl<'a> SaveParser<'a> {
+    fn parse_item(&self, line: usize, text: &str, words: &[(usize, &str)])
+        -> Result<Item, SaveError>
+    {
+        let name = rest(text, words);
+        self.items.get(&name)
+            .ok_or_else(|| malformed(line, words[0].0, format!("unknown item `{}`", name)))
+    }
+
+    /// Parses a `character` or `recruit` block, leaving `self.line` on its
+    /// `end`.
+    fn parse_character(&mut self) -> Result<Character, SaveError> {
+        let start = self.line;
+        let mut name = None;
+        let mut class = None;
+        let mut lvl = None;
+        let mut xp = None;
+        let mut hp = None;
+        let mut mp = None;
+        let mut stats = [None; 6];
+        let mut equipment: [Option<Item>; 7] = Default::default();
+        let mut statuses = Vec::new();
+        loop {
+            self.line += 1;
+            let Some(text) = self.lines.get(self.line) else {
+                return Err(malformed(
+                    start,
+                    1,
+                    format!("`{}` without `end`", self.lines[start].trim())
+                ));
+            };
+            let (line, words) = (self.line, words(text));
+            match words.first() {
+                None => {},
+                Some(&(_, "end")) => break,
+                Some(&(_, "name")) => name = Some(rest(text, &words)),
+                Some(&(column, "class")) => {
+                    let class_name = rest(text, &words);
+                    class = Some(
+                        CharacterClass::ALL.iter().copied()
+                            .find(|class| class.name() == class_name)
+                            .ok_or_else(|| malformed(
+                                line,
+                                column,
+                                format!("unknown class `{}`", class_name)
+                            ))?
+                    );
+                },
+                Some(&(_, "lvl")) => lvl = Some(number(line, text, &words, 1)?),
+                Some(&(_, "xp")) => xp = Some(number(line, text, &words, 1)?),
+                Some(&(_, "hp")) => hp = Some((
+                    number(line, text, &words, 1)?,
+                    number(line, text, &words, 2)?
+                )),
+                Some(&(_, "mp")) => mp = Some((
+                    number(line, text, &words, 1)?,
+                    number(line, text, &words, 2)?
+                )),
+                Some(&(_, "status")) => {
+                    let Some(&(column, word)) = words.get(1) else {
+                        return Err(malformed(line, text.chars().count() + 1, "expected status"));
+                    };
+                    let status = Status::ALL.iter().copied()
+                        .find(|status| status.key() == word)
+                        .ok_or_else(|| {
+                            malformed(line, column, format!("unknown status `{}`", word))
+                        })?;
+                    let left = match words.len() {
+                        2 => None,
+                        _ => Some(number(line, text, &words, 2)?)
+                    };
+                    statuses.push(StatusEffect { status, left });
+                },
+                Some(&(column, word)) => {
+                    let slot = ItemSlot::ALL.iter().position(|slot| slot.key() == word);
+                    let stat = STAT_KEYS.iter().position(|key| *key == word);
+                    match (slot, stat) {
+                        (Some(i), _) => equipment[i] = Some(self.parse_item(line, text, &words)?),
+                        (None, Some(i)) => stats[i] = Some(number(line, text, &words, 1)?),
+                        (None, None) => return Err(
+                            malformed(line, column, format!("unknown key `{}`", word))
+                        )
+                    }
+                }
+            }
+        }
+        let missing = |key: &str| malformed(self.line, 1, format!("missing `{}`", key));
+        let [weapon, shield, armor, helmet, gloves, boots, accessory] = equipment;
+        let (hp, max_hp) = hp.ok_or_else(|| missing("hp"))?;
+        let (mp, max_mp) = mp.ok_or_else(|| missing("mp"))?;
+        let mut stats = stats.iter().zip(STAT_KEYS);
+        let mut stat = || {
+            let (value, key) = stats.next().unwrap();
+            value.ok_or_else(|| missing(key))
+        };
+        Ok(Character {
+            name: name.ok_or_else(|| missing("name"))?,
+            class: class.ok_or_else(|| missing("class"))?,
+            lvl: lvl.ok_or_else(|| missing("lvl"))?,
+            xp: xp.ok_or_else(|| missing("xp"))?,
+            hp,
+            max_hp,
+            mp,
+            max_mp,
+            base_atk: stat()?,
+            base_def: stat()?,
+            base_matk: stat()?,
+            base_mdef: stat()?,
+            base_agi: stat()?,
+            base_luck: stat()?,
+            weapon,
+            shield,
+            armor,
+            helmet,
+            gloves,
+            boots,
+            accessory,
+            buffs: Vec::new(),
+            statuses
+        })
+    }
+
+    /// Parses a `level` block, leaving `self.line` on its `end`.
+    fn parse_level(&mut self) -> Result<Level, SaveError> {
+        let start = self.line;
+        // events in the level are blocks ending with `end` too
+        let mut depth = 0;
+        let Some(len) = self.lines[start + 1..].iter().position(|line| {
+            match line.split_whitespace().next() {
+                Some("event") => depth += 1,
+                Some("end") if depth > 0 => depth -= 1,
+                Some("end") => return true,
+                _ => {}
+            }
+            false
+        }) else {
+            return Err(malformed(start, 1, "`level` without `end`"));
+        };
+        self.line = start + 1 + len;
+        self.lines[start + 1..self.line].join("\n").parse()
+            .map_err(|err: LevelParseError| malformed(
+                start + err.line,
+                err.column,
+                err.message
+            ))
+    }
+
+    fn parse(mut self) -> Result<State, SaveError> {
+        let mut floor = None;
+        let mut pos = None;
+        let mut dir = None;
+        let mut party = Vec::new();
+        let mut roster = Vec::new();
+        let mut items = Vec::new();
+        let mut floors = Vec::new();
+        let mut seed = None;
+        let mut streams = Vec::new();
+        let mut encounter_steps = None;
+        let mut ward_steps = None;
+        let mut light_steps = None;
+        let mut vars = BTreeMap::new();
+        while self.line < self.lines.len() {
+            let text = self.lines[self.line].clone();
+            let (line, words) = (self.line, words(&text));
+            match words.first() {
+                None => {},
+                Some(&(_, "floor")) => floor = Some(number(line, &text, &words, 1)?),
+                Some(&(_, "pos")) => pos = Some(Position {
+                    x: number(line, &text, &words, 1)?,
+                    y: number(line, &text, &words, 2)?
+                }),
+                Some(&(_, "dir")) => {
+                    let Some(&(column, word)) = words.get(1) else {
+                        return Err(malformed(
+                            line,
+                            text.chars().count() + 1,
+                            "expected direction"
+                        ));
+                    };
+                    dir = Some(parse_dir(word).ok_or_else(|| {
+                        malformed(line, column, format!("unknown direction `{}`", word))
+                    })?);
+                },
+                Some(&(_, "seed")) => seed = Some(number(line, &text, &words, 1)?),
+                Some(&(_, "rng")) => {
+                    let Some(&(column, name)) = words.get(1) else {
+                        return Err(malformed(line, text.chars().count() + 1, "expected name"));
+                    };
+                    if !Rngs::NAMES.contains(&name) {
+                        let message = format!("unknown generator `{}`", name);
+                        return Err(malformed(line, column, message));
+                    }
+                    streams.push((name.to_string(), Rng::new(number(line, &text, &words, 2)?)));
+                },
+                Some(&(_, "steps")) => encounter_steps = Some(number(line, &text, &words, 1)?),
+                Some(&(_, "ward")) => ward_steps = Some(number(line, &text, &words, 1)?),
+                Some(&(_, "light")) => light_steps = Some(number(line, &text, &words, 1)?),
+                Some(&(_, "var")) => {
+                    let Some(&(_, var)) = words.get(1) else {
+                        return Err(malformed(line, text.chars().count() + 1, "expected variable"));
+                    };
+                    if words.len() < 3 {
+                        return Err(malformed(line, text.chars().count() + 1, "expected value"));
+                    }
+                    vars.insert(var.to_string(), Value::parse(&rest(&text, &words[1..])));
+                },
+                Some(&(_, "item")) => items.push(self.parse_item(line, &text, &words)?),
+                Some(&(_, "character")) => party.push(self.parse_character()?),
+                Some(&(_, "recruit")) => roster.push(self.parse_character()?),
+                Some(&(_, "level")) => floors.push(self.parse_level()?),
+                Some(&(column, word)) => {
+                    return Err(malformed(line, column, format!("unknown key `{}`", word)));
+                }
+            }
+            self.line += 1;
+        }
+
+        let missing = |key: &str| malformed(self.line, 1, format!("missing `{}`", key));
+        let floor: usize = floor.ok_or_else(|| missing("floor"))?;
+        let pos = pos.ok_or_else(|| missing("pos"))?;
+        let dungeon = Dungeon { floors };
+        let Some(level) = dungeon.floors.get(floor) else {
+            return Err(malformed(self.line, 1, format!("there is no floor {}", floor)));
+        };
+        if !level.contains(pos.x as isize, pos.y as isize) {
+            return Err(malformed(self.line, 1, "position outside the level"));
+        }
+        // generators missing from the save start from the seed
+        let mut rng = Rngs::new(seed.ok_or_else(|| missing("seed"))?);
+        for (name, stream) in streams {
+            if let Some(rng) = rng.get_mut(&name) {
+                *rng = stream;
+            }
+        }
+        let mut state = State {
+            dungeon,
+            floor,
+            pos,
+            dir: dir.ok_or_else(|| missing("dir"))?,
+            party,
+            roster,
+            inventory: Vec::new(),
+            rng,
+            encounter_steps: encounter_steps.ok_or_else(|| missing("steps"))?,
+            ward_steps: ward_steps.ok_or_else(|| missing("ward"))?,
+            light_steps: light_steps.ok_or_else(|| missing("light"))?,
+            vars
+        };
+        for item in items {
+            state.add_item(item);
+        }
+        Ok(state)
+    }
+}
+
+const STAT_KEYS: [&str; 6] = ["atk", "def", "matk", "mdef", "agi", "luck"];
+
+impl State {
+    /// Reads a save, looking up the items in it in `items`.
+    pub fn from_save(src: &str, items: &ItemCatalog) -> Result<State, SaveError> {
+        let mut lines = src.lines().map(str::to_string).collect::<Vec<_>>();
+        let header = lines.first().map(|line| words(line)).unwrap_or_default();
+        let version = match header[..] {
+            [(_, SAVE_MAGIC), (column, version)] => version.parse::<u32>()
+                .map_err(|_| malformed(0, column, "invalid version"))?,
+            _ => return Err(malformed(0, 1, "not a save file"))
+        };
+        if version == 0 || version > SAVE_VERSION {
+            return Err(SaveError::IncompatibleVersion(version));
+        }
+        lines.remove(0);
+        for migration in &MIGRATIONS[version as usize - 1..] {
+            migration(&mut lines);
+        }
+        // keep line numbers in errors pointing at the right line
+        lines.insert(0, String::new());
+        SaveParser { lines, line: 1, items }.parse()
+    }
+}
+
+fn write_character(
+    f: &mut Formatter<'_>,
+    keyword: &str,
+    character: &Character
+) -> fmt::Result {
+    writeln!(f, "{}", keyword)?;
+    writeln!(f, "name {}", character.name)?;
+    writeln!(f, "class {}", character.class.name())?;
+    writeln!(f, "lvl {}", character.lvl)?;
+    writeln!(f, "xp {}", character.xp)?;
+    writeln!(f, "hp {} {}", character.hp, character.max_hp)?;
+    writeln!(f, "mp {} {}", character.mp, character.max_mp)?;
+    for (key, value) in STAT_KEYS.iter().zip([
+        character.base_atk,
+        character.base_def,
+        character.base_matk,
+        character.base_mdef,
+        character.base_agi,
+        character.base_luck
+    ]) {
+        writeln!(f, "{} {}", key, value)?;
+    }
+    for slot in ItemSlot::ALL {
+        if let Some(item) = character.item(*slot) {
+            writeln!(f, "{} {}", slot.key(), item.name())?;
+        }
+    }
+    for effect in character.statuses.iter() {
+        match effect.left {
+            Some(left) => writeln!(f, "status {} {}", effect.status.key(), left)?,
+            None => writeln!(f, "status {}", effect.status.key())?
+        }
+    }
+    writeln!(f, "end")?;
+    Ok(())
+}
+
+impl Display for State {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} {}", SAVE_MAGIC, SAVE_VERSION)?;
+        writeln!(f, "floor {}", self.floor)?;
+        writeln!(f, "pos {} {}", self.pos.x, self.pos.y)?;
+        writeln!(f, "dir {}", dir_name(self.dir))?;
+        writeln!(f, "seed {}", self.rng.seed)?;
+        for name in Rngs::NAMES {
+            if let Some(rng) = self.rng.get(name) {
+                writeln!(f, "rng {} {}", name, rng.state)?;
+            }
+        }
+        writeln!(f, "steps {}", self.encounter_steps)?;
+        writeln!(f, "ward {}", self.ward_steps)?;
+        writeln!(f, "light {}", self.light_steps)?;
+        for (var, value) in self.vars.iter() {
+            writeln!(f, "var {} {}", var, value)?;
+        }
+        for stack in self.inventory.iter() {
+            for _ in 0..stack.count {
+                writeln!(f, "item {}", stack.item.name())?;
+            }
+        }
+        for character in self.party.iter() {
+            write_character(f, "character", character)?;
+        }
+        for character in self.roster.iter() {
+            write_character(f, "recruit", character)?;
+        }
+        for level in self.dungeon.floors.iter() {
+            writeln!(f, "level")?;
+            write!(f, "{}", level)?;
+            writeln!(f, "end")?;
+        }
+        Ok(())
+    }
+}
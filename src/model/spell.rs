@@ -0,0 +1,261 @@
+use crate::model::{
+    *,
+    CharacterClass::*,
+    Spell::*
+};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Spell {
+    Heal,
+    Light,
+    Guard,
+    Cure,
+    Ward,
+    Bless,
+    Restore,
+    Spark,
+    Reveal,
+    Flame
+}
+
+/// Who a spell affects, which decides what has to be chosen when casting it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SpellTarget {
+    Ally,
+    AllAllies,
+    Enemy,
+    /// All enemies of the same kind as the chosen one.
+    EnemyGroup,
+    Caster
+}
+
+/// Steps that `Light` lasts for.
+pub const LIGHT_STEPS: u32 = 60;
+/// Steps that `Ward` keeps random encounters away for.
+pub const WARD_STEPS: u32 = 40;
+/// How much `Bless` raises ATK by, in percent.
+pub const BLESS_PERCENT: i32 = 25;
+
+impl Spell {
+    pub const ALL: &'static [Spell] = &[
+        Heal,
+        Light,
+        Guard,
+        Cure,
+        Ward,
+        Bless,
+        Restore,
+        Spark,
+        Reveal,
+        Flame
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Heal => "HEAL",
+            Light => "LIGHT",
+            Guard => "GUARD",
+            Cure => "CURE",
+            Ward => "WARD",
+            Bless => "BLESS",
+            Restore => "RESTORE",
+            Spark => "SPARK",
+            Reveal => "REVEAL",
+            Flame => "FLAME"
+        }
+    }
+
+    /// The class that can learn the spell.
+    pub fn class(&self) -> CharacterClass {
+        match self {
+            Heal | Light | Guard | Cure | Ward | Bless | Restore => Priest,
+            Spark | Reveal | Flame => Magician
+        }
+    }
+
+    /// The level at which the spell is learned.
+    pub fn lvl(&self) -> u32 {
+        match self {
+            Heal | Light | Spark => 1,
+            Guard | Cure | Reveal => 2,
+            Ward | Bless | Flame => 3,
+            Restore => 4
+        }
+    }
+
+    pub fn mp_cost(&self) -> u32 {
+        match self {
+            Light | Spark => 1,
+            Heal | Guard | Cure => 2,
+            Ward | Bless | Reveal | Flame => 3,
+            Restore => 5
+        }
+    }
+
+    pub fn target(&self) -> SpellTarget {
+        match self {
+            Heal | Cure => SpellTarget::Ally,
+            Light | Ward | Bless | Restore | Reveal => SpellTarget::AllAllies,
+            Guard => SpellTarget::Caster,
+            Spark => SpellTarget::Enemy,
+            Flame => SpellTarget::EnemyGroup
+        }
+    }
+
+    pub fn in_battle(&self) -> bool {
+        matches!(self, Heal | Guard | Cure | Bless | Restore | Spark | Flame)
+    }
+
+    pub fn in_explore(&self) -> bool {
+        matches!(self, Heal | Light | Cure | Ward | Restore | Reveal)
+    }
+
+    pub fn desc(&self) -> &'static [&'static str] {
+        match self {
+            Heal => &["HEALS ONE ALLY."],
+            Light => &["LIGHTS UP THE CELLS", "AROUND THE PARTY."],
+            Guard => &["HALVES THE DAMAGE THE", "CASTER TAKES THIS TURN."],
+            Cure => &["CURES POISON, SLEEP AND", "PARALYSIS."],
+            Ward => &["KEEPS MONSTERS AWAY", "FOR A WHILE."],
+            Bless => &["RAISES THE ATK OF THE", "PARTY UNTIL THE BATTLE", "IS OVER."],
+            Restore => &["HEALS THE WHOLE PARTY."],
+            Spark => &["STRIKES ONE ENEMY."],
+            Reveal => &["MAPS THE WHOLE FLOOR."],
+            Flame => &["BURNS ALL ENEMIES OF", "ONE KIND."]
+        }
+    }
+}
+
+impl Character {
+    /// The spells the character has learned, which depend on class and level.
+    pub fn spells(&self) -> Vec<Spell> {
+        Spell::ALL.iter().copied()
+            .filter(|spell| spell.class() == self.class && spell.lvl() <= self.lvl)
+            .collect()
+    }
+
+    pub fn can_cast(&self, spell: Spell) -> bool {
+        self.can_act()
+            && self.mp >= spell.mp_cost()
+            && spell.class() == self.class
+            && spell.lvl() <= self.lvl
+    }
+
+    /// Restores `amount` HP, unless the character is dead.
+    pub fn heal(&mut self, amount: u32) {
+        if self.is_alive() {
+            self.hp = (self.hp + amount).min(self.max_hp);
+        }
+    }
+}
+
+impl State {
+    /// Casts `spell` by party member `caster`, spending its MP. `target` is
+    /// an index into the party or into `enemies`, depending on the target of
+    /// the spell, and is ignored for spells without a choice of target. The
+    /// MP is spent even if the spell fizzles in an anti-magic zone. Returns
+    /// lines describing what happened.
+    pub fn cast_spell(
+        &mut self,
+        caster: usize,
+        spell: Spell,
+        target: usize,
+        enemies: &mut [Enemy]
+    ) -> Vec<String> {
+        let character = &mut self.party[caster];
+        character.mp = character.mp.saturating_sub(spell.mp_cost());
+        let matk = character.matk();
+        let mut messages = vec![format!("{} CASTS {}!", character.name, spell.name())];
+        if self.magic_blocked() {
+            messages.push("BUT THE SPELL FIZZLES.".to_string());
+            return messages;
+        }
+        match spell {
+            Heal | Restore => {
+                let targets = match spell {
+                    Heal => vec![target],
+                    _ => (0..self.party.len()).collect()
+                };
+                for i in targets {
+                    let Some(character) = self.party.get_mut(i) else {
+                        continue;
+                    };
+                    if character.is_alive() {
+                        let before = character.hp;
+                        character.heal(healing(matk));
+                        messages.push(format!(
+                            "{} RECOVERS {} HP.",
+                            character.name,
+                            character.hp - before
+                        ));
+                    }
+                }
+            },
+            Cure => {
+                let Some(character) = self.party.get_mut(target) else {
+                    return messages;
+                };
+                let before = messages.len();
+                for &status in Status::ALL.iter().filter(|status| status.curable()) {
+                    if character.cure(status) {
+                        messages.push(format!("{} {}", character.name, status.cured_text()));
+                    }
+                }
+                if messages.len() == before {
+                    messages.push("NOTHING HAPPENS.".to_string());
+                }
+            },
+            Light => {
+                self.light_steps = self.light_steps.max(LIGHT_STEPS);
+                messages.push("A SOFT LIGHT SHINES.".to_string());
+            },
+            Guard => {
+                messages.push(format!("{} IS SHIELDED.", self.party[caster].name));
+            },
+            Ward => {
+                self.ward_encounters(WARD_STEPS);
+                messages.push("MONSTERS WILL KEEP AWAY.".to_string());
+            },
+            Bless => {
+                for character in self.party.iter_mut().filter(|c| c.is_alive()) {
+                    character.buffs.retain(|buff| buff.spell != Bless);
+                    character.buffs.push(Buff {
+                        spell: Bless,
+                        stat: Stat::Atk,
+                        modifier: Modifier::Percent(BLESS_PERCENT)
+                    });
+                }
+                messages.push("THE PARTY FEELS STRONGER.".to_string());
+            },
+            Reveal => {
+                self.level_mut().automap.reveal_all();
+                messages.push("THE FLOOR IS REVEALED.".to_string());
+            },
+            Spark | Flame => {
+                let Some(monster) = enemies.get(target).map(|enemy| enemy.monster) else {
+                    return messages;
+                };
+                for (k, enemy) in enemies.iter_mut().enumerate() {
+                    let hit = match spell {
+                        Spark => k == target,
+                        _ => enemy.monster == monster
+                    };
+                    if !hit || !enemy.is_alive() {
+                        continue;
+                    }
+                    let damage = magic_damage(matk, enemy.monster.mdef(), false);
+                    enemy.take_damage(damage);
+                    messages.push(format!(
+                        "{} TAKES {} DAMAGE.",
+                        enemy.monster.name(),
+                        damage
+                    ));
+                    if !enemy.is_alive() {
+                        messages.push(format!("{} IS SLAIN.", enemy.monster.name()));
+                    }
+                }
+            }
+        }
+        messages
+    }
+}
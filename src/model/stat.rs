@@ -0,0 +1,178 @@
+use crate::model::{
+    *,
+    Stat::*
+};
+
+/// The stats of a character that equipment and other effects can modify.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Stat {
+    Atk,
+    Def,
+    Matk,
+    Mdef,
+    Agi,
+    Luck
+}
+
+impl Stat {
+    pub const ALL: &'static [Stat] = &[
+        Atk,
+        Def,
+        Matk,
+        Mdef,
+        Agi,
+        Luck
+    ];
+
+    /// The abbreviation shown on screen.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Atk => "ATK",
+            Def => "DEF",
+            Matk => "MAG",
+            Mdef => "RES",
+            Agi => "AGI",
+            Luck => "LUC"
+        }
+    }
+
+    /// The key used in data files.
+    pub fn key(&self) -> &'static str {
+        match self {
+            Atk => "atk",
+            Def => "def",
+            Matk => "matk",
+            Mdef => "mdef",
+            Agi => "agi",
+            Luck => "luck"
+        }
+    }
+}
+
+/// A change to a stat. All additions are applied before any percentages.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Modifier {
+    Add(i32),
+    Percent(i32)
+}
+
+impl Modifier {
+    /// The modifier as shown on screen and written in data files, e.g. `+3`
+    /// or `-10%`.
+    pub fn text(&self) -> String {
+        match self {
+            Modifier::Add(value) => format!("{:+}", value),
+            Modifier::Percent(percent) => format!("{:+}%", percent)
+        }
+    }
+
+    pub fn parse(word: &str) -> Option<Modifier> {
+        match word.strip_suffix('%') {
+            Some(percent) => percent.parse().ok().map(Modifier::Percent),
+            None => word.parse().ok().map(Modifier::Add)
+        }
+    }
+}
+
+/// What a modifier to a character's stat comes from.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ModSource {
+    Class(CharacterClass),
+    Item(Item),
+    Spell(Spell),
+    Status(Status)
+}
+
+impl ModSource {
+    pub fn name(&self) -> &str {
+        match self {
+            ModSource::Class(class) => class.name(),
+            ModSource::Item(item) => item.name(),
+            ModSource::Spell(spell) => spell.name(),
+            ModSource::Status(status) => status.name()
+        }
+    }
+}
+
+/// A spell's effect on a stat, which lasts until the end of the battle.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Buff {
+    pub spell: Spell,
+    pub stat: Stat,
+    pub modifier: Modifier
+}
+
+/// Applies `modifiers` to `base`, never going below 0.
+pub fn apply_modifiers(base: u32, modifiers: impl IntoIterator<Item = Modifier>) -> u32 {
+    let (mut add, mut percent) = (0i64, 100i64);
+    for modifier in modifiers {
+        match modifier {
+            Modifier::Add(value) => add += value as i64,
+            Modifier::Percent(value) => percent += value as i64
+        }
+    }
+    ((base as i64 + add).max(0) * percent.max(0) / 100) as u32
+}
+
+impl Character {
+    pub fn base_stat(&self, stat: Stat) -> u32 {
+        match stat {
+            Atk => self.base_atk,
+            Def => self.base_def,
+            Matk => self.base_matk,
+            Mdef => self.base_mdef,
+            Agi => self.base_agi,
+            Luck => self.base_luck
+        }
+    }
+
+    /// Everything that modifies `stat` and where it comes from: the class,
+    /// then equipment, then spells, then statuses.
+    pub fn modifiers(&self, stat: Stat) -> Vec<(ModSource, Modifier)> {
+        let mut modifiers = Vec::new();
+        for &(s, modifier) in self.class.passives() {
+            if s == stat {
+                modifiers.push((ModSource::Class(self.class), modifier));
+            }
+        }
+        for item in self.equipment() {
+            for &(s, modifier) in item.mods.iter() {
+                if s == stat {
+                    modifiers.push((ModSource::Item(item.clone()), modifier));
+                }
+            }
+        }
+        for buff in self.buffs.iter() {
+            if buff.stat == stat {
+                modifiers.push((ModSource::Spell(buff.spell), buff.modifier));
+            }
+        }
+        for effect in self.statuses.iter() {
+            for &(s, modifier) in effect.status.mods() {
+                if s == stat {
+                    modifiers.push((ModSource::Status(effect.status), modifier));
+                }
+            }
+        }
+        modifiers
+    }
+
+    /// The value of `stat` with all modifiers applied.
+    pub fn stat(&self, stat: Stat) -> u32 {
+        apply_modifiers(
+            self.base_stat(stat),
+            self.modifiers(stat).into_iter().map(|(_, modifier)| modifier)
+        )
+    }
+}
+
+impl State {
+    /// Removes what only lasts for a battle from the party, waking up those
+    /// asleep.
+    pub fn end_battle(&mut self) {
+        for character in self.party.iter_mut() {
+            character.buffs.clear();
+            character.cure(Status::Sleep);
+        }
+    }
+}
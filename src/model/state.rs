@@ -1,10 +1,48 @@
+use ::std::collections::BTreeMap;
 use crate::model::*;
 
+/// The most characters that can go into the dungeon together.
+pub const MAX_PARTY_SIZE: usize = 6;
+
 #[derive(Clone, Debug)]
 pub struct State {
-    pub level: Level,
+    pub dungeon: Dungeon,
+    pub floor: usize,
     pub pos: Position,
     pub dir: Direction,
     pub party: Vec<Character>,
-    pub inventory: Vec<Item>
+    /// Characters waiting in the guild.
+    pub roster: Vec<Character>,
+    pub inventory: Vec<ItemStack>,
+    pub rng: Rngs,
+    /// Steps taken since the last random encounter.
+    pub encounter_steps: u32,
+    /// Steps left during which no random encounters happen.
+    pub ward_steps: u32,
+    /// Steps left until the `Light` spell wears off.
+    pub light_steps: u32,
+    /// Story flags and variables set by level events, by name.
+    pub vars: BTreeMap<String, Value>
+}
+
+impl State {
+    pub fn level(&self) -> &Level {
+        &self.dungeon.floors[self.floor]
+    }
+
+    pub fn level_mut(&mut self) -> &mut Level {
+        &mut self.dungeon.floors[self.floor]
+    }
+
+    /// Whether the party stands where it entered the dungeon, next to the
+    /// guild.
+    pub fn at_entrance(&self) -> bool {
+        self.floor == 0 && self.pos == self.level().start
+    }
+
+    /// Keeps random encounters away for the next `steps` steps, for items
+    /// and spells that ward off monsters.
+    pub fn ward_encounters(&mut self, steps: u32) {
+        self.ward_steps = self.ward_steps.max(steps);
+    }
 }
@@ -0,0 +1,221 @@
+use crate::model::{
+    *,
+    Status::*
+};
+
+/// Conditions that afflict characters, from the mildest to the worst.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Status {
+    Poison,
+    Sleep,
+    Paralysis,
+    Stone,
+    Dead
+}
+
+/// A status condition on a character and how long it lasts.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct StatusEffect {
+    pub status: Status,
+    /// Steps or battle turns left, see `Status::counts_turns`. `None` if it
+    /// lasts until cured.
+    pub left: Option<u32>
+}
+
+/// HP that poison drains per step. It never kills on its own.
+pub const POISON_DAMAGE: u32 = 1;
+/// Steps that paralysis lasts for.
+pub const PARALYSIS_STEPS: u32 = 40;
+/// The most battle turns that sleep lasts for.
+pub const MAX_SLEEP_TURNS: u32 = 3;
+
+impl Status {
+    pub const ALL: &'static [Status] = &[
+        Poison,
+        Sleep,
+        Paralysis,
+        Stone,
+        Dead
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Poison => "POISON",
+            Sleep => "SLEEP",
+            Paralysis => "PARALYSIS",
+            Stone => "STONE",
+            Dead => "DEAD"
+        }
+    }
+
+    /// Short name for the party list.
+    pub fn abbrev(&self) -> &'static str {
+        match self {
+            Poison => "PSN",
+            Sleep => "SLP",
+            Paralysis => "PAR",
+            Stone => "STN",
+            Dead => "DEAD"
+        }
+    }
+
+    /// Name in item and save files.
+    pub fn key(&self) -> &'static str {
+        match self {
+            Poison => "poison",
+            Sleep => "sleep",
+            Paralysis => "paralysis",
+            Stone => "stone",
+            Dead => "dead"
+        }
+    }
+
+    /// Whether the duration counts battle turns rather than steps.
+    pub fn counts_turns(&self) -> bool {
+        *self == Sleep
+    }
+
+    /// Whether the status keeps a character from acting.
+    pub fn incapacitates(&self) -> bool {
+        *self != Poison
+    }
+
+    /// Whether the `Cure` spell can cure the status.
+    pub fn curable(&self) -> bool {
+        matches!(self, Poison | Sleep | Paralysis)
+    }
+
+    /// How the status changes the stats of those who have it.
+    pub fn mods(&self) -> &'static [(Stat, Modifier)] {
+        match self {
+            Poison => &[(Stat::Agi, Modifier::Percent(-20))],
+            Paralysis => &[(Stat::Agi, Modifier::Percent(-50))],
+            Sleep | Stone | Dead => &[]
+        }
+    }
+
+    /// What is said when a character comes down with the status.
+    pub fn inflicted_text(&self) -> &'static str {
+        match self {
+            Poison => "IS POISONED.",
+            Sleep => "FALLS ASLEEP.",
+            Paralysis => "IS PARALYZED.",
+            Stone => "TURNS TO STONE.",
+            Dead => "FALLS."
+        }
+    }
+
+    /// What is said when a character is cured of the status.
+    pub fn cured_text(&self) -> &'static str {
+        match self {
+            Poison => "FEELS BETTER.",
+            Sleep => "WAKES UP.",
+            Paralysis => "CAN MOVE AGAIN.",
+            Stone => "RETURNS TO FLESH.",
+            Dead => "LIVES AGAIN."
+        }
+    }
+}
+
+impl Character {
+    pub fn has(&self, status: Status) -> bool {
+        self.statuses.iter().any(|effect| effect.status == status)
+    }
+
+    /// The worst status the character has.
+    pub fn worst_status(&self) -> Option<Status> {
+        self.statuses.iter().map(|effect| effect.status).max()
+    }
+
+    /// Whether the character can take actions and cast spells.
+    pub fn can_act(&self) -> bool {
+        !self.statuses.iter().any(|effect| effect.status.incapacitates())
+    }
+
+    /// Whether the character is out of a fight for good, unlike a sleeping
+    /// one who wakes up again.
+    pub fn is_disabled(&self) -> bool {
+        self.has(Dead) || self.has(Stone) || self.has(Paralysis)
+    }
+
+    /// Afflicts the character with `status` for `left` steps or turns, or
+    /// until cured if `None`. Returns `false` if nothing changed because the
+    /// character is dead or already has the status.
+    pub fn inflict(&mut self, status: Status, left: Option<u32>) -> bool {
+        if self.has(Dead) || self.has(status) {
+            return false;
+        }
+        if status == Dead {
+            self.hp = 0;
+            self.statuses.clear();
+        }
+        self.statuses.push(StatusEffect { status, left });
+        true
+    }
+
+    /// Cures `status`, bringing the dead back with 1 HP. Returns `false` if
+    /// the character did not have it.
+    pub fn cure(&mut self, status: Status) -> bool {
+        if !self.has(status) {
+            return false;
+        }
+        self.statuses.retain(|effect| effect.status != status);
+        if status == Dead {
+            self.hp = 1;
+        }
+        true
+    }
+
+    /// Counts down statuses measured in turns or, if `turns` is `false`,
+    /// in steps, curing those that run out.
+    fn count_down(&mut self, turns: bool) {
+        for effect in self.statuses.iter_mut() {
+            if effect.status.counts_turns() == turns {
+                if let Some(left) = &mut effect.left {
+                    *left = left.saturating_sub(1);
+                }
+            }
+        }
+        self.statuses.retain(|effect| effect.left != Some(0));
+    }
+
+    /// Counts a step, draining HP if the character is poisoned.
+    pub fn step_statuses(&mut self) {
+        if self.has(Poison) && self.is_alive() {
+            self.hp = self.hp.saturating_sub(POISON_DAMAGE).max(1);
+        }
+        self.count_down(false);
+    }
+
+    /// Counts a battle turn.
+    pub fn turn_statuses(&mut self) {
+        self.count_down(true);
+    }
+}
+
+impl Monster {
+    /// The status the monster's attacks can inflict and the chance of it in
+    /// percent.
+    pub fn inflicts(&self) -> Option<(Status, u32)> {
+        match self {
+            Monster::GiantRat => Some((Poison, 30)),
+            Monster::Imp => Some((Sleep, 30)),
+            Monster::Slime => Some((Paralysis, 10)),
+            Monster::Basilisk => Some((Stone, 10)),
+            Monster::Kobold => None
+        }
+    }
+}
+
+impl State {
+    /// Afflicts party member `i` with `status` for a random duration.
+    /// Returns `false` if nothing changed.
+    pub fn inflict(&mut self, i: usize, status: Status) -> bool {
+        let left = match status {
+            Sleep => Some(1 + self.rng.combat.below(MAX_SLEEP_TURNS)),
+            Paralysis => Some(PARALYSIS_STEPS),
+            Poison | Stone | Dead => None
+        };
+        self.party[i].inflict(status, left)
+    }
+}
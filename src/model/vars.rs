@@ -0,0 +1,158 @@
+use ::std::fmt::{
+    self,
+    Display,
+    Formatter
+};
+use crate::model::*;
+
+/// The value of a story variable. Flags are variables set to `true`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    Int(i64),
+    Str(String)
+}
+
+/// A test of a story variable, for events and walls that depend on the
+/// progress of the story.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Condition {
+    pub var: String,
+    pub test: Test
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Test {
+    /// The variable is set to something other than `false`, 0 or an empty
+    /// string.
+    IsSet,
+    IsUnset,
+    Eq(Value),
+    Ne(Value),
+    Lt(i64),
+    Le(i64),
+    Gt(i64),
+    Ge(i64)
+}
+
+impl Value {
+    /// Reads `true` and `false` as booleans, numbers as integers and anything
+    /// else as a string. Text in double quotes is always a string, for
+    /// strings like `"42"`, `"true"` or `""`.
+    pub fn parse(text: &str) -> Value {
+        if let Some(s) = text.strip_prefix('"').and_then(|text| text.strip_suffix('"')) {
+            return Value::Str(s.to_string());
+        }
+        match text {
+            "true" => Value::Bool(true),
+            "false" => Value::Bool(false),
+            _ => match text.parse() {
+                Ok(n) => Value::Int(n),
+                Err(_) => Value::Str(text.to_string())
+            }
+        }
+    }
+
+    pub fn is_set(&self) -> bool {
+        match self {
+            Value::Bool(b) => *b,
+            Value::Int(n) => *n != 0,
+            Value::Str(s) => !s.is_empty()
+        }
+    }
+}
+
+/// Writes strings in double quotes, so that `Value::parse` reads them back
+/// as strings.
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Int(n) => write!(f, "{}", n),
+            Value::Str(s) => write!(f, "\"{}\"", s)
+        }
+    }
+}
+
+impl Test {
+    /// The operator between variable and operand in files, empty for
+    /// `IsSet` and `IsUnset`.
+    pub fn op(&self) -> &'static str {
+        match self {
+            Test::IsSet | Test::IsUnset => "",
+            Test::Eq(_) => "=",
+            Test::Ne(_) => "!=",
+            Test::Lt(_) => "<",
+            Test::Le(_) => "<=",
+            Test::Gt(_) => ">",
+            Test::Ge(_) => ">="
+        }
+    }
+}
+
+/// Writes the condition the way it follows `if` in files.
+impl Display for Condition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.test {
+            Test::IsSet => write!(f, "{}", self.var),
+            Test::IsUnset => write!(f, "not {}", self.var),
+            Test::Eq(value) | Test::Ne(value) => {
+                write!(f, "{} {} {}", self.var, self.test.op(), value)
+            },
+            Test::Lt(n) | Test::Le(n) | Test::Gt(n) | Test::Ge(n) => {
+                write!(f, "{} {} {}", self.var, self.test.op(), n)
+            }
+        }
+    }
+}
+
+impl State {
+    pub fn var(&self, name: &str) -> Option<&Value> {
+        self.vars.get(name)
+    }
+
+    pub fn set_var(&mut self, name: &str, value: Value) {
+        self.vars.insert(name.to_string(), value);
+    }
+
+    /// Whether the flag or variable `name` is set, see `Test::IsSet`.
+    pub fn flag(&self, name: &str) -> bool {
+        self.var(name).is_some_and(Value::is_set)
+    }
+
+    /// Whether `condition` holds. Comparing a variable that is not a number
+    /// with a number fails, unless it is unset, which counts as 0.
+    pub fn holds(&self, condition: &Condition) -> bool {
+        let value = self.var(&condition.var);
+        let n = match value {
+            Some(Value::Int(n)) => Some(*n),
+            None => Some(0),
+            _ => None
+        };
+        match &condition.test {
+            Test::IsSet => self.flag(&condition.var),
+            Test::IsUnset => !self.flag(&condition.var),
+            Test::Eq(operand) => value == Some(operand),
+            Test::Ne(operand) => value != Some(operand),
+            Test::Lt(m) => n.is_some_and(|n| n < *m),
+            Test::Le(m) => n.is_some_and(|n| n <= *m),
+            Test::Gt(m) => n.is_some_and(|n| n > *m),
+            Test::Ge(m) => n.is_some_and(|n| n >= *m)
+        }
+    }
+
+    /// Changes the walls waiting for a condition that now holds, on every
+    /// floor.
+    pub fn change_walls(&mut self) {
+        for floor in 0..self.dungeon.floors.len() {
+            let changes = self.dungeon.floors[floor].wall_changes.clone();
+            let (now, later): (Vec<_>, Vec<_>) = changes.into_iter()
+                .partition(|change| self.holds(&change.condition));
+            let level = &mut self.dungeon.floors[floor];
+            for change in now {
+                *level.wall_towards_mut(change.pos, change.dir) = change.wall;
+            }
+            level.wall_changes = later;
+        }
+    }
+}
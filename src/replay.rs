@@ -0,0 +1,198 @@
+//! Text format for replays, to attach to bug reports and to play back in
+//! tests.
+//!
+//! The first line identifies the file and its version, e.g.
+//! `blackbox-replay 2`. It is followed by the seed the game was started
+//! with as `seed N`, for starting it the same way again, the updates the
+//! recording lasted as `updates N` and one `input N INPUT` line per input,
+//! where `N` is the number of updates that ran before it and `INPUT` is
+//! written as described in `input`. Keys and buttons are recorded as the
+//! actions they were bound to, so that replays play back the same whatever
+//! the bindings. A line `state` ends the inputs, and the rest of the file
+//! is the game the replay starts from, in the format of a save file:
+//!
+//! ```text
+//! blackbox-replay 2
+//! seed 1792328621351749365
+//! updates 95
+//! input 0 action confirm
+//! input 12 action cancel
+//! input 40 action up
+//! input 40 action forward
+//! state
+//! blackbox-save 10
+//! ...
+//! ```
+
+use ::ggez::*;
+use ::std::{
+    collections::VecDeque,
+    fmt::{
+        self,
+        Display,
+        Formatter
+    }
+};
+use crate::{
+    *,
+    model::*
+};
+
+pub const REPLAY_MAGIC: &str = "blackbox-replay";
+pub const REPLAY_VERSION: u32 = 2;
+
+/// Everything the player did from the start of a game, to play it back
+/// exactly. Replays only play back the same if any save loaded along the
+/// way is still the same.
+#[derive(Clone, Debug)]
+pub struct Replay {
+    pub seed: u64,
+    /// The updates that ran while recording, each `TIME_STEP` long.
+    pub updates: u64,
+    /// Actions done, keys and buttons pressed while a scene captured them
+    /// and characters typed, with the number of updates that ran before
+    /// each.
+    pub inputs: Vec<(u64, Input)>,
+    /// The game at the start.
+    pub state: State
+}
+
+/// Feeds the inputs of a replay to a game, each before the update it was
+/// recorded at.
+#[derive(Clone, Debug)]
+pub struct Playback {
+    inputs: VecDeque<(u64, Input)>,
+    pub updates: u64
+}
+
+impl Replay {
+    /// Starts recording from `state`.
+    pub fn new(state: &State) -> Self {
+        Replay {
+            seed: state.rng.seed,
+            updates: 0,
+            inputs: Vec::new(),
+            state: state.clone()
+        }
+    }
+
+    /// Reads a replay, looking up the items of the game it starts from in
+    /// `items`. Errors are reported like those of saves, with lines counted
+    /// from the start of the replay.
+    pub fn parse(src: &str, items: &ItemCatalog) -> Result<Replay, SaveError> {
+        let malformed = |line: usize, column: usize, message: &str| SaveError::Malformed {
+            line: line + 1,
+            column,
+            message: message.to_string()
+        };
+        let lines = src.lines().collect::<Vec<_>>();
+        match lines.first().map(|line| line.split_whitespace().collect::<Vec<_>>()) {
+            Some(words) if words.len() == 2 && words[0] == REPLAY_MAGIC => {
+                let version = words[1].parse::<u32>()
+                    .map_err(|_| malformed(0, REPLAY_MAGIC.len() + 2, "invalid version"))?;
+                if version == 0 || version > REPLAY_VERSION {
+                    return Err(SaveError::IncompatibleVersion(version));
+                }
+            },
+            _ => return Err(malformed(0, 1, "not a replay file"))
+        }
+        let mut seed = None;
+        let mut updates = None;
+        let mut inputs = Vec::new();
+        let mut i = 1;
+        loop {
+            let Some(line) = lines.get(i) else {
+                return Err(malformed(i, 1, "expected `state`"));
+            };
+            let (keyword, rest) = line.split_once(' ').unwrap_or((line, ""));
+            let column = keyword.len() + 2;
+            match keyword {
+                "seed" => seed = Some(rest.trim().parse::<u64>()
+                    .map_err(|_| malformed(i, column, "expected seed"))?),
+                "updates" => updates = Some(rest.trim().parse::<u64>()
+                    .map_err(|_| malformed(i, column, "expected number of updates"))?),
+                "input" => {
+                    let (at, input) = rest.split_once(' ').unwrap_or((rest, ""));
+                    let at = at.parse::<u64>()
+                        .map_err(|_| malformed(i, column, "expected number of updates"))?;
+                    let column = column + at.to_string().len() + 1;
+                    match input.parse::<Input>() {
+                        Ok(Input::Wait(_)) => {
+                            let message = "expected action, key, button or text";
+                            return Err(malformed(i, column, message));
+                        },
+                        Ok(input) => inputs.push((at, input)),
+                        Err(message) => return Err(malformed(i, column, &message))
+                    }
+                },
+                "state" => break,
+                "" => {},
+                _ => return Err(malformed(i, 1, &format!("unknown keyword `{}`", keyword)))
+            }
+            i += 1;
+        }
+        let state = State::from_save(&lines[i + 1..].join("\n"), items).map_err(|err| match err {
+            SaveError::Malformed { line, column, message } => SaveError::Malformed {
+                line: line + i + 1,
+                column,
+                message
+            },
+            err => err
+        })?;
+        Ok(Replay {
+            seed: seed.ok_or_else(|| malformed(i, 1, "missing `seed`"))?,
+            updates: updates.ok_or_else(|| malformed(i, 1, "missing `updates`"))?,
+            inputs,
+            state
+        })
+    }
+}
+
+impl Display for Replay {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} {}", REPLAY_MAGIC, REPLAY_VERSION)?;
+        writeln!(f, "seed {}", self.seed)?;
+        writeln!(f, "updates {}", self.updates)?;
+        for (at, input) in self.inputs.iter() {
+            writeln!(f, "input {} {}", at, input)?;
+        }
+        writeln!(f, "state")?;
+        write!(f, "{}", self.state)
+    }
+}
+
+impl Playback {
+    pub fn new(replay: &Replay) -> Self {
+        Playback {
+            inputs: replay.inputs.iter().copied().collect(),
+            updates: replay.updates
+        }
+    }
+
+    /// Feeds `game` the inputs recorded before the update it is about to
+    /// run.
+    pub fn feed(&mut self, game: &mut Game) -> GameResult {
+        while let Some(&(at, input)) = self.inputs.front() {
+            if at > game.updates {
+                break;
+            }
+            self.inputs.pop_front();
+            match input {
+                Input::Key(code) => game.press(Control::Key(code))?,
+                Input::Button(button) => game.press(Control::Button(button))?,
+                Input::Action(action) => {
+                    game.action(action)?;
+                },
+                Input::Text(character) => game.text_input(character)?,
+                Input::Wait(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether every input was fed and as many updates ran as while
+    /// recording.
+    pub fn is_finished(&self, game: &Game) -> bool {
+        self.inputs.is_empty() && game.updates >= self.updates
+    }
+}
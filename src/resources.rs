@@ -1,10 +1,30 @@
 use ::ggez::*;
-use ::std::include_bytes;
-use crate::drawing::*;
+use ::std::{
+    fs,
+    include_bytes,
+    path::Path,
+    str::from_utf8
+};
+use crate::{
+    drawing::*,
+    model::*
+};
 
 const FONT_REGULAR: &[u8] = include_bytes!("../res/font/font_regular.png");
 const FONT_BOLD: &[u8] = include_bytes!("../res/font/font_bold.png");
 
+/// Directory searched for level files before falling back to `LEVELS`.
+pub const LEVEL_DIR: &str = "res/levels";
+const LEVELS: &[(&str, &[u8])] = &[
+    ("entrance", include_bytes!("../res/levels/entrance.lvl")),
+    ("cellar", include_bytes!("../res/levels/cellar.lvl"))
+];
+/// Item catalog read in place of the embedded `ITEMS` if present.
+pub const ITEM_FILE: &str = "res/items.itm";
+const ITEMS: &[u8] = include_bytes!("../res/items.itm");
+
+/// What drawing the game takes. The data the game runs on is loaded by the
+/// functions below instead, since loading it needs no `Context`.
 pub struct Resources {
     pub font_regular: BitmapFont,
     pub font_bold: BitmapFont
@@ -18,3 +38,51 @@ impl Resources {
         })
     }
 }
+
+/// Loads the item catalog from `ITEM_FILE` if present, and otherwise from
+/// the embedded copy.
+pub fn load_items() -> GameResult<ItemCatalog> {
+    let path = Path::new(ITEM_FILE);
+    let src = if path.is_file() {
+        fs::read_to_string(path)?
+    } else {
+        from_utf8(ITEMS).map_err(|err| GameError::ResourceLoadError(
+            format!("{}: {}", path.display(), err)
+        ))?.to_string()
+    };
+    src.parse().map_err(|err| GameError::ResourceLoadError(
+        format!("{}: {}", path.display(), err)
+    ))
+}
+
+/// Loads the level `name` from `LEVEL_DIR` if present there, so that levels
+/// can be edited without recompiling, and otherwise from the embedded copy.
+pub fn load_level(name: &str) -> GameResult<Level> {
+    let path = Path::new(LEVEL_DIR).join(format!("{}.lvl", name));
+    let src = if path.is_file() {
+        fs::read_to_string(&path)?
+    } else if let Some((_, data)) = LEVELS.iter().find(|(n, _)| *n == name) {
+        from_utf8(data).map_err(|err| GameError::ResourceLoadError(
+            format!("{}: {}", path.display(), err)
+        ))?.to_string()
+    } else {
+        return Err(GameError::ResourceNotFound(
+            path.display().to_string(),
+            Vec::new()
+        ));
+    };
+    src.parse().map_err(|err| GameError::ResourceLoadError(
+        format!("{}: {}", path.display(), err)
+    ))
+}
+
+/// Loads the named levels as the floors of a dungeon, top to bottom.
+pub fn load_dungeon(floors: &[&str]) -> GameResult<Dungeon> {
+    let dungeon = Dungeon {
+        floors: floors.iter()
+            .map(|name| load_level(name))
+            .collect::<GameResult<_>>()?
+    };
+    dungeon.validate().map_err(GameError::ResourceLoadError)?;
+    Ok(dungeon)
+}
@@ -0,0 +1,33 @@
+use ::ggez::*;
+use ::std::{
+    fs,
+    path::PathBuf
+};
+use crate::model::*;
+
+pub const SAVE_DIR: &str = "saves";
+pub const SAVE_SLOTS: usize = 3;
+
+pub fn save_path(slot: usize) -> PathBuf {
+    PathBuf::from(SAVE_DIR).join(format!("slot{}.sav", slot + 1))
+}
+
+pub fn slot_exists(slot: usize) -> bool {
+    save_path(slot).is_file()
+}
+
+/// Reads the save in `slot`. I/O errors are reported separately from saves
+/// that could be read but not understood.
+pub fn read_save(slot: usize, items: &ItemCatalog) -> GameResult<Result<State, SaveError>> {
+    Ok(State::from_save(&fs::read_to_string(save_path(slot))?, items))
+}
+
+pub fn write_save(slot: usize, state: &State) -> GameResult {
+    fs::create_dir_all(SAVE_DIR)?;
+    // write to a temporary file first so a crash cannot destroy the old save
+    let path = save_path(slot);
+    let tmp_path = path.with_extension("sav.tmp");
+    fs::write(&tmp_path, state.to_string())?;
+    fs::rename(&tmp_path, &path)?;
+    Ok(())
+}
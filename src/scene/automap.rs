@@ -0,0 +1,236 @@
+use ::ggez::{
+    *,
+    graphics::*,
+    mint::Point2
+};
+use crate::{
+    *,
+    drawing::*,
+    model::{
+        *,
+        Direction::*
+    },
+    scene::{
+        *,
+        explore::*,
+        view_character::*
+    }
+};
+
+#[derive(Clone, Debug)]
+pub struct AutomapScene {}
+
+pub const AUTOMAP_MAX_CELL_SIZE: f32 = 24.0;
+pub const MINIMAP_CELL_SIZE: f32 = 16.0;
+pub const SEEN_CELL_COLOR: Color = Color::new(0.2, 0.2, 0.2, 1.0);
+
+pub const MINIMAP_LEFT: f32 = LOCATION_LEFT;
+pub const MINIMAP_TOP: f32 = LOCATION_TOP + LOCATION_HEIGHT + 16.0;
+pub const MINIMAP_WIDTH: f32 = LOCATION_WIDTH;
+pub const MINIMAP_HEIGHT: f32 = VIEWPORT_TOP + VIEWPORT_HEIGHT - MINIMAP_TOP;
+pub const MINIMAP_RECT: Rect = Rect {
+    x: MINIMAP_LEFT,
+    y: MINIMAP_TOP,
+    w: MINIMAP_WIDTH,
+    h: MINIMAP_HEIGHT
+};
+pub const MINIMAP_BORDER_RECT: Rect = Rect {
+    x: MINIMAP_LEFT - 1.0,
+    y: MINIMAP_TOP - 1.0,
+    w: MINIMAP_WIDTH + 2.0,
+    h: MINIMAP_HEIGHT + 2.0
+};
+
+pub const AUTOMAP_RECT: Rect = Rect {
+    x: VIEWPORT_LEFT,
+    y: VIEWPORT_TOP,
+    w: VIEWPORT_WIDTH,
+    h: PARTYLIST_BOTTOM - VIEWPORT_TOP
+};
+
+impl Scene for AutomapScene {
+    fn action(&mut self, action: Action, game: &mut Game) -> GameResult<Transition> {
+        match action {
+            Action::Cancel | Action::OpenAutomap => return Ok(Transition::Pop(SceneResult::None)),
+            Action::ToggleMinimap => game.settings.minimap = !game.settings.minimap,
+            _ => {}
+        }
+        Ok(Transition::None)
+    }
+
+    fn draw(
+        &self,
+        ctx: &mut Context,
+        canvas: &mut Canvas,
+        game: &Game,
+        resources: &Resources
+    ) -> GameResult {
+        let state = &game.state;
+        let level = state.level();
+        draw_rect(
+            ctx, canvas,
+            &rect_points(CHAR_DETAILS_BORDER_RECT),
+            Color::WHITE,
+            Color::BLACK
+        )?;
+        // fit the whole level into the map, centered
+        let cell_size = (AUTOMAP_RECT.w / level.width as f32)
+            .min(AUTOMAP_RECT.h / level.height as f32)
+            .min(AUTOMAP_MAX_CELL_SIZE)
+            .floor();
+        let origin = Point2 {
+            x: AUTOMAP_RECT.x + (AUTOMAP_RECT.w - cell_size * level.width as f32) / 2.0,
+            y: AUTOMAP_RECT.y + (AUTOMAP_RECT.h - cell_size * level.height as f32) / 2.0
+        };
+        draw_automap(
+            ctx, canvas,
+            state,
+            AUTOMAP_RECT,
+            cell_size,
+            origin
+        )?;
+
+        draw_location(
+            ctx, canvas,
+            resources,
+            state.floor,
+            level
+        )?;
+        // draw control panel
+        draw_controls(
+            ctx, canvas,
+            game, resources,
+            &[
+                (Action::ToggleMinimap, "MINIMAP"),
+                (Action::Cancel, "BACK")
+            ]
+        )?;
+
+        Ok(())
+    }
+}
+/// Draws the seen parts of the current floor with cells of `cell_size`
+/// pixels, the top-left corner of the level at `origin`. Only cells lying
+/// completely within `bounds` are drawn.
+pub fn draw_automap(
+    ctx: &mut Context,
+    canvas: &mut Canvas,
+    state: &State,
+    bounds: Rect,
+    cell_size: f32,
+    origin: Point2<f32>
+) -> GameResult {
+    let (level, pos) = (state.level(), state.pos);
+    let corner = |x: usize, y: usize| Point2 {
+        x: origin.x + x as f32 * cell_size,
+        y: origin.y + y as f32 * cell_size
+    };
+    let visible = |x: usize, y: usize| {
+        let (top_left, bottom_right) = (corner(x, y), corner(x + 1, y + 1));
+        bounds.contains(top_left) && bounds.contains(bottom_right)
+    };
+    let automap = &level.automap;
+    let mut builder = MeshBuilder::new();
+    for x in 0..level.width {
+        for y in 0..level.height {
+            if automap.cells[x][y] && visible(x, y) {
+                let top_left = corner(x, y);
+                builder.rectangle(
+                    DrawMode::fill(),
+                    Rect::new(top_left.x, top_left.y, cell_size, cell_size),
+                    SEEN_CELL_COLOR
+                )?;
+            }
+        }
+    }
+    // walls are drawn as lines between two corners, doors with a gap in the
+    // middle third and one-way walls in gray
+    let mut wall = |wall: Wall, from: Point2<f32>, to: Point2<f32>| -> GameResult {
+        let lerp = |t: f32| Point2 {
+            x: from.x + (to.x - from.x) * t,
+            y: from.y + (to.y - from.y) * t
+        };
+        match wall {
+            Wall::None => {},
+            // secret doors pass for walls until they are found
+            Wall::Some | Wall::Secret => {
+                builder.line(&[from, to], 2.0, Color::WHITE)?;
+            },
+            Wall::OneWay(_) => {
+                builder.line(&[from, to], 2.0, GRAY)?;
+            },
+            Wall::Door(_) => {
+                builder.line(&[from, lerp(1.0 / 3.0)], 2.0, Color::WHITE)?;
+                builder.line(&[lerp(2.0 / 3.0), to], 2.0, Color::WHITE)?;
+            }
+        }
+        Ok(())
+    };
+    for x in 0..level.width {
+        for y in 0..=level.height {
+            let cell_y = y.min(level.height - 1);
+            if automap.horiz_walls[x][y] && visible(x, cell_y) {
+                wall(level.horiz_walls[x][y], corner(x, y), corner(x + 1, y))?;
+            }
+        }
+    }
+    for x in 0..=level.width {
+        for y in 0..level.height {
+            let cell_x = x.min(level.width - 1);
+            if automap.vert_walls[x][y] && visible(cell_x, y) {
+                wall(level.vert_walls[x][y], corner(x, y), corner(x, y + 1))?;
+            }
+        }
+    }
+    // party arrow
+    if visible(pos.x, pos.y) {
+        let center = corner(pos.x, pos.y);
+        let (cx, cy) = (center.x + cell_size / 2.0, center.y + cell_size / 2.0);
+        let r = cell_size * 0.35;
+        let (fx, fy) = match state.dir {
+            East => (1.0, 0.0),
+            South => (0.0, 1.0),
+            West => (-1.0, 0.0),
+            North => (0.0, -1.0)
+        };
+        builder.polygon(
+            DrawMode::fill(),
+            &[
+                Point2 { x: cx + fx * r, y: cy + fy * r },
+                Point2 { x: cx - fx * r - fy * r, y: cy - fy * r + fx * r },
+                Point2 { x: cx - fx * r + fy * r, y: cy - fy * r - fx * r }
+            ],
+            Color::WHITE
+        )?;
+    }
+    let mesh = Mesh::from_data(ctx, builder.build());
+    canvas.draw(&mesh, DrawParam::default());
+    Ok(())
+}
+
+/// Draws the part of the automap around the party into the panel below the
+/// location box.
+pub fn draw_minimap(
+    ctx: &mut Context,
+    canvas: &mut Canvas,
+    state: &State
+) -> GameResult {
+    let pos = state.pos;
+    draw_rect(
+        ctx, canvas,
+        &rect_points(MINIMAP_BORDER_RECT),
+        Color::WHITE,
+        Color::BLACK
+    )?;
+    let origin = Point2 {
+        x: MINIMAP_LEFT + MINIMAP_WIDTH / 2.0 - (pos.x as f32 + 0.5) * MINIMAP_CELL_SIZE,
+        y: MINIMAP_TOP + MINIMAP_HEIGHT / 2.0 - (pos.y as f32 + 0.5) * MINIMAP_CELL_SIZE
+    };
+    draw_automap(
+        ctx, canvas,
+        state,
+        MINIMAP_RECT,
+        MINIMAP_CELL_SIZE,
+        origin
+    )
+}
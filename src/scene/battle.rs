@@ -0,0 +1,744 @@
+use ::ggez::{
+    *,
+    graphics::*
+};
+use ::std::{
+    collections::VecDeque,
+    time::Duration
+};
+use crate::{
+    *,
+    drawing::*,
+    model::*,
+    scene::{
+        *,
+        battle::BattlePhase::*,
+        explore::*
+    }
+};
+
+#[derive(Clone, Debug)]
+pub struct BattleScene {
+    pub enemies: EnemyParty,
+    pub phase: BattlePhase,
+    pub actions: Vec<Option<BattleAction>>,
+    pub defending: Vec<bool>,
+    pub turn: u32,
+    pub messages: Vec<String>
+}
+
+#[derive(Clone, Debug)]
+pub enum BattlePhase {
+    ChooseCommand { i: usize, selected: usize },
+    /// `selected` indexes the spells the party member can cast in battle.
+    ChooseSpell { i: usize, selected: usize },
+    /// Chooses an enemy, or an ally for spells targeting one.
+    ChooseTarget {
+        i: usize,
+        command: BattleCommand,
+        spell: Option<Spell>,
+        selected: usize
+    },
+    Resolve { queue: VecDeque<Combatant>, timer: Duration },
+    /// Holds the party members who leveled up, with their stats from before.
+    Victory { level_ups: Vec<(usize, Character)> },
+    Defeat,
+    Fled
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BattleCommand {
+    Attack,
+    Defend,
+    Spell,
+    Item,
+    Flee
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BattleAction {
+    Attack(usize),
+    Defend,
+    /// A spell and its target, an index into the party or the enemies.
+    Spell(Spell, usize),
+    /// An item and the party member receiving it.
+    Item(Item, usize),
+    Flee
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Combatant {
+    PartyMember(usize),
+    Enemy(usize)
+}
+
+pub const COMMANDS: &[BattleCommand] = &[
+    BattleCommand::Attack,
+    BattleCommand::Defend,
+    BattleCommand::Spell,
+    BattleCommand::Item,
+    BattleCommand::Flee
+];
+
+pub const ACTION_DURATION: Duration = Duration::from_millis(800);
+
+impl BattleCommand {
+    pub fn name(&self) -> &'static str {
+        match self {
+            BattleCommand::Attack => "ATTACK",
+            BattleCommand::Defend => "DEFEND",
+            BattleCommand::Spell => "SPELL",
+            BattleCommand::Item => "ITEM",
+            BattleCommand::Flee => "FLEE"
+        }
+    }
+}
+
+/// The spells `character` knows that can be cast in battle.
+pub fn battle_spells(character: &Character) -> Vec<Spell> {
+    character.spells().into_iter().filter(|spell| spell.in_battle()).collect()
+}
+
+fn targets_ally(spell: Option<Spell>) -> bool {
+    spell.map(|spell| spell.target()) == Some(SpellTarget::Ally)
+}
+
+impl BattleScene {
+    pub fn new(enemies: EnemyParty, state: &State) -> Self {
+        let mut scene = BattleScene {
+            enemies,
+            phase: ChooseCommand { i: 0, selected: 0 },
+            actions: vec![None; state.party.len()],
+            defending: vec![false; state.party.len()],
+            turn: 0,
+            messages: Vec::new()
+        };
+        scene.choose_from(state, 0);
+        scene.messages = vec!["MONSTERS APPEAR!".to_string()];
+        scene
+    }
+
+    fn next_alive_enemy(&self, from: usize) -> Option<usize> {
+        let len = self.enemies.enemies.len();
+        (0..len).map(|j| (from + j) % len)
+            .find(|&j| self.enemies.enemies[j].is_alive())
+    }
+
+    fn prev_alive_enemy(&self, from: usize) -> Option<usize> {
+        let len = self.enemies.enemies.len();
+        (0..len).map(|j| (from + len - j) % len)
+            .find(|&j| self.enemies.enemies[j].is_alive())
+    }
+
+    /// Moves on to the next party member after `i` who can act, or starts
+    /// resolving the round once everybody has chosen a command.
+    fn advance(&mut self, state: &State, i: usize) {
+        self.choose_from(state, i + 1);
+    }
+
+    /// Lets the first party member from `from` on who can act choose a
+    /// command, or starts resolving the round if there is none.
+    fn choose_from(&mut self, state: &State, from: usize) {
+        self.phase = match next_active_member(&state.party, from) {
+            Some(j) => {
+                self.messages.clear();
+                ChooseCommand { i: j, selected: 0 }
+            },
+            None => {
+                for (defending, action) in self.defending.iter_mut()
+                    .zip(self.actions.iter())
+                {
+                    *defending = *action == Some(BattleAction::Defend);
+                }
+                Resolve {
+                    queue: turn_queue(state, &self.enemies),
+                    timer: Duration::ZERO
+                }
+            }
+        };
+    }
+
+    /// Makes using `item` on party member `target` the action of the party
+    /// member choosing a command.
+    fn choose_item(&mut self, state: &State, item: Item, target: usize) {
+        if let ChooseCommand { i, .. } = self.phase {
+            self.actions[i] = Some(BattleAction::Item(item, target));
+            self.advance(state, i);
+        }
+    }
+
+    fn start_round(&mut self, state: &mut State) {
+        self.turn += 1;
+        self.actions.iter_mut().for_each(|action| *action = None);
+        self.defending.iter_mut().for_each(|defending| *defending = false);
+        self.messages.clear();
+        for character in state.party.iter_mut() {
+            character.turn_statuses();
+        }
+        self.choose_from(state, 0);
+    }
+
+    /// Carries out the action of a single combatant. Returns `false` if the
+    /// combatant could not act, so that no time is spent on it.
+    fn act(&mut self, state: &mut State, combatant: Combatant) -> bool {
+        match combatant {
+            Combatant::PartyMember(i) => {
+                let Some(character) = state.party.get_mut(i) else {
+                    return false;
+                };
+                if !character.can_act() {
+                    return false;
+                }
+                match self.actions[i].clone() {
+                    Some(BattleAction::Attack(target)) => {
+                        let Some(target) = self.next_alive_enemy(target) else {
+                            return false;
+                        };
+                        let enemy = &mut self.enemies.enemies[target];
+                        self.messages = vec![format!("{} ATTACKS!", character.name)];
+                        let damage = physical_damage(
+                            character.atk(),
+                            enemy.monster.def(),
+                            false
+                        );
+                        enemy.take_damage(damage);
+                        self.messages.push(format!(
+                            "{} TAKES {} DAMAGE.",
+                            enemy.monster.name(),
+                            damage
+                        ));
+                        if !enemy.is_alive() {
+                            self.messages.push(format!(
+                                "{} IS SLAIN.",
+                                enemy.monster.name()
+                            ));
+                        }
+                    },
+                    Some(BattleAction::Spell(spell, target)) => {
+                        if !character.can_cast(spell) {
+                            self.messages = vec![
+                                format!("{} CANNOT CAST {}.", character.name, spell.name())
+                            ];
+                            return true;
+                        }
+                        // like attacks, spells move on to the next enemy
+                        // if theirs has already fallen
+                        let target = match spell.target() {
+                            SpellTarget::Enemy | SpellTarget::EnemyGroup => {
+                                let Some(target) = self.next_alive_enemy(target) else {
+                                    return false;
+                                };
+                                target
+                            },
+                            _ => target
+                        };
+                        self.messages = state.cast_spell(
+                            i,
+                            spell,
+                            target,
+                            &mut self.enemies.enemies
+                        );
+                        if spell == Spell::Guard {
+                            self.defending[i] = true;
+                        }
+                    },
+                    Some(BattleAction::Item(item, target)) => {
+                        let uses = format!("{} USES {}!", character.name, item.name());
+                        self.messages = match uses.len() <= DESC_WIDTH {
+                            true => vec![uses],
+                            false => vec![
+                                format!("{} USES", character.name),
+                                format!("{}!", item.name())
+                            ]
+                        };
+                        // somebody else may have used up the last one
+                        let Some(item_i) = state.inventory.iter()
+                            .position(|stack| stack.item == item)
+                        else {
+                            self.messages.push("BUT NONE ARE LEFT.".to_string());
+                            return true;
+                        };
+                        self.messages.extend(state.use_item(item_i, target));
+                        if item.effect == Some(ItemEffect::Escape) {
+                            self.phase = Fled;
+                            state.end_battle();
+                        }
+                    },
+                    Some(BattleAction::Defend) => {
+                        self.messages = vec![
+                            format!("{} DEFENDS.", character.name)
+                        ];
+                    },
+                    Some(BattleAction::Flee) => {
+                        // the odds of escaping improve every turn, so that
+                        // the party can never be stuck in a fight forever
+                        let fastest = self.enemies.enemies.iter()
+                            .filter(|enemy| enemy.is_alive())
+                            .map(|enemy| enemy.monster.agi())
+                            .max()
+                            .unwrap_or(0);
+                        if character.agi() + self.turn > fastest {
+                            self.messages = vec![
+                                "THE PARTY FLEES!".to_string()
+                            ];
+                            self.phase = Fled;
+                            state.end_battle();
+                        } else {
+                            self.messages = vec![
+                                format!("{} TRIES TO FLEE...", character.name),
+                                "BUT CANNOT ESCAPE!".to_string()
+                            ];
+                        }
+                    },
+                    None => return false
+                }
+            },
+            Combatant::Enemy(k) => {
+                let Some(enemy) = self.enemies.enemies.get(k) else {
+                    return false;
+                };
+                if !enemy.is_alive() {
+                    return false;
+                }
+                // statues are left alone
+                let alive = (0..state.party.len())
+                    .filter(|&i| state.party[i].is_alive() && !state.party[i].has(Status::Stone))
+                    .collect::<Vec<_>>();
+                if alive.is_empty() {
+                    return false;
+                }
+                let i = alive[(self.turn as usize + k) % alive.len()];
+                let character = &mut state.party[i];
+                let monster = enemy.monster;
+                let damage = if monster.matk() > monster.atk() {
+                    self.messages = vec![
+                        format!("{} CASTS A SPELL!", monster.name())
+                    ];
+                    magic_damage(monster.matk(), character.mdef(), self.defending[i])
+                } else {
+                    self.messages = vec![
+                        format!("{} ATTACKS!", monster.name())
+                    ];
+                    physical_damage(monster.atk(), character.def(), self.defending[i])
+                };
+                character.take_damage(damage);
+                self.messages.push(format!(
+                    "{} TAKES {} DAMAGE.",
+                    character.name,
+                    damage
+                ));
+                if !character.is_alive() {
+                    self.messages.push(format!("{} FALLS.", character.name));
+                } else if let Some((status, chance)) = monster.inflicts() {
+                    if state.rng.combat.chance(chance) && state.inflict(i, status) {
+                        self.messages.push(format!(
+                            "{} {}",
+                            state.party[i].name,
+                            status.inflicted_text()
+                        ));
+                    }
+                }
+            }
+        }
+        true
+    }
+}
+
+fn next_alive_member(party: &[Character], from: usize) -> Option<usize> {
+    (from..party.len()).find(|&i| party[i].is_alive())
+}
+
+fn next_active_member(party: &[Character], from: usize) -> Option<usize> {
+    (from..party.len()).find(|&i| party[i].can_act())
+}
+
+/// Orders every living combatant by agility, fastest first. Ties are won by
+/// the party, and within each side by whoever is listed first.
+pub fn turn_queue(state: &State, enemies: &EnemyParty) -> VecDeque<Combatant> {
+    let mut queue = state.party.iter().enumerate()
+        .filter(|(_, character)| character.is_alive())
+        .map(|(i, character)| (character.agi(), Combatant::PartyMember(i)))
+        .chain(
+            enemies.enemies.iter().enumerate()
+                .filter(|(_, enemy)| enemy.is_alive())
+                .map(|(k, enemy)| (enemy.monster.agi(), Combatant::Enemy(k)))
+        )
+        .collect::<Vec<_>>();
+    queue.sort_by(|(agi1, _), (agi2, _)| agi2.cmp(agi1));
+    queue.into_iter().map(|(_, combatant)| combatant).collect()
+}
+
+impl Scene for BattleScene {
+    fn action(&mut self, action: Action, game: &mut Game) -> GameResult<Transition> {
+        let state = &mut game.state;
+        match self.phase.clone() {
+            ChooseCommand { i, selected } => match action {
+                Action::Confirm => match COMMANDS[selected] {
+                    BattleCommand::Attack => {
+                        if let Some(target) = self.next_alive_enemy(0) {
+                            self.phase = ChooseTarget {
+                                i,
+                                command: BattleCommand::Attack,
+                                spell: None,
+                                selected: target
+                            };
+                        }
+                    },
+                    BattleCommand::Spell => {
+                        if battle_spells(&state.party[i]).is_empty() {
+                            self.messages = vec![
+                                format!("{} KNOWS NO SPELLS.", state.party[i].name)
+                            ];
+                        } else {
+                            self.phase = ChooseSpell { i, selected: 0 };
+                        }
+                    },
+                    BattleCommand::Defend => {
+                        self.actions[i] = Some(BattleAction::Defend);
+                        self.advance(state, i);
+                    },
+                    BattleCommand::Flee => {
+                        self.actions[i] = Some(BattleAction::Flee);
+                        self.advance(state, i);
+                    },
+                    BattleCommand::Item => {
+                        return Ok(Transition::Push(Box::new(ViewInventoryScene::new(
+                            ItemPredicate::UsableInBattle
+                        ))));
+                    }
+                },
+                Action::Cancel => {
+                    if let Some(j) = (0..i).rev().find(|&j| state.party[j].can_act()) {
+                        self.actions[j] = None;
+                        self.phase = ChooseCommand { i: j, selected: 0 };
+                    }
+                },
+                Action::Up => self.phase = ChooseCommand {
+                    i,
+                    selected: (selected + COMMANDS.len() - 1) % COMMANDS.len()
+                },
+                Action::Down => self.phase = ChooseCommand {
+                    i,
+                    selected: (selected + 1) % COMMANDS.len()
+                },
+                _ => {}
+            },
+            ChooseSpell { i, selected } => {
+                let spells = battle_spells(&state.party[i]);
+                match action {
+                    Action::Confirm => {
+                        let spell = spells[selected];
+                        if !state.party[i].can_cast(spell) {
+                            self.messages = vec!["NOT ENOUGH MP.".to_string()];
+                            return Ok(Transition::None);
+                        }
+                        let target = match spell.target() {
+                            SpellTarget::Enemy | SpellTarget::EnemyGroup
+                                => self.next_alive_enemy(0),
+                            SpellTarget::Ally => next_alive_member(&state.party, 0),
+                            SpellTarget::AllAllies | SpellTarget::Caster => None
+                        };
+                        if let Some(target) = target {
+                            self.phase = ChooseTarget {
+                                i,
+                                command: BattleCommand::Spell,
+                                spell: Some(spell),
+                                selected: target
+                            };
+                        } else {
+                            self.actions[i] = Some(BattleAction::Spell(spell, i));
+                            self.advance(state, i);
+                        }
+                    },
+                    Action::Cancel => {
+                        self.messages.clear();
+                        self.phase = ChooseCommand {
+                            i,
+                            selected: COMMANDS.iter()
+                                .position(|&c| c == BattleCommand::Spell)
+                                .unwrap_or(0)
+                        };
+                    },
+                    Action::Up => {
+                        self.messages.clear();
+                        self.phase = ChooseSpell {
+                            i,
+                            selected: (selected + spells.len() - 1) % spells.len()
+                        };
+                    },
+                    Action::Down => {
+                        self.messages.clear();
+                        self.phase = ChooseSpell {
+                            i,
+                            selected: (selected + 1) % spells.len()
+                        };
+                    },
+                    _ => {}
+                }
+            },
+            ChooseTarget { i, command, spell, selected } => match action {
+                Action::Confirm => {
+                    self.actions[i] = Some(match spell {
+                        Some(spell) => BattleAction::Spell(spell, selected),
+                        None => BattleAction::Attack(selected)
+                    });
+                    self.advance(state, i);
+                },
+                Action::Cancel => {
+                    self.phase = match spell {
+                        Some(spell) => ChooseSpell {
+                            i,
+                            selected: battle_spells(&state.party[i]).iter()
+                                .position(|&s| s == spell)
+                                .unwrap_or(0)
+                        },
+                        None => ChooseCommand {
+                            i,
+                            selected: COMMANDS.iter()
+                                .position(|&c| c == command)
+                                .unwrap_or(0)
+                        }
+                    };
+                },
+                Action::Up => {
+                    let target = if targets_ally(spell) {
+                        let len = state.party.len();
+                        (1..=len).map(|j| (selected + len - j) % len)
+                            .find(|&j| state.party[j].is_alive())
+                    } else {
+                        let len = self.enemies.enemies.len();
+                        self.prev_alive_enemy(selected + len - 1)
+                    };
+                    if let Some(target) = target {
+                        self.phase = ChooseTarget { i, command, spell, selected: target };
+                    }
+                },
+                Action::Down => {
+                    let target = if targets_ally(spell) {
+                        next_alive_member(&state.party, selected + 1)
+                            .or_else(|| next_alive_member(&state.party, 0))
+                    } else {
+                        self.next_alive_enemy(selected + 1)
+                    };
+                    if let Some(target) = target {
+                        self.phase = ChooseTarget { i, command, spell, selected: target };
+                    }
+                },
+                _ => {}
+            },
+            Resolve { .. } => {},
+            Victory { level_ups } => {
+                if action == Action::Confirm {
+                    return Ok(match level_ups.is_empty() {
+                        true => Transition::Pop(SceneResult::None),
+                        false => Transition::Replace(Box::new(LevelUpScene { level_ups, i: 0 }))
+                    });
+                }
+            },
+            Fled => {
+                if action == Action::Confirm {
+                    return Ok(Transition::Pop(SceneResult::None));
+                }
+            },
+            Defeat => {
+                if action == Action::Confirm {
+                    return Ok(Transition::Reset(Box::new(GameOverScene {})));
+                }
+            }
+        }
+        Ok(Transition::None)
+    }
+
+    /// Takes the item chosen in the inventory.
+    fn resume(&mut self, result: SceneResult, game: &mut Game) -> GameResult<Transition> {
+        let state = &game.state;
+        if let SceneResult::Item { i, target } = result {
+            let item = state.inventory[i].item.clone();
+            self.choose_item(state, item, target);
+        }
+        Ok(Transition::None)
+    }
+
+    fn update(&mut self, delta: Duration, game: &mut Game) -> GameResult<Transition> {
+        let state = &mut game.state;
+
+        let next = match &mut self.phase {
+            Resolve { queue, timer } => {
+                if delta < *timer {
+                    *timer -= delta;
+                    return Ok(Transition::None);
+                }
+                queue.pop_front()
+            },
+            _ => return Ok(Transition::None)
+        };
+        if self.enemies.is_defeated() {
+            state.end_battle();
+            let xp = self.enemies.enemies.iter()
+                .map(|enemy| enemy.monster.xp())
+                .sum();
+            let (share, level_ups) = state.award_xp(xp);
+            self.messages = vec![
+                "THE ENEMIES ARE DEFEATED!".to_string(),
+                format!("SURVIVORS GAIN {} XP.", share)
+            ];
+            for (i, _) in level_ups.iter() {
+                self.messages.push(format!(
+                    "{} REACHES LV.{}!",
+                    state.party[*i].name,
+                    state.party[*i].lvl
+                ));
+            }
+            // battles cannot follow one another, so only the messages of
+            // victory events are kept
+            if let Some(outcome) = state.run_events(&Trigger::Victory, &game.items) {
+                self.messages.extend(outcome.messages);
+            }
+            self.phase = Victory { level_ups };
+        } else if state.party.iter().all(|character| character.is_disabled()) {
+            state.end_battle();
+            self.messages = vec!["THE PARTY HAS FALLEN...".to_string()];
+            self.phase = Defeat;
+        } else if let Some(combatant) = next {
+            let acted = self.act(state, combatant);
+            if let Resolve { timer, .. } = &mut self.phase {
+                *timer = if acted { ACTION_DURATION } else { Duration::ZERO };
+            }
+        } else {
+            self.start_round(state);
+        }
+        Ok(Transition::None)
+    }
+
+    fn draw(
+        &self,
+        ctx: &mut Context,
+        canvas: &mut Canvas,
+        game: &Game,
+        resources: &Resources
+    ) -> GameResult {
+        let state = &game.state;
+        draw_rect(
+            ctx, canvas,
+            &rect_points(VIEWPORT_BORDER_RECT),
+            Color::WHITE,
+            Color::BLACK
+        )?;
+
+        // draw enemy roster
+        draw_bitmap_text(
+            canvas,
+            "  ENEMY     HP",
+            &resources.font_bold,
+            Color::WHITE,
+            VIEWPORT_LEFT + 8.0,
+            VIEWPORT_TOP + 8.0
+        );
+        for (k, enemy) in self.enemies.enemies.iter().enumerate() {
+            let x = VIEWPORT_LEFT + 8.0;
+            let y = VIEWPORT_TOP + 8.0 + 16.0 * (k + 1) as f32;
+            if matches!(
+                self.phase,
+                ChooseTarget { spell, selected, .. } if selected == k && !targets_ally(spell)
+            ) {
+                draw_bitmap_text(
+                    canvas,
+                    ">",
+                    &resources.font_bold,
+                    Color::WHITE,
+                    x, y
+                );
+            }
+            draw_bitmap_text(
+                canvas,
+                format!("{:9} {}/{}", enemy.monster.name(), enemy.hp, enemy.monster.max_hp()),
+                &resources.font_regular,
+                match enemy.is_alive() {
+                    true => Color::WHITE,
+                    false => GRAY
+                },
+                x + 32.0, y
+            );
+        }
+
+        // draw battle messages
+        let prompt;
+        let messages = match self.phase {
+            ChooseSpell { i, selected } if self.messages.is_empty() => {
+                let spell = battle_spells(&state.party[i])[selected];
+                prompt = [format!("{}: {} MP", spell.name(), spell.mp_cost())].into_iter()
+                    .chain(spell.desc().iter().map(|line| line.to_string()))
+                    .collect::<Vec<_>>();
+                &prompt[..]
+            },
+            ChooseCommand { i, .. } | ChooseTarget { i, .. }
+                if self.messages.is_empty() =>
+            {
+                prompt = vec![format!("WHAT WILL {} DO?", state.party[i].name)];
+                &prompt[..]
+            },
+            _ => &self.messages[..]
+        };
+        for (j, line) in messages.iter().enumerate() {
+            let y = VIEWPORT_TOP + VIEWPORT_HEIGHT - 8.0
+                - 16.0 * (messages.len() - j) as f32;
+            draw_bitmap_text(
+                canvas,
+                line,
+                &resources.font_regular,
+                Color::WHITE,
+                VIEWPORT_LEFT + 8.0, y
+            );
+        }
+
+        draw_partylist(
+            ctx, canvas,
+            resources,
+            &state.party,
+            &None,
+            match self.phase {
+                ChooseTarget { spell, selected, .. } if targets_ally(spell) => Some(selected),
+                ChooseCommand { i, .. } | ChooseSpell { i, .. } | ChooseTarget { i, .. }
+                    => Some(i),
+                _ => None
+            }
+        )?;
+        // draw control panel
+        match self.phase {
+            ChooseCommand { selected, .. } => {
+                let controls = COMMANDS.iter().enumerate()
+                    .map(|(j, command)| {
+                        (if j == selected { ">" } else { "" }, command.name())
+                    })
+                    .collect::<Vec<_>>();
+                draw_panel(ctx, canvas, resources, &controls)?;
+            },
+            ChooseSpell { i, selected } => {
+                let spells = battle_spells(&state.party[i]);
+                let controls = spells.iter().enumerate()
+                    .map(|(j, spell)| {
+                        (if j == selected { ">" } else { "" }, spell.name())
+                    })
+                    .collect::<Vec<_>>();
+                draw_panel(ctx, canvas, resources, &controls)?;
+            },
+            ChooseTarget { .. } => draw_controls(
+                ctx, canvas,
+                game, resources,
+                &[(Action::Confirm, "TARGET"), (Action::Cancel, "BACK")]
+            )?,
+            Resolve { .. } => draw_controls(
+                ctx, canvas,
+                game, resources,
+                &[]
+            )?,
+            Victory { .. } | Defeat | Fled => draw_controls(
+                ctx, canvas,
+                game, resources,
+                &[(Action::Confirm, "CONTINUE")]
+            )?
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,193 @@
+use ::ggez::{
+    *,
+    graphics::*
+};
+use crate::{
+    *,
+    drawing::*,
+    scene::{
+        *,
+        explore::*,
+        view_inventory::*
+    }
+};
+
+/// Lists every action with the keys and buttons bound to it, for binding
+/// more of them or clearing them. Changes are written right away to where
+/// `Settings::bindings_path` says.
+#[derive(Clone, Debug, Default)]
+pub struct ControlsScene {
+    /// Indexes `Action::ALL`.
+    pub i: usize,
+    /// Whether the next key or button pressed is bound to the selected
+    /// action.
+    pub binding: bool,
+    pub message: Option<String>
+}
+
+impl ControlsScene {
+    /// Writes the bindings, telling if that failed.
+    fn write(&mut self, game: &Game) {
+        if let Some(path) = &game.settings.bindings_path {
+            if write_bindings(path, &game.settings.bindings).is_err() {
+                self.message = Some("COULD NOT WRITE\nTHE CONTROLS.".to_string());
+            }
+        }
+    }
+}
+
+impl Scene for ControlsScene {
+    fn action(&mut self, action: Action, game: &mut Game) -> GameResult<Transition> {
+        let len = Action::ALL.len();
+        let selected = Action::ALL[self.i];
+        match action {
+            Action::Confirm => {
+                self.binding = true;
+                self.message = None;
+            },
+            Action::Erase => {
+                if selected.is_required() {
+                    self.message = Some("THIS ACTION MUST\nSTAY BOUND.".to_string());
+                } else {
+                    game.settings.bindings.unbind(selected);
+                    self.message = None;
+                    self.write(game);
+                }
+            },
+            Action::Cancel => return Ok(Transition::Pop(SceneResult::None)),
+            Action::Up => {
+                self.i = (self.i + len - 1) % len;
+                self.message = None;
+            },
+            Action::Down => {
+                self.i = (self.i + 1) % len;
+                self.message = None;
+            },
+            _ => {}
+        }
+        Ok(Transition::None)
+    }
+
+    fn captures_controls(&self) -> bool {
+        self.binding
+    }
+
+    /// Binds `control` to the selected action, unless it is bound to
+    /// `Action::Cancel`, which stops waiting for one instead.
+    fn control(&mut self, control: Control, game: &mut Game) -> GameResult<Transition> {
+        self.binding = false;
+        if !game.settings.bindings.controls(Action::Cancel).contains(&control) {
+            game.settings.bindings.bind(Action::ALL[self.i], control);
+            self.write(game);
+        }
+        Ok(Transition::None)
+    }
+
+    fn draw(
+        &self,
+        ctx: &mut Context,
+        canvas: &mut Canvas,
+        game: &Game,
+        resources: &Resources
+    ) -> GameResult {
+        let bindings = &game.settings.bindings;
+        draw_rect(
+            ctx, canvas,
+            &rect_points(INVENTORY_BORDER_RECT),
+            Color::WHITE,
+            Color::BLACK
+        )?;
+
+        // draw list of actions, with the keys or the buttons bound to them
+        let len = Action::ALL.len();
+        let skip_len = self.i.saturating_sub(ITEMS_PER_SCREEN / 2)
+            .min(len.saturating_sub(ITEMS_PER_SCREEN));
+        for (j, &action) in Action::ALL.iter().skip(skip_len).take(ITEMS_PER_SCREEN)
+            .enumerate()
+        {
+            let y = 24.0 + j as f32 * 16.0;
+            if self.i == j + skip_len {
+                draw_bitmap_text(
+                    canvas,
+                    ">",
+                    &resources.font_bold,
+                    Color::WHITE,
+                    24.0, y
+                );
+            }
+            draw_bitmap_text(
+                canvas,
+                action.name(),
+                &resources.font_regular,
+                Color::WHITE,
+                56.0, y
+            );
+            let labels = bindings.controls(action).iter()
+                .filter(|control| control.is_button() == game.gamepad)
+                .map(Control::label)
+                .collect::<Vec<_>>();
+            draw_bitmap_text(
+                canvas,
+                labels.join(" "),
+                &resources.font_bold,
+                Color::WHITE,
+                280.0, y
+            );
+        }
+
+        // draw everything bound to the selected action, or the result of the
+        // last change
+        draw_rect(
+            ctx, canvas,
+            &rect_points(ITEM_DETAILS_BORDER_RECT),
+            Color::WHITE,
+            Color::BLACK
+        )?;
+        let selected = Action::ALL[self.i];
+        draw_bitmap_text(
+            canvas,
+            selected.name(),
+            &resources.font_bold,
+            Color::WHITE,
+            24.0, 348.0
+        );
+        let lines = if self.binding {
+            vec!["PRESS A KEY OR BUTTON.".to_string()]
+        } else if let Some(message) = &self.message {
+            message.lines().map(str::to_string).collect()
+        } else {
+            let names = |button: bool| bindings.controls(selected).iter()
+                .filter(|control| control.is_button() == button)
+                .filter_map(Control::name)
+                .map(|name| name.replace('_', " "))
+                .collect::<Vec<_>>()
+                .join(", ");
+            vec![
+                format!("KEYS    {}", names(false)),
+                format!("BUTTONS {}", names(true))
+            ]
+        };
+        for (i, line) in lines.iter().enumerate() {
+            draw_bitmap_text(
+                canvas,
+                line,
+                &resources.font_regular,
+                Color::WHITE,
+                24.0, 364.0 + i as f32 * 16.0
+            );
+        }
+
+        // draw control panel
+        let controls: &[(Action, &str)] = match self.binding {
+            true => &[(Action::Cancel, "CANCEL")],
+            false => &[(Action::Confirm, "ADD"), (Action::Erase, "CLEAR"), (Action::Cancel, "BACK")]
+        };
+        draw_controls(
+            ctx, canvas,
+            game, resources,
+            controls
+        )?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,242 @@
+use ::ggez::{
+    *,
+    graphics::*
+};
+use ::std::mem::take;
+use crate::{
+    *,
+    drawing::*,
+    model::*,
+    scene::{
+        *,
+        explore::*,
+        view_character::*
+    }
+};
+
+#[derive(Clone, Debug)]
+pub struct CreateCharacterScene {
+    pub phase: CreateCharacterPhase
+}
+
+#[derive(Clone, Debug)]
+pub enum CreateCharacterPhase {
+    ChooseClass {
+        selected: usize
+    },
+    EnterName {
+        class: CharacterClass,
+        name: String
+    },
+    Roll {
+        character: Character
+    }
+}
+
+/// The longest name that fits next to the stats in the party list.
+pub const MAX_NAME_LEN: usize = 9;
+
+fn class_index(class: CharacterClass) -> usize {
+    CharacterClass::ALL.iter().position(|c| *c == class).unwrap_or(0)
+}
+
+impl Scene for CreateCharacterScene {
+    fn action(&mut self, action: Action, game: &mut Game) -> GameResult<Transition> {
+        let state = &mut game.state;
+        match &mut self.phase {
+            CreateCharacterPhase::ChooseClass { selected } => {
+                let len = CharacterClass::ALL.len();
+                match action {
+                    Action::Confirm => {
+                        self.phase = CreateCharacterPhase::EnterName {
+                            class: CharacterClass::ALL[*selected],
+                            name: String::new()
+                        };
+                    },
+                    Action::Cancel => return Ok(Transition::Pop(SceneResult::None)),
+                    Action::Up => *selected = (*selected + len - 1) % len,
+                    Action::Down => *selected = (*selected + 1) % len,
+                    _ => {}
+                }
+            },
+            CreateCharacterPhase::EnterName { class, name } => match action {
+                Action::Confirm => {
+                    let name = name.trim_end();
+                    if !name.is_empty() {
+                        self.phase = CreateCharacterPhase::Roll {
+                            character: Character::roll(
+                                name.to_string(),
+                                *class,
+                                &mut state.rng.stats
+                            )
+                        };
+                    }
+                },
+                Action::Erase => {
+                    name.pop();
+                },
+                Action::Cancel => {
+                    self.phase = CreateCharacterPhase::ChooseClass {
+                        selected: class_index(*class)
+                    };
+                },
+                _ => {}
+            },
+            CreateCharacterPhase::Roll { character } => match action {
+                Action::Confirm => {
+                    return Ok(Transition::Pop(SceneResult::Character(character.clone())));
+                },
+                Action::Reroll => {
+                    *character = Character::roll(
+                        take(&mut character.name),
+                        character.class,
+                        &mut state.rng.stats
+                    );
+                },
+                Action::Cancel => {
+                    self.phase = CreateCharacterPhase::EnterName {
+                        class: character.class,
+                        name: take(&mut character.name)
+                    };
+                },
+                _ => {}
+            }
+        }
+        Ok(Transition::None)
+    }
+
+    /// Types letters into the name, since key codes don't tell apart what
+    /// the keyboard layout produces.
+    fn text_input(&mut self, character: char, _game: &mut Game) -> GameResult<Transition> {
+        if let CreateCharacterPhase::EnterName { name, .. } = &mut self.phase {
+            let allowed = character.is_ascii_alphabetic()
+                || (character == ' ' && !name.is_empty());
+            if allowed && name.len() < MAX_NAME_LEN {
+                name.push(character.to_ascii_uppercase());
+            }
+        }
+        Ok(Transition::None)
+    }
+
+    fn draw(
+        &self,
+        ctx: &mut Context,
+        canvas: &mut Canvas,
+        game: &Game,
+        resources: &Resources
+    ) -> GameResult {
+        draw_rect(
+            ctx, canvas,
+            &rect_points(CHAR_DETAILS_BORDER_RECT),
+            Color::WHITE,
+            Color::BLACK
+        )?;
+
+        let controls: &[(Action, &str)] = match &self.phase {
+            CreateCharacterPhase::ChooseClass { selected } => {
+                draw_bitmap_text(
+                    canvas,
+                    "CHOOSE A CLASS",
+                    &resources.font_bold,
+                    Color::WHITE,
+                    24.0, 24.0
+                );
+                for (i, class) in CharacterClass::ALL.iter().enumerate() {
+                    let y = 56.0 + i as f32 * 16.0;
+                    if i == *selected {
+                        draw_bitmap_text(
+                            canvas,
+                            ">",
+                            &resources.font_bold,
+                            Color::WHITE,
+                            24.0, y
+                        );
+                    }
+                    draw_bitmap_text(
+                        canvas,
+                        class.name(),
+                        &resources.font_regular,
+                        Color::WHITE,
+                        56.0, y
+                    );
+                }
+                &[(Action::Confirm, "CHOOSE"), (Action::Cancel, "BACK")]
+            },
+            CreateCharacterPhase::EnterName { class, name } => {
+                draw_bitmap_text(
+                    canvas,
+                    "ENTER A NAME",
+                    &resources.font_bold,
+                    Color::WHITE,
+                    24.0, 24.0
+                );
+                draw_bitmap_text(
+                    canvas,
+                    class.name(),
+                    &resources.font_regular,
+                    Color::WHITE,
+                    24.0, 40.0
+                );
+                draw_bitmap_text(
+                    canvas,
+                    format!("{}_", name),
+                    &resources.font_regular,
+                    Color::WHITE,
+                    24.0, 72.0
+                );
+                &[(Action::Confirm, "DONE"), (Action::Erase, "DELETE"), (Action::Cancel, "BACK")]
+            },
+            CreateCharacterPhase::Roll { character } => {
+                draw_bitmap_text(
+                    canvas,
+                    &character.name,
+                    &resources.font_bold,
+                    Color::WHITE,
+                    24.0, 24.0
+                );
+                draw_bitmap_text(
+                    canvas,
+                    format!("{} LV.{}", character.class.name(), character.lvl),
+                    &resources.font_regular,
+                    Color::WHITE,
+                    24.0, 40.0
+                );
+                for (i, (stat, val)) in [
+                    ("HP", character.max_hp),
+                    ("MP", character.max_mp),
+                    ("ATK", character.base_atk),
+                    ("DEF", character.base_def),
+                    ("MAG", character.base_matk),
+                    ("RES", character.base_mdef),
+                    ("AGI", character.base_agi),
+                    ("LUC", character.base_luck),
+                ].iter().enumerate() {
+                    let y = 72.0 + i as f32 * 16.0;
+                    draw_bitmap_text(
+                        canvas,
+                        stat,
+                        &resources.font_bold,
+                        Color::WHITE,
+                        24.0, y
+                    );
+                    draw_bitmap_text(
+                        canvas,
+                        val.to_string(),
+                        &resources.font_regular,
+                        Color::WHITE,
+                        88.0, y
+                    );
+                }
+                &[(Action::Reroll, "REROLL"), (Action::Confirm, "ACCEPT"), (Action::Cancel, "BACK")]
+            }
+        };
+        // draw control panel
+        draw_controls(
+            ctx, canvas,
+            game, resources,
+            controls
+        )?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,62 @@
+use ::ggez::{
+    *,
+    graphics::*
+};
+use crate::{
+    *,
+    drawing::*,
+    model::*
+};
+
+/// Lines that fit on the overlay, the rest of the variables are cut off.
+pub const DEBUG_OVERLAY_LINES: usize = 27;
+
+/// Draws where the party is, the seed of the game and every story variable
+/// on top of whatever scene is showing, for checking on events while
+/// playing.
+pub fn draw(
+    ctx: &mut Context,
+    canvas: &mut Canvas,
+    game: &Game,
+    resources: &Resources
+) -> GameResult {
+    let state = &game.state;
+    draw_rect(
+        ctx, canvas,
+        &rect_points(Rect {
+            x: 0.0,
+            y: 0.0,
+            w: WINDOW_WIDTH,
+            h: WINDOW_HEIGHT
+        }),
+        TRANSPARENT,
+        Color::new(0.0, 0.0, 0.0, 0.8)
+    )?;
+    let mut lines = vec![format!(
+        "FLOOR {} ({}, {}) {}",
+        state.floor + 1,
+        state.pos.x,
+        state.pos.y,
+        dir_name(state.dir).to_uppercase()
+    )];
+    lines.push(format!("SEED {}", state.rng.seed));
+    if state.vars.is_empty() {
+        lines.push("NO VARIABLES SET".to_string());
+    }
+    for (var, value) in state.vars.iter() {
+        lines.push(format!("{} = {}", var, value).to_uppercase());
+    }
+    for (i, line) in lines.iter().take(DEBUG_OVERLAY_LINES).enumerate() {
+        draw_bitmap_text(
+            canvas,
+            line,
+            match i {
+                0 => &resources.font_bold,
+                _ => &resources.font_regular
+            },
+            Color::WHITE,
+            16.0, 16.0 + 16.0 * i as f32
+        );
+    }
+    Ok(())
+}
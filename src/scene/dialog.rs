@@ -0,0 +1,235 @@
+use ::ggez::{
+    *,
+    graphics::*
+};
+use ::std::{
+    mem::take,
+    time::Duration
+};
+use crate::{
+    *,
+    drawing::*,
+    scene::{
+        *,
+        explore::*
+    }
+};
+
+/// A message box drawn over the scene below, typing out text a page at a
+/// time and asking to choose one of `options` at the end if there are any.
+/// Pops with the `SceneResult::Answer` once there is one.
+#[derive(Clone, Debug)]
+pub struct DialogScene {
+    /// The text, wrapped to fit the box.
+    pub lines: Vec<String>,
+    pub page: usize,
+    /// Characters of the current page typed out so far.
+    pub shown: f32,
+    pub options: Vec<String>,
+    pub selected: usize
+}
+
+impl DialogScene {
+    pub fn new(text: &[String], options: Vec<String>) -> Self {
+        DialogScene {
+            lines: wrap_text(text, DIALOG_WIDTH),
+            page: 0,
+            shown: 0.0,
+            options,
+            selected: 0
+        }
+    }
+
+    pub fn pages(&self) -> usize {
+        self.lines.len().div_ceil(DIALOG_LINES).max(1)
+    }
+
+    pub fn page_lines(&self) -> &[String] {
+        let start = (self.page * DIALOG_LINES).min(self.lines.len());
+        let end = (start + DIALOG_LINES).min(self.lines.len());
+        &self.lines[start..end]
+    }
+
+    /// Whether the current page is typed out completely.
+    pub fn revealed(&self) -> bool {
+        let len = self.page_lines().iter().map(|line| line.chars().count()).sum::<usize>();
+        self.shown >= len as f32
+    }
+
+    /// Whether the question is showing, waiting for an answer.
+    pub fn asking(&self) -> bool {
+        !self.options.is_empty() && self.page + 1 == self.pages() && self.revealed()
+    }
+}
+
+/// Characters that fit on a line of the box.
+pub const DIALOG_WIDTH: usize = 24;
+/// Lines of text on a page of the box, with one more line below them for
+/// the prompt.
+pub const DIALOG_LINES: usize = 4;
+pub const DIALOG_HEIGHT: f32 = 16.0 * (DIALOG_LINES + 2) as f32;
+pub const DIALOG_BORDER_RECT: Rect = Rect {
+    x: VIEWPORT_LEFT - 1.0,
+    y: VIEWPORT_TOP + VIEWPORT_HEIGHT - DIALOG_HEIGHT,
+    w: VIEWPORT_WIDTH + 2.0,
+    h: DIALOG_HEIGHT + 1.0
+};
+pub const TYPEWRITER_CHARS_PER_SECOND: f32 = 60.0;
+
+/// Splits each line of `text` into lines of at most `width` characters,
+/// breaking between words where possible and within them otherwise.
+pub fn wrap_text(text: &[String], width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            if !line.is_empty() && line.len() + 1 + word.len() <= width {
+                line.push(' ');
+                line.push_str(word);
+                continue;
+            }
+            if !line.is_empty() {
+                lines.push(take(&mut line));
+            }
+            let mut word = word;
+            while word.len() > width {
+                let (head, tail) = word.split_at(width);
+                lines.push(head.to_string());
+                word = tail;
+            }
+            line.push_str(word);
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+impl Scene for DialogScene {
+    fn action(&mut self, action: Action, _game: &mut Game) -> GameResult<Transition> {
+        let options = self.options.len();
+        match action {
+            Action::Confirm => {
+                if !self.revealed() {
+                    self.shown = f32::INFINITY;
+                } else if self.page + 1 < self.pages() {
+                    self.page += 1;
+                    self.shown = 0.0;
+                } else if self.options.is_empty() {
+                    return Ok(Transition::Pop(SceneResult::None));
+                } else {
+                    return Ok(Transition::Pop(SceneResult::Answer(self.selected)));
+                }
+            },
+            Action::Up if self.asking() => {
+                self.selected = (self.selected + options - 1) % options;
+            },
+            Action::Down if self.asking() => {
+                self.selected = (self.selected + 1) % options;
+            },
+            _ => {}
+        }
+        Ok(Transition::None)
+    }
+
+    fn update(&mut self, delta: Duration, _game: &mut Game) -> GameResult<Transition> {
+        if !self.revealed() {
+            self.shown += delta.as_secs_f32() * TYPEWRITER_CHARS_PER_SECOND;
+        }
+        Ok(Transition::None)
+    }
+
+    fn draw(
+        &self,
+        ctx: &mut Context,
+        canvas: &mut Canvas,
+        game: &Game,
+        resources: &Resources
+    ) -> GameResult {
+        // draw the current page as far as it is typed out
+        draw_rect(
+            ctx, canvas,
+            &rect_points(DIALOG_BORDER_RECT),
+            Color::WHITE,
+            Color::BLACK
+        )?;
+        let mut left = self.shown as usize;
+        for (i, line) in self.page_lines().iter().enumerate() {
+            let shown = line.chars().take(left).collect::<String>();
+            left = left.saturating_sub(line.chars().count());
+            draw_bitmap_text(
+                canvas,
+                shown,
+                &resources.font_regular,
+                Color::WHITE,
+                VIEWPORT_LEFT + 8.0,
+                DIALOG_BORDER_RECT.y + 8.0 + 16.0 * i as f32
+            );
+        }
+        if self.revealed() && self.page + 1 < self.pages() {
+            draw_bitmap_text(
+                canvas,
+                "MORE...",
+                &resources.font_bold,
+                Color::WHITE,
+                VIEWPORT_LEFT + VIEWPORT_WIDTH - 8.0 - 16.0 * 7.0,
+                DIALOG_BORDER_RECT.y + 8.0 + 16.0 * DIALOG_LINES as f32
+            );
+        }
+
+        // draw the options above the box, once there is nothing more to read
+        if self.asking() {
+            let width = self.options.iter().map(String::len).max().unwrap_or(0);
+            let w = 16.0 * (width + 2) as f32 + 16.0;
+            let h = 16.0 * self.options.len() as f32 + 16.0;
+            let x = VIEWPORT_LEFT + VIEWPORT_WIDTH - w;
+            let y = DIALOG_BORDER_RECT.y - h;
+            draw_rect(
+                ctx, canvas,
+                &rect_points(Rect { x, y, w: w + 1.0, h: h + 1.0 }),
+                Color::WHITE,
+                Color::BLACK
+            )?;
+            for (i, option) in self.options.iter().enumerate() {
+                let y = y + 8.0 + 16.0 * i as f32;
+                if i == self.selected {
+                    draw_bitmap_text(
+                        canvas,
+                        ">",
+                        &resources.font_bold,
+                        Color::WHITE,
+                        x + 8.0, y
+                    );
+                }
+                draw_bitmap_text(
+                    canvas,
+                    option,
+                    &resources.font_regular,
+                    Color::WHITE,
+                    x + 40.0, y
+                );
+            }
+        }
+
+        // draw control panel
+        let action = if !self.revealed() {
+            "SKIP"
+        } else if self.page + 1 < self.pages() {
+            "MORE"
+        } else if !self.options.is_empty() {
+            "ANSWER"
+        } else {
+            "CONTINUE"
+        };
+        draw_controls(
+            ctx, canvas,
+            game, resources,
+            &[(Action::Confirm, action)]
+        )?;
+
+        Ok(())
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
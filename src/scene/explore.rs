@@ -1,10 +1,10 @@
 use ::ggez::{
     *,
     graphics::*,
-    input::keyboard::*,
     mint::Point2
 };
 use ::std::{
+    f32::consts::PI,
     mem::take,
     time::Duration
 };
@@ -14,14 +14,40 @@ use crate::{
     model::*,
     scene::{
         *,
+        automap::*,
+        dialog::*,
         explore::ExploreAnimation::*
     }
 };
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct ExploreScene {
     pub anim: Option<ExploreAnimation>,
-    pub selected: Option<usize>
+    pub selected: Option<usize>,
+    /// What happened at the last wall or cell the party interacted with,
+    /// shown until the next key press.
+    pub messages: Vec<String>,
+    /// The question of the events the party set off, while it is asked.
+    pub choice: Option<Choice>
+}
+
+impl ExploreScene {
+    /// Shows what running events led to in a message box, asking the
+    /// question they stopped at. A battle they started begins once the box
+    /// is closed.
+    fn show_outcome(&mut self, outcome: EventOutcome) -> Transition {
+        if let Some(enemies) = outcome.battle {
+            self.anim = Some(Encounter(ENCOUNTER_DURATION, enemies));
+        }
+        if outcome.messages.is_empty() && outcome.choice.is_none() {
+            return Transition::None;
+        }
+        let options = outcome.choice.as_ref()
+            .map(|choice| choice.options.clone())
+            .unwrap_or_default();
+        self.choice = outcome.choice;
+        Transition::Push(Box::new(DialogScene::new(&outcome.messages, options)))
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -29,7 +55,11 @@ pub enum ExploreAnimation {
     StepBackward(Duration),
     StepForward(Duration),
     StepLeft(Duration),
-    StepRight(Duration)
+    StepRight(Duration),
+    LeaveFloor(Duration),
+    EnterFloor(Duration),
+    /// The viewport flashes before the battle with `EnemyParty` starts.
+    Encounter(Duration, EnemyParty)
 }
 
 pub const VIEWPORT_LEFT: f32 = 16.0;
@@ -72,6 +102,8 @@ pub const CONTROLS_WIDTH: f32 = 192.0;
 pub const CONTROLS_HEIGHT: f32 = PARTYLIST_HEIGHT;
 // pub const CONTROLS_RIGHT: f32 = CONTROLS_LEFT + CONTROLS_WIDTH;
 // pub const CONTROLS_BOTTOM: f32 = CONTROLS_TOP + CONTROLS_HEIGHT;
+/// The most glyphs of a key or button the control panel shows.
+pub const MAX_LABEL_LEN: usize = 3;
 pub const CONTROLS_BORDER_RECT: Rect = Rect {
     x: CONTROLS_LEFT - 1.0,
     y: CONTROLS_TOP - 1.0,
@@ -79,6 +111,17 @@ pub const CONTROLS_BORDER_RECT: Rect = Rect {
     h: CONTROLS_HEIGHT + 2.0
 };
 
+pub const LOCATION_LEFT: f32 = CONTROLS_LEFT;
+pub const LOCATION_TOP: f32 = VIEWPORT_TOP;
+pub const LOCATION_WIDTH: f32 = CONTROLS_WIDTH;
+pub const LOCATION_HEIGHT: f32 = 48.0;
+pub const LOCATION_BORDER_RECT: Rect = Rect {
+    x: LOCATION_LEFT - 1.0,
+    y: LOCATION_TOP - 1.0,
+    w: LOCATION_WIDTH + 2.0,
+    h: LOCATION_HEIGHT + 2.0
+};
+
 pub const WALL_BASE_POINTS: &[(f32, f32)] = &[
     (0.5, 0.5),
     (-0.5, 0.5),
@@ -99,19 +142,14 @@ pub const FLOOR_BASE_POINTS: &[(f32, f32)] = &[
 ];
 
 pub const STEP_DURATION: Duration = Duration::from_millis(200);
-
-fn expect_explore(scene: &Scene) -> &ExploreScene {
-    match scene {
-        Scene::Explore(explore_scene) => explore_scene,
-        _ => unimplemented!()
-    }
-}
-fn expect_explore_mut(scene: &mut Scene) -> &mut ExploreScene {
-    match scene {
-        Scene::Explore(explore_scene) => explore_scene,
-        _ => unimplemented!()
-    }
-}
+pub const FLOOR_CHANGE_DURATION: Duration = Duration::from_millis(500);
+pub const PIT_DAMAGE: u32 = 2;
+/// Cells lit around the party by the `Light` spell.
+pub const LIGHT_RADIUS: isize = 1;
+pub const ENCOUNTER_DURATION: Duration = Duration::from_millis(600);
+pub const ENCOUNTER_FLASHES: f32 = 3.0;
+/// Steps after an encounter during which no other encounter happens.
+pub const ENCOUNTER_GRACE_STEPS: u32 = 4;
 
 fn viewport_point(dx: f32, dy: f32, dz: f32) -> Point2<f32> {
     let w = INITIAL_WIDTH * HORIZ_VANISH_RATE.powf(dy);
@@ -122,125 +160,103 @@ fn viewport_point(dx: f32, dy: f32, dz: f32) -> Point2<f32> {
     }
 }
 
-pub fn key_down_event(
-    _ctx: &mut Context,
-    input: KeyInput,
-    _repeated: bool,
-    game: &mut Game
-) -> GameResult {
-    let (state, scene) = (&mut game.state, expect_explore_mut(&mut game.scene));
-    if let Some(i) = scene.selected.as_mut() {
-        match input.keycode {
-            Some(KeyCode::Return) => {
-                game.scene = Scene::ViewCharacter(ViewCharacterScene {
-                    i: *i,
-                    selected: ItemSlot::Weapon
-                });
-            }
-            Some(KeyCode::Escape)
-                => scene.selected = None,
-            Some(KeyCode::Up)
-                => *i = (*i + state.party.len() - 1) % state.party.len(),
-            Some(KeyCode::Down)
-                => *i = (*i + 1) % state.party.len(),
-            _   => {}
-        }
-    } else {
-        match input.keycode {
-            Some(KeyCode::Up | KeyCode::W) => {
-                if state.level.wall_towards(state.pos, state.dir).is_passable() {
-                    scene.anim = Some(StepForward(STEP_DURATION));
-                }
-            },
-            Some(KeyCode::S) => {
-                if state.level.wall_towards(state.pos, state.dir.rev()).is_passable() {
-                    state.pos = state.pos.move_by(state.dir, -1);
-                    scene.anim = Some(StepBackward(STEP_DURATION));
-                }
-            },
-            Some(KeyCode::A) => {
-                if state.level.wall_towards(state.pos, state.dir.left()).is_passable() {
-                    scene.anim = Some(StepLeft(STEP_DURATION));
-                }
-            },
-            Some(KeyCode::D) => {
-                if state.level.wall_towards(state.pos, state.dir.right()).is_passable() {
-                    scene.anim = Some(StepRight(STEP_DURATION));
-                }
-            },
-            Some(KeyCode::P) => scene.selected = Some(0),
-            Some(KeyCode::I) => {
-                game.scene = Scene::ViewInventory(ViewInventoryScene {
-                    i: 0,
-                    parent: Box::new(take(&mut game.scene)),
-                    pred: ItemPredicate::Usable
-                });
+/// Takes the stairs, ladder or pit the party is standing on.
+fn change_floor(state: &mut State) {
+    let Some(feature) = state.feature().cloned() else {
+        return;
+    };
+    if let Some((floor, pos)) = feature.destination(state.floor) {
+        if let Feature::Pit(_) = feature {
+            for character in state.party.iter_mut().filter(|c| c.is_alive()) {
+                character.hp = character.hp.saturating_sub(PIT_DAMAGE).max(1);
             }
-            Some(KeyCode::Down) => state.dir = state.dir.rev(),
-            Some(KeyCode::Left) => state.dir = state.dir.left(),
-            Some(KeyCode::Right) => state.dir = state.dir.right(),
-            _ => {}
         }
+        state.floor = floor;
+        state.pos = pos;
     }
-    Ok(())
 }
 
-fn finish_anim(
-    pos: &mut Position,
-    dir: &mut Direction,
-    anim: &mut Option<ExploreAnimation>
-) -> GameResult {
-    match anim {
-        Some(StepBackward(_)) => {},
-        Some(StepForward(_)) => {
-            *pos = pos.move_by(*dir, 1);
-        },
-        Some(StepLeft(_)) => {
-            *pos = pos.move_by(dir.left(), 1);
-        },
-        Some(StepRight(_)) => {
-            *pos = pos.move_by(dir.right(), 1);
-        },
-        _ => {}
+/// Counts a step, wearing off spells and statuses, and rolls for a random
+/// encounter in the zone the party is standing in.
+fn count_step(state: &mut State) -> Option<EnemyParty> {
+    state.encounter_steps = state.encounter_steps.saturating_add(1);
+    state.light_steps = state.light_steps.saturating_sub(1);
+    for character in state.party.iter_mut() {
+        character.step_statuses();
     }
-    *anim = None;
-    Ok(())
+    if state.ward_steps > 0 {
+        state.ward_steps -= 1;
+        return None;
+    }
+    if state.encounter_steps <= ENCOUNTER_GRACE_STEPS {
+        return None;
+    }
+    let zone = state.dungeon.floors[state.floor].zone_at(state.pos)?;
+    if !state.rng.encounters.chance(zone.rate) {
+        return None;
+    }
+    let enemies = zone.roll_party(&mut state.rng.encounters)?;
+    state.encounter_steps = 0;
+    Some(enemies)
 }
 
-pub fn update(ctx: &mut Context, game: &mut Game) -> GameResult {
-    let (state, scene) = (&mut game.state, expect_explore_mut(&mut game.scene));
-
-    match &mut scene.anim {
-        Some(
-            StepBackward(dur)
-            | StepForward(dur)
-            | StepLeft(dur)
-            | StepRight(dur)
-        ) => {
-            if ctx.time.delta() >= *dur {
-                finish_anim(&mut state.pos, &mut state.dir, &mut scene.anim)?;
-            } else {
-                *dur -= ctx.time.delta();
-            }
+/// Ends the animation that just played out. Returns what the events of a cell
+/// the party stepped onto led to, for the caller to show.
+fn finish_anim(
+    state: &mut State,
+    items: &ItemCatalog,
+    anim: &mut Option<ExploreAnimation>,
+    messages: &mut Vec<String>
+) -> GameResult<Option<EventOutcome>> {
+    let stepped = matches!(
+        anim,
+        Some(StepBackward(_) | StepForward(_) | StepLeft(_) | StepRight(_))
+    );
+    let step_dir = match anim {
+        Some(StepForward(_)) => Some(state.dir),
+        Some(StepLeft(_)) => Some(state.dir.left()),
+        Some(StepRight(_)) => Some(state.dir.right()),
+        _ => None
+    };
+    match anim {
+        Some(LeaveFloor(_)) => {
+            change_floor(state);
+            *anim = Some(EnterFloor(FLOOR_CHANGE_DURATION));
         },
-        _ => {}
+        Some(EnterFloor(_)) => *anim = None,
+        _ => {
+            let new_pos = step_dir.and_then(|d| state.pos.move_by(d, 1, state.level()));
+            if let Some(new_pos) = new_pos {
+                state.pos = new_pos;
+            }
+            *anim = match state.level().features.get(&state.pos) {
+                Some(Feature::Stairs(..) | Feature::Pit(_))
+                    => Some(LeaveFloor(FLOOR_CHANGE_DURATION)),
+                _ => None
+            };
+            if anim.is_none() && stepped {
+                *messages = state.trigger_feature(items);
+                let outcome = state.run_events(&Trigger::Step, items);
+                // battles started by events take the place of random ones
+                let enemies = count_step(state);
+                if outcome.as_ref().is_none_or(|outcome| outcome.battle.is_none()) {
+                    *anim = enemies.map(|enemies| Encounter(ENCOUNTER_DURATION, enemies));
+                }
+                return Ok(outcome);
+            }
+        }
     }
-    Ok(())
+    Ok(None)
 }
 
-fn draw_wall_rect(
-    ctx: &mut Context,
-    canvas: &mut Canvas,
+fn wall_rect_points(
     base_points: &[(f32, f32)],
     x: f32,
     y: f32,
     front: bool,
     anim: &Option<ExploreAnimation>
-) -> GameResult {
-    let intensity = INTENSITY_VANISH.powi(y as i32);
-    let color = Color::new(intensity, intensity, intensity, 1.0);
-
-    let points = base_points.into_iter().map(|&(du, dz)| {
+) -> Vec<Point2<f32>> {
+    base_points.into_iter().map(|&(du, dz)| {
         let (mut dx, mut dy) = (x, y);
         if front {
             dx += du;
@@ -254,8 +270,7 @@ fn draw_wall_rect(
             _ => {}
         };
         viewport_point(dx, dy, dz)
-    }).collect::<Vec<_>>();
-    draw_rect(ctx, canvas, &points, color, Color::BLACK)
+    }).collect()
 }
 
 fn draw_floor_rect(
@@ -293,11 +308,23 @@ fn draw_wall(
 ) -> GameResult {
     let dx = x;
     let dy = y as f32 + 0.5;
-    if wall == Wall::Some || wall == Wall::Door {
-        draw_wall_rect(ctx, canvas, WALL_BASE_POINTS, dx, dy, front, anim)?;
-    }
-    if wall == Wall::Door {
-        draw_wall_rect(ctx, canvas, DOOR_BASE_POINTS, dx, dy, front, anim)?;
+    let intensity = INTENSITY_VANISH.powi(dy as i32);
+    let color = Color::new(intensity, intensity, intensity, 1.0);
+    let wall_points = wall_rect_points(WALL_BASE_POINTS, dx, dy, front, anim);
+    let door_points = wall_rect_points(DOOR_BASE_POINTS, dx, dy, front, anim);
+    match wall {
+        Wall::Some | Wall::Secret | Wall::OneWay(_) => {
+            draw_rect(ctx, canvas, &wall_points, color, Color::BLACK)?;
+        },
+        // open doors leave an empty frame to look through
+        Wall::Door(Door { open: true, .. }) => {
+            draw_rect(ctx, canvas, &door_points, color, TRANSPARENT)?;
+        },
+        Wall::Door(_) => {
+            draw_rect(ctx, canvas, &wall_points, color, Color::BLACK)?;
+            draw_rect(ctx, canvas, &door_points, color, Color::BLACK)?;
+        },
+        Wall::None => {}
     }
     Ok(())
 }
@@ -308,11 +335,13 @@ pub fn draw_viewport(
     level: &Level,
     pos: &Position,
     dir: &Direction,
+    dark: bool,
     anim: &Option<ExploreAnimation>
 ) -> GameResult {
     let mut render_points = Vec::new();
+    // darkness leaves nothing but the frame of the viewport
     for x in (-MAX_VANISH_DIST)..=MAX_VANISH_DIST {
-        for y in 0..=MAX_VANISH_DIST {
+        for y in (0..=MAX_VANISH_DIST).filter(|_| !dark) {
             render_points.push((x, y));
         }
     }
@@ -322,6 +351,10 @@ pub fn draw_viewport(
         d2.partial_cmp(&d1).unwrap() // reversed!
     });
     for &(x, y) in render_points.iter() {
+        // nothing to see past the hard edges of a level
+        let Some(wall_pos) = pos.translate(*dir, x, y, level) else {
+            continue;
+        };
         draw_floor_rect(
             ctx, canvas,
             FLOOR_BASE_POINTS,
@@ -330,30 +363,54 @@ pub fn draw_viewport(
             anim
         )?;
 
-        let wall_pos = pos.translate(*dir, x, y);
         draw_wall(
             ctx, canvas,
-            level.wall_towards(wall_pos, *dir),
+            level.looks_towards(wall_pos, *dir),
             x as f32, y as f32 + 0.5,
             true,
             anim
         )?;
         draw_wall(
             ctx, canvas,
-            level.wall_towards(wall_pos, dir.left()),
+            level.looks_towards(wall_pos, dir.left()),
             x as f32 - 0.5, y as f32,
             false,
             anim
         )?;
         draw_wall(
             ctx, canvas,
-            level.wall_towards(wall_pos, dir.right()),
+            level.looks_towards(wall_pos, dir.right()),
             x as f32 + 0.5, y as f32,
             false,
             anim
         )?;
     }
 
+    // fade to black while changing floors
+    let total = FLOOR_CHANGE_DURATION.as_secs_f32();
+    let fade = match anim {
+        Some(LeaveFloor(dur)) => 1.0 - dur.as_secs_f32() / total,
+        Some(EnterFloor(dur)) => dur.as_secs_f32() / total,
+        _ => 0.0
+    };
+    if fade > 0.0 {
+        draw_rect(
+            ctx, canvas,
+            &rect_points(VIEWPORT_BORDER_RECT),
+            TRANSPARENT, Color::new(0.0, 0.0, 0.0, fade)
+        )?;
+    }
+    // flash when monsters appear
+    if let Some(Encounter(dur, _)) = anim {
+        let t = 1.0 - dur.as_secs_f32() / ENCOUNTER_DURATION.as_secs_f32();
+        let flash = (t * ENCOUNTER_FLASHES * PI).sin().abs();
+        draw_rect(
+            ctx, canvas,
+            &rect_points(VIEWPORT_BORDER_RECT),
+            TRANSPARENT, Color::new(1.0, 1.0, 1.0, flash)
+        )?;
+    }
+
     draw_rect(
         ctx, canvas,
         &rect_points(VIEWPORT_BORDER_RECT),
@@ -387,7 +444,7 @@ pub fn draw_partylist(
 
     draw_bitmap_text(
         canvas,
-        "# NAME      HP  MP",
+        "# NAME      HP  MP  ST",
         &resources.font_bold,
         Color::WHITE,
         VIEWPORT_LEFT + 8.0,
@@ -414,27 +471,88 @@ pub fn draw_partylist(
             );
         }
         let line = format!(
-            "{:9} {:<3} {:<3}",
+            "{:9} {:<3} {:<3} {}",
             character.name,
             character.hp,
-            character.mp
+            character.mp,
+            character.worst_status().map_or("OK", |status| status.abbrev())
         );
         draw_bitmap_text(
             canvas,
             &line,
             &resources.font_regular,
-            Color::WHITE,
+            match character.can_act() {
+                true => Color::WHITE,
+                false => GRAY
+            },
             x + 32.0, y
         );
     }
     Ok(())
 }
 
+pub fn draw_location(
+    ctx: &mut Context,
+    canvas: &mut Canvas,
+    resources: &Resources,
+    floor: usize,
+    level: &Level
+) -> GameResult {
+    draw_rect(
+        ctx, canvas,
+        &rect_points(LOCATION_BORDER_RECT),
+        Color::WHITE,
+        Color::BLACK
+    )?;
+
+    draw_bitmap_text(
+        canvas,
+        format!("FLOOR {}", floor + 1),
+        &resources.font_bold,
+        Color::WHITE,
+        LOCATION_LEFT + 8.0,
+        LOCATION_TOP + 8.0
+    );
+    draw_bitmap_text(
+        canvas,
+        &level.name,
+        &resources.font_regular,
+        Color::WHITE,
+        LOCATION_LEFT + 8.0,
+        LOCATION_TOP + 24.0
+    );
+    Ok(())
+}
+
+/// Draws the control panel, labeling each action with what it is bound to.
+/// Actions bound to nothing are left out.
 pub fn draw_controls(
     ctx: &mut Context,
     canvas: &mut Canvas,
+    game: &Game,
     resources: &Resources,
-    controls: &[(&str, &str)]
+    controls: &[(Action, &str)]
+) -> GameResult {
+    let labeled = controls.iter()
+        .filter_map(|&(action, line)| {
+            let label = game.settings.bindings.label(action, game.gamepad)?;
+            // cut long key names like NUMPAD0 so the lines stay in the panel
+            let label = label.chars().take(MAX_LABEL_LEN).collect::<String>();
+            Some((format!("[{}]", label), line))
+        })
+        .collect::<Vec<_>>();
+    let lines = labeled.iter()
+        .map(|(key, line)| (key.as_str(), *line))
+        .collect::<Vec<_>>();
+    draw_panel(ctx, canvas, resources, &lines)
+}
+
+/// Draws lines into the control panel, each with a bold column on the left.
+pub fn draw_panel(
+    ctx: &mut Context,
+    canvas: &mut Canvas,
+    resources: &Resources,
+    lines: &[(&str, &str)]
 ) -> GameResult {
     draw_rect(
         ctx, canvas,
@@ -443,9 +561,13 @@ pub fn draw_controls(
         Color::BLACK
     )?;
 
-    for (i, (key, line)) in controls.iter().enumerate() {
+    // the bold column is 4 glyphs wide, and 5 for labels of 3 glyphs in
+    // brackets, which push the lines one glyph to the right
+    let width = lines.iter().map(|(key, _)| key.len()).max().unwrap_or(0)
+        .clamp(4, MAX_LABEL_LEN + 2);
+    for (i, (key, line)) in lines.iter().enumerate() {
         let x1 = CONTROLS_LEFT + 8.0;
-        let x2 = x1 + 64.0;
+        let x2 = x1 + 16.0 * width as f32;
         let y = CONTROLS_TOP + 8.0 + 16.0 * i as f32;
         draw_bitmap_text(
             canvas,
@@ -465,39 +587,267 @@ pub fn draw_controls(
     Ok(())
 }
 
-pub fn draw(ctx: &mut Context, game: &Game) -> GameResult {
-    let (state, scene) = (&game.state, expect_explore(&game.scene));
-    // set up canvas
-    let mut canvas = Canvas::from_frame(ctx, Color::BLACK);
-    canvas.set_sampler(Sampler::nearest_clamp());
+impl Scene for ExploreScene {
+    fn action(&mut self, action: Action, game: &mut Game) -> GameResult<Transition> {
+        let state = &mut game.state;
+        if matches!(self.anim, Some(LeaveFloor(_) | EnterFloor(_) | Encounter(..))) {
+            return Ok(Transition::None);
+        }
+        if let Some(i) = self.selected.as_mut() {
+            match action {
+                Action::Confirm => {
+                    return Ok(Transition::Push(Box::new(ViewCharacterScene {
+                        i: *i,
+                        selected: ItemSlot::Weapon,
+                        stat: None
+                    })));
+                }
+                Action::Cancel
+                    => self.selected = None,
+                Action::Up
+                    => *i = (*i + state.party.len() - 1) % state.party.len(),
+                Action::Down
+                    => *i = (*i + 1) % state.party.len(),
+                _   => {}
+            }
+        } else {
+            self.messages.clear();
+            match action {
+                Action::MoveForward => {
+                    if state.level().can_move(state.pos, state.dir) {
+                        self.anim = Some(StepForward(STEP_DURATION));
+                    }
+                },
+                Action::MoveBack => {
+                    if state.level().can_move(state.pos, state.dir.rev()) {
+                        state.pos = state.pos.move_by(state.dir, -1, state.level())
+                            .unwrap_or(state.pos);
+                        self.anim = Some(StepBackward(STEP_DURATION));
+                    }
+                },
+                Action::StrafeLeft => {
+                    if state.level().can_move(state.pos, state.dir.left()) {
+                        self.anim = Some(StepLeft(STEP_DURATION));
+                    }
+                },
+                Action::StrafeRight => {
+                    if state.level().can_move(state.pos, state.dir.right()) {
+                        self.anim = Some(StepRight(STEP_DURATION));
+                    }
+                },
+                Action::Interact => {
+                    // events on the wall take the place of opening or searching it
+                    match state.run_events(&Trigger::Wall(state.dir), &game.items) {
+                        Some(outcome) => return Ok(self.show_outcome(outcome)),
+                        None => self.messages = state.interact().into_iter().collect()
+                    }
+                },
+                Action::Climb => {
+                    if let Some(Feature::Ladder(..)) = state.level().features.get(&state.pos) {
+                        self.anim = Some(LeaveFloor(FLOOR_CHANGE_DURATION));
+                    }
+                },
+                Action::OpenGuild if state.at_entrance() => {
+                    return Ok(Transition::Replace(Box::new(GuildScene {
+                        i: 0,
+                        swapping: None,
+                        message: None
+                    })));
+                },
+                Action::OpenParty => self.selected = Some(0),
+                Action::OpenAutomap => return Ok(Transition::Push(Box::new(AutomapScene {}))),
+                Action::OpenSpells => {
+                    return Ok(Transition::Push(Box::new(SpellsScene {
+                        phase: SpellsPhase::ChooseCaster { selected: 0 },
+                        messages: Vec::new()
+                    })));
+                },
+                Action::ToggleMinimap => game.settings.minimap = !game.settings.minimap,
+                Action::SaveGame => {
+                    let save_load_scene = SaveLoadScene::new(SaveLoadMode::Save, &game.items);
+                    return Ok(Transition::Push(Box::new(save_load_scene)));
+                },
+                Action::LoadGame => {
+                    let save_load_scene = SaveLoadScene::new(SaveLoadMode::Load, &game.items);
+                    return Ok(Transition::Push(Box::new(save_load_scene)));
+                },
+                Action::OpenInventory => {
+                    return Ok(Transition::Push(Box::new(ViewInventoryScene::new(
+                        ItemPredicate::Usable
+                    ))));
+                }
+                Action::TurnAround => state.dir = state.dir.rev(),
+                Action::TurnLeft => state.dir = state.dir.left(),
+                Action::TurnRight => state.dir = state.dir.right(),
+                _ => {}
+            }
+        }
+        Ok(Transition::None)
+    }
 
-    draw_viewport(
-        ctx, &mut canvas,
-        &state.level, &state.pos, &state.dir,
-        &scene.anim
-    )?;
+    fn update(&mut self, delta: Duration, game: &mut Game) -> GameResult<Transition> {
+        let state = &mut game.state;
 
-    draw_partylist(
-        ctx, &mut canvas,
-        &game.resources,
-        &state.party,
-        &scene.anim,
-        scene.selected
-    )?;
-    // draw control panel
-    if scene.selected.is_some() {
-        draw_controls(
-            ctx, &mut canvas,
-            &game.resources,
-            &[("[\n]", "DETAILS"), ("[\x1b]", "BACK")]
+        match &mut self.anim {
+            Some(
+                StepBackward(dur)
+                | StepForward(dur)
+                | StepLeft(dur)
+                | StepRight(dur)
+                | LeaveFloor(dur)
+                | EnterFloor(dur)
+                | Encounter(dur, _)
+            ) => {
+                if delta < *dur {
+                    *dur -= delta;
+                } else if let Some(Encounter(_, enemies)) = &self.anim {
+                    // the party is back to exploring once the battle pops
+                    let enemies = enemies.clone();
+                    self.anim = None;
+                    return Ok(Transition::Push(Box::new(BattleScene::new(enemies, state))));
+                } else {
+                    let outcome = finish_anim(
+                        state,
+                        &game.items,
+                        &mut self.anim,
+                        &mut self.messages
+                    )?;
+                    if let Some(mut outcome) = outcome {
+                        // what the cell itself did is told along with its events
+                        let mut messages = take(&mut self.messages);
+                        messages.append(&mut outcome.messages);
+                        outcome.messages = messages;
+                        return Ok(self.show_outcome(outcome));
+                    }
+                }
+            },
+            _ => {}
+        }
+        if self.anim.is_none() && !state.in_darkness() {
+            let (pos, dir) = (state.pos, state.dir);
+            state.level_mut().reveal(pos, dir, MAX_VANISH_DIST);
+            if state.light_steps > 0 {
+                state.level_mut().reveal_around(pos, LIGHT_RADIUS);
+            }
+        }
+        Ok(Transition::None)
+    }
+
+    /// Shows what the events the party set off elsewhere led to, and goes on
+    /// with the events waiting for an answer.
+    fn resume(&mut self, result: SceneResult, game: &mut Game) -> GameResult<Transition> {
+        let state = &mut game.state;
+        Ok(match result {
+            SceneResult::Events(outcome) => self.show_outcome(outcome),
+            SceneResult::Answer(answer) => match self.choice.take() {
+                Some(choice) => {
+                    let outcome = state.answer(choice, answer, &game.items);
+                    self.show_outcome(outcome)
+                },
+                None => Transition::None
+            },
+            _ => Transition::None
+        })
+    }
+
+    fn draw(
+        &self,
+        ctx: &mut Context,
+        canvas: &mut Canvas,
+        game: &Game,
+        resources: &Resources
+    ) -> GameResult {
+        let state = &game.state;
+        draw_viewport(
+            ctx, canvas,
+            state.level(), &state.pos, &state.dir,
+            state.in_darkness(),
+            &self.anim
         )?;
-    } else {
-        draw_controls(
-            ctx, &mut canvas,
-            &game.resources,
-            &[("[P]", "PARTY"), ("[I]", "ITEMS")]
+        if !self.messages.is_empty() {
+            let height = 16.0 * (self.messages.len() + 1) as f32;
+            draw_rect(
+                ctx, canvas,
+                &rect_points(Rect {
+                    x: VIEWPORT_LEFT - 1.0,
+                    y: VIEWPORT_TOP + VIEWPORT_HEIGHT - height,
+                    w: VIEWPORT_WIDTH + 2.0,
+                    h: height + 1.0
+                }),
+                Color::WHITE,
+                Color::BLACK
+            )?;
+            for (i, line) in self.messages.iter().enumerate() {
+                draw_bitmap_text(
+                    canvas,
+                    line,
+                    &resources.font_regular,
+                    Color::WHITE,
+                    VIEWPORT_LEFT + 8.0,
+                    VIEWPORT_TOP + VIEWPORT_HEIGHT - height + 8.0 + 16.0 * i as f32
+                );
+            }
+        }
+        draw_location(
+            ctx, canvas,
+            resources,
+            state.floor,
+            state.level()
         )?;
-    }
+        if game.settings.minimap {
+            draw_minimap(ctx, canvas, state)?;
+        }
+
+        draw_partylist(
+            ctx, canvas,
+            resources,
+            &state.party,
+            &self.anim,
+            self.selected
+        )?;
+        // draw control panel
+        if self.selected.is_some() {
+            draw_controls(
+                ctx, canvas,
+                game, resources,
+                &[(Action::Confirm, "DETAILS"), (Action::Cancel, "BACK")]
+            )?;
+        } else {
+            let mut controls = vec![
+                (Action::OpenParty, "PARTY"),
+                (Action::OpenInventory, "ITEMS"),
+                (Action::OpenSpells, "SPELLS"),
+                (Action::OpenAutomap, "MAP"),
+                (Action::SaveGame, "SAVE"),
+                (Action::LoadGame, "LOAD")
+            ];
+            if let Some(Feature::Ladder(..)) = state.level().features.get(&state.pos) {
+                controls.push((Action::Climb, "CLIMB"));
+            }
+            if state.at_entrance() {
+                controls.push((Action::OpenGuild, "GUILD"));
+            }
+            let facing = match state.pos.move_by(state.dir, 1, state.level()) {
+                Some(_) => state.level().looks_towards(state.pos, state.dir),
+                None => Wall::None
+            };
+            let interact = match facing {
+                Wall::Door(door) if door.open => Some("CLOSE"),
+                Wall::Door(_) => Some("OPEN"),
+                Wall::Some => Some("SEARCH"),
+                _ => None
+            };
+            // there is only room left for one more line
+            if let Some(interact) = interact.filter(|_| controls.len() < 7) {
+                controls.push((Action::Interact, interact));
+            }
+            draw_controls(
+                ctx, canvas,
+                game, resources,
+                &controls
+            )?;
+        }
 
-    canvas.finish(ctx)
+        Ok(())
+    }
 }
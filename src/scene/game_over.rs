@@ -0,0 +1,62 @@
+use ::ggez::{
+    *,
+    graphics::*
+};
+use crate::{
+    *,
+    drawing::*,
+    model::*,
+    scene::{
+        *,
+        explore::*
+    }
+};
+
+#[derive(Clone, Debug)]
+pub struct GameOverScene {}
+
+impl Scene for GameOverScene {
+    fn action(&mut self, action: Action, game: &mut Game) -> GameResult<Transition> {
+        match action {
+            Action::Confirm => {
+                // the new game is seeded from the old one, so that replays
+                // play back the same
+                let seed = Rng::new(game.state.rng.seed).next_u64();
+                game.state = Game::initial_state(&game.items, seed)?;
+                Ok(Transition::Reset(Box::new(GuildScene {
+                    i: 0,
+                    swapping: None,
+                    message: None
+                })))
+            },
+            Action::Cancel => Ok(Transition::Quit),
+            _ => Ok(Transition::None)
+        }
+    }
+
+    fn draw(
+        &self,
+        ctx: &mut Context,
+        canvas: &mut Canvas,
+        game: &Game,
+        resources: &Resources
+    ) -> GameResult {
+        let text = "GAME OVER";
+        draw_bitmap_text(
+            canvas,
+            text,
+            &resources.font_bold,
+            Color::WHITE,
+            VIEWPORT_LEFT + (VIEWPORT_WIDTH - 16.0 * text.len() as f32) / 2.0,
+            VIEWPORT_TOP + VIEWPORT_HEIGHT / 2.0 - 8.0
+        );
+        // draw control panel
+        draw_controls(
+            ctx, canvas,
+            game, resources,
+            &[(Action::Confirm, "NEW GAME"), (Action::Cancel, "QUIT")]
+        )?;
+
+        Ok(())
+    }
+}
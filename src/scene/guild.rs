@@ -0,0 +1,246 @@
+use ::ggez::{
+    *,
+    graphics::*
+};
+use crate::{
+    *,
+    drawing::*,
+    model::*,
+    scene::{
+        *,
+        explore::*,
+        view_inventory::*
+    }
+};
+
+/// The guild at the dungeon entrance, where characters wait on the roster
+/// and the party is formed.
+#[derive(Clone, Debug)]
+pub struct GuildScene {
+    /// Indexes the party members first, then the roster.
+    pub i: usize,
+    /// The party member chosen to trade places with another.
+    pub swapping: Option<usize>,
+    pub message: Option<String>
+}
+
+impl Scene for GuildScene {
+    fn action(&mut self, action: Action, game: &mut Game) -> GameResult<Transition> {
+        let state = &mut game.state;
+        let party_len = state.party.len();
+        let len = party_len + state.roster.len();
+        match action {
+            Action::Confirm => {
+                self.message = None;
+                if let Some(j) = self.swapping.take() {
+                    if self.i < party_len {
+                        state.party.swap(j, self.i);
+                    }
+                } else if self.i < party_len {
+                    let character = state.party.remove(self.i);
+                    state.roster.push(character);
+                } else if self.i < len {
+                    if party_len >= MAX_PARTY_SIZE {
+                        self.message = Some("THE PARTY IS FULL.".to_string());
+                    } else {
+                        let character = state.roster.remove(self.i - party_len);
+                        state.party.push(character);
+                        self.i = party_len;
+                    }
+                }
+            },
+            Action::Swap if self.i < party_len => {
+                self.swapping = match self.swapping {
+                    Some(j) if j == self.i => None,
+                    _ => Some(self.i)
+                };
+            },
+            Action::OpenTemple => {
+                return Ok(Transition::Push(Box::new(TempleScene { i: 0, message: None })));
+            },
+            Action::OpenControls => {
+                return Ok(Transition::Push(Box::new(ControlsScene::default())));
+            },
+            Action::NewCharacter => {
+                return Ok(Transition::Push(Box::new(CreateCharacterScene {
+                    phase: CreateCharacterPhase::ChooseClass { selected: 0 }
+                })));
+            },
+            Action::Cancel => {
+                if self.swapping.is_some() {
+                    self.swapping = None;
+                } else if state.party.is_empty() {
+                    self.message = Some("THE PARTY IS EMPTY.".to_string());
+                } else {
+                    return Ok(Transition::Replace(Box::new(ExploreScene::default())));
+                }
+            },
+            Action::Up if len > 0 => {
+                self.i = (self.i + len - 1) % len;
+                self.message = None;
+            },
+            Action::Down if len > 0 => {
+                self.i = (self.i + 1) % len;
+                self.message = None;
+            },
+            _ => {}
+        }
+        Ok(Transition::None)
+    }
+
+    /// Takes in the character created by `CreateCharacterScene`.
+    fn resume(&mut self, result: SceneResult, game: &mut Game) -> GameResult<Transition> {
+        let state = &mut game.state;
+        if let SceneResult::Character(character) = result {
+            self.message = Some(format!("{} JOINS THE GUILD.", character.name));
+            state.roster.push(character);
+            self.i = state.party.len() + state.roster.len() - 1;
+        }
+        Ok(Transition::None)
+    }
+
+    fn draw(
+        &self,
+        ctx: &mut Context,
+        canvas: &mut Canvas,
+        game: &Game,
+        resources: &Resources
+    ) -> GameResult {
+        let state = &game.state;
+        draw_rect(
+            ctx, canvas,
+            &rect_points(INVENTORY_BORDER_RECT),
+            Color::WHITE,
+            Color::BLACK
+        )?;
+
+        // draw the party followed by the roster, with a heading for each
+        let mut rows: Vec<(Option<usize>, Option<&Character>)> = Vec::new();
+        rows.push((None, None));
+        rows.extend(state.party.iter().enumerate().map(|(i, c)| (Some(i), Some(c))));
+        rows.push((None, None));
+        rows.extend(state.roster.iter().enumerate()
+            .map(|(i, c)| (Some(state.party.len() + i), Some(c))));
+        let row_i = rows.iter().position(|(i, _)| *i == Some(self.i)).unwrap_or(0);
+        let skip_len = row_i.saturating_sub(ITEMS_PER_SCREEN / 2)
+            .min(rows.len().saturating_sub(ITEMS_PER_SCREEN));
+        for (j, (i, character)) in rows.iter().enumerate().skip(skip_len)
+            .take(ITEMS_PER_SCREEN)
+        {
+            let y = 24.0 + (j - skip_len) as f32 * 16.0;
+            let Some(character) = character else {
+                draw_bitmap_text(
+                    canvas,
+                    match j {
+                        0 => format!("PARTY {}/{}", state.party.len(), MAX_PARTY_SIZE),
+                        _ => "ROSTER".to_string()
+                    },
+                    &resources.font_bold,
+                    Color::WHITE,
+                    24.0, y
+                );
+                continue;
+            };
+            if *i == Some(self.i) {
+                draw_bitmap_text(
+                    canvas,
+                    ">",
+                    &resources.font_bold,
+                    Color::WHITE,
+                    24.0, y
+                );
+            }
+            draw_bitmap_text(
+                canvas,
+                &character.name,
+                &resources.font_regular,
+                Color::WHITE,
+                56.0, y
+            );
+            draw_bitmap_text(
+                canvas,
+                format!("LV.{}", character.lvl),
+                &resources.font_regular,
+                Color::WHITE,
+                232.0, y
+            );
+            if *i == self.swapping {
+                draw_bitmap_text(
+                    canvas,
+                    "<",
+                    &resources.font_bold,
+                    Color::WHITE,
+                    392.0, y
+                );
+            }
+        }
+        // draw details of the selected character, or the result of the last action
+        draw_rect(
+            ctx, canvas,
+            &rect_points(ITEM_DETAILS_BORDER_RECT),
+            Color::WHITE,
+            Color::BLACK
+        )?;
+        let selected = state.party.iter().chain(&state.roster).nth(self.i);
+        if let Some(message) = &self.message {
+            draw_bitmap_text(
+                canvas,
+                message,
+                &resources.font_regular,
+                Color::WHITE,
+                24.0, 348.0
+            );
+        } else if let Some(character) = selected {
+            draw_bitmap_text(
+                canvas,
+                &character.name,
+                &resources.font_bold,
+                Color::WHITE,
+                24.0, 348.0
+            );
+            let mut lines = vec![
+                format!("{} LV.{}", character.class.name(), character.lvl),
+                format!("HP {}/{}", character.hp, character.max_hp),
+                format!("MP {}/{}", character.mp, character.max_mp)
+            ];
+            if let Some(status) = character.worst_status() {
+                lines.push(status.name().to_string());
+            }
+            for (i, line) in lines.iter().enumerate() {
+                draw_bitmap_text(
+                    canvas,
+                    line,
+                    &resources.font_regular,
+                    Color::WHITE,
+                    24.0, 364.0 + i as f32 * 16.0
+                );
+            }
+        }
+        // draw control panel
+        let in_party = self.i < state.party.len();
+        let mut controls = vec![
+            (Action::Confirm, match (self.swapping, in_party) {
+                (Some(_), _) => "SWAP",
+                (None, true) => "REMOVE",
+                (None, false) => "ADD"
+            })
+        ];
+        if in_party && self.swapping.is_none() {
+            controls.push((Action::Swap, "SWAP"));
+        }
+        controls.push((Action::NewCharacter, "NEW"));
+        controls.push((Action::OpenTemple, "TEMPLE"));
+        controls.push((Action::OpenControls, "CONTROLS"));
+        controls.push((Action::Cancel, match self.swapping {
+            Some(_) => "CANCEL",
+            None => "LEAVE"
+        }));
+        draw_controls(
+            ctx, canvas,
+            game, resources,
+            &controls
+        )?;
+
+        Ok(())
+    }
+}
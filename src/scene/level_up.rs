@@ -0,0 +1,139 @@
+use ::ggez::{
+    *,
+    graphics::*
+};
+use crate::{
+    *,
+    drawing::*,
+    model::*,
+    scene::{
+        *,
+        explore::*,
+        view_character::*
+    }
+};
+
+/// Shows what the party members who leveled up after a battle gained, one
+/// at a time.
+#[derive(Clone, Debug)]
+pub struct LevelUpScene {
+    /// Indices into the party, with the stats from before leveling up.
+    pub level_ups: Vec<(usize, Character)>,
+    pub i: usize
+}
+
+impl Scene for LevelUpScene {
+    fn action(&mut self, action: Action, _game: &mut Game) -> GameResult<Transition> {
+        if action == Action::Confirm {
+            self.i += 1;
+            if self.i >= self.level_ups.len() {
+                return Ok(Transition::Pop(SceneResult::None));
+            }
+        }
+        Ok(Transition::None)
+    }
+
+    fn draw(
+        &self,
+        ctx: &mut Context,
+        canvas: &mut Canvas,
+        game: &Game,
+        resources: &Resources
+    ) -> GameResult {
+        let state = &game.state;
+        draw_rect(
+            ctx, canvas,
+            &rect_points(CHAR_DETAILS_BORDER_RECT),
+            Color::WHITE,
+            Color::BLACK
+        )?;
+
+        // draw stats before and after
+        if let Some((i, before)) = self.level_ups.get(self.i) {
+            let after = &state.party[*i];
+            draw_bitmap_text(
+                canvas,
+                &after.name,
+                &resources.font_bold,
+                Color::WHITE,
+                24.0, 24.0
+            );
+            draw_bitmap_text(
+                canvas,
+                format!("{} LV.{} > LV.{}", after.class.name(), before.lvl, after.lvl),
+                &resources.font_regular,
+                Color::WHITE,
+                24.0, 40.0
+            );
+            for (j, (stat, old, new)) in [
+                ("HP", before.max_hp, after.max_hp),
+                ("MP", before.max_mp, after.max_mp),
+                ("ATK", before.base_atk, after.base_atk),
+                ("DEF", before.base_def, after.base_def),
+                ("MAG", before.base_matk, after.base_matk),
+                ("RES", before.base_mdef, after.base_mdef),
+                ("AGI", before.base_agi, after.base_agi),
+                ("LUC", before.base_luck, after.base_luck),
+            ].iter().enumerate() {
+                let y = 72.0 + j as f32 * 16.0;
+                draw_bitmap_text(
+                    canvas,
+                    stat,
+                    &resources.font_bold,
+                    Color::WHITE,
+                    24.0, y
+                );
+                draw_bitmap_text(
+                    canvas,
+                    format!("{:>3} >", old),
+                    &resources.font_regular,
+                    Color::WHITE,
+                    88.0, y
+                );
+                draw_bitmap_text(
+                    canvas,
+                    new.to_string(),
+                    match new > old {
+                        true => &resources.font_bold,
+                        false => &resources.font_regular
+                    },
+                    match new > old {
+                        true => Color::WHITE,
+                        false => GRAY
+                    },
+                    168.0, y
+                );
+                if new > old {
+                    draw_bitmap_text(
+                        canvas,
+                        format!("(+{})", new - old),
+                        &resources.font_regular,
+                        Color::WHITE,
+                        232.0, y
+                    );
+                }
+            }
+            // spells learned along the way
+            let learned = after.spells().into_iter()
+                .filter(|spell| spell.lvl() > before.lvl)
+                .collect::<Vec<_>>();
+            for (j, spell) in learned.iter().enumerate() {
+                draw_bitmap_text(
+                    canvas,
+                    format!("LEARNED {}!", spell.name()),
+                    &resources.font_regular,
+                    Color::WHITE,
+                    24.0, 216.0 + j as f32 * 16.0
+                );
+            }
+        }
+        // draw control panel
+        draw_controls(
+            ctx, canvas,
+            game, resources,
+            &[(Action::Confirm, "CONTINUE")]
+        )?;
+
+        Ok(())
+    }
+}
@@ -1,9 +1,43 @@
+pub mod automap;
+pub mod battle;
+pub mod controls;
+pub mod create_character;
+pub mod debug_overlay;
+pub mod dialog;
 pub mod explore;
+pub mod game_over;
+pub mod guild;
+pub mod level_up;
+pub mod save_load;
 mod scene;
+pub mod spells;
+mod stack;
+pub mod temple;
 pub mod view_character;
 pub mod view_inventory;
 
+pub use automap::AutomapScene;
+pub use battle::BattleScene;
+pub use controls::ControlsScene;
+pub use create_character::{
+    CreateCharacterPhase,
+    CreateCharacterScene
+};
+pub use dialog::DialogScene;
 pub use explore::ExploreScene;
+pub use game_over::GameOverScene;
+pub use guild::GuildScene;
+pub use level_up::LevelUpScene;
+pub use save_load::{
+    SaveLoadMode,
+    SaveLoadScene
+};
 pub use scene::*;
+pub use spells::{
+    SpellsPhase,
+    SpellsScene
+};
+pub use stack::*;
+pub use temple::TempleScene;
 pub use view_character::ViewCharacterScene;
 pub use view_inventory::ViewInventoryScene;
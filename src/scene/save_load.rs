@@ -0,0 +1,192 @@
+use ::ggez::{
+    *,
+    graphics::*
+};
+use crate::{
+    *,
+    drawing::*,
+    model::*,
+    scene::{
+        *,
+        explore::*,
+        view_inventory::*
+    }
+};
+
+#[derive(Clone, Debug)]
+pub struct SaveLoadScene {
+    pub mode: SaveLoadMode,
+    pub i: usize,
+    /// Describes the contents of each slot, `None` for empty slots.
+    pub slots: Vec<Option<String>>,
+    pub message: Option<String>
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SaveLoadMode {
+    Save,
+    Load
+}
+
+impl SaveLoadScene {
+    pub fn new(mode: SaveLoadMode, items: &ItemCatalog) -> Self {
+        SaveLoadScene {
+            mode,
+            i: 0,
+            slots: (0..SAVE_SLOTS).map(|slot| describe_slot(slot, items)).collect(),
+            message: None
+        }
+    }
+}
+
+fn describe_slot(slot: usize, items: &ItemCatalog) -> Option<String> {
+    if !slot_exists(slot) {
+        return None;
+    }
+    Some(match read_save(slot, items) {
+        Ok(Ok(state)) => format!("FLOOR {} {}", state.floor + 1, state.level().name),
+        Ok(Err(SaveError::IncompatibleVersion(version)))
+            => format!("UNSUPPORTED VERSION {}", version),
+        Ok(Err(SaveError::Malformed { .. })) => "DAMAGED".to_string(),
+        Err(_) => "UNREADABLE".to_string()
+    })
+}
+
+impl Scene for SaveLoadScene {
+    fn action(&mut self, action: Action, game: &mut Game) -> GameResult<Transition> {
+        let state = &mut game.state;
+        let items = &game.items;
+        match action {
+            Action::Confirm => match self.mode {
+                SaveLoadMode::Save => {
+                    self.message = Some(match write_save(self.i, state) {
+                        Ok(()) => "GAME SAVED.".to_string(),
+                        Err(_) => "COULD NOT WRITE THE SAVE.".to_string()
+                    });
+                    self.slots[self.i] = describe_slot(self.i, items);
+                },
+                SaveLoadMode::Load => {
+                    if self.slots[self.i].is_none() {
+                        self.message = Some("THIS SLOT IS EMPTY.".to_string());
+                        return Ok(Transition::None);
+                    }
+                    match read_save(self.i, items) {
+                        Ok(Ok(loaded)) => {
+                            // the scenes of the game before are of no use
+                            // after loading
+                            *state = loaded;
+                            return Ok(Transition::Reset(Box::new(ExploreScene::default())));
+                        },
+                        Ok(Err(SaveError::IncompatibleVersion(version))) => {
+                            self.message = Some(format!(
+                                "SAVE VERSION {} IS NOT\nSUPPORTED BY THIS GAME.",
+                                version
+                            ));
+                        },
+                        Ok(Err(SaveError::Malformed { line, .. })) => {
+                            self.message = Some(format!(
+                                "SAVE IS DAMAGED\n(LINE {}).",
+                                line
+                            ));
+                        },
+                        Err(_) => {
+                            self.message = Some("COULD NOT READ THE SAVE.".to_string());
+                        }
+                    }
+                }
+            },
+            Action::Cancel => return Ok(Transition::Pop(SceneResult::None)),
+            Action::Up => {
+                self.i = (self.i + SAVE_SLOTS - 1) % SAVE_SLOTS;
+                self.message = None;
+            },
+            Action::Down => {
+                self.i = (self.i + 1) % SAVE_SLOTS;
+                self.message = None;
+            },
+            _ => {}
+        }
+        Ok(Transition::None)
+    }
+
+    fn draw(
+        &self,
+        ctx: &mut Context,
+        canvas: &mut Canvas,
+        game: &Game,
+        resources: &Resources
+    ) -> GameResult {
+        draw_rect(
+            ctx, canvas,
+            &rect_points(INVENTORY_BORDER_RECT),
+            Color::WHITE,
+            Color::BLACK
+        )?;
+
+        draw_bitmap_text(
+            canvas,
+            match self.mode {
+                SaveLoadMode::Save => "SAVE GAME",
+                SaveLoadMode::Load => "LOAD GAME"
+            },
+            &resources.font_bold,
+            Color::WHITE,
+            24.0, 24.0
+        );
+        // draw list of slots
+        for (slot, label) in self.slots.iter().enumerate() {
+            let y = 56.0 + slot as f32 * 16.0;
+            if self.i == slot {
+                draw_bitmap_text(
+                    canvas,
+                    ">",
+                    &resources.font_bold,
+                    Color::WHITE,
+                    24.0, y
+                );
+            }
+            draw_bitmap_text(
+                canvas,
+                format!("{} {}", slot + 1, label.as_deref().unwrap_or("EMPTY")),
+                &resources.font_regular,
+                match self.mode == SaveLoadMode::Save || label.is_some() {
+                    true => Color::WHITE,
+                    false => GRAY
+                },
+                56.0, y
+            );
+        }
+        // draw result of the last action
+        draw_rect(
+            ctx, canvas,
+            &rect_points(ITEM_DETAILS_BORDER_RECT),
+            Color::WHITE,
+            Color::BLACK
+        )?;
+        if let Some(message) = &self.message {
+            for (i, line) in message.lines().enumerate() {
+                draw_bitmap_text(
+                    canvas,
+                    line,
+                    &resources.font_regular,
+                    Color::WHITE,
+                    24.0, 348.0 + i as f32 * 16.0
+                );
+            }
+        }
+        // draw control panel
+        draw_controls(
+            ctx, canvas,
+            game, resources,
+            &[
+                (Action::Confirm, match self.mode {
+                    SaveLoadMode::Save => "SAVE",
+                    SaveLoadMode::Load => "LOAD"
+                }),
+                (Action::Cancel, "BACK")
+            ]
+        )?;
+
+        Ok(())
+    }
+}
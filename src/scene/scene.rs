@@ -1,15 +1,86 @@
-use crate::scene::*;
-
-#[derive(Clone, Debug)]
-pub enum Scene {
-    Explore(ExploreScene),
-    ViewCharacter(ViewCharacterScene),
-    ViewInventory(ViewInventoryScene),
-    Default // should never be initialized
-}
+use ::ggez::{
+    *,
+    graphics::*
+};
+use ::std::time::Duration;
+use crate::{
+    *,
+    model::*
+};
+
+/// A screen of the game. Only the scene on top of the `SceneStack` receives
+/// input and updates, but overlays let the scenes below them show through.
+pub trait Scene {
+    /// Does `action`, if the scene has any use for it.
+    fn action(&mut self, action: Action, game: &mut Game) -> GameResult<Transition>;
 
-impl Default for Scene {
-    fn default() -> Self {
-        Self::Default
+    /// Whether the scene takes the next key or button pressed as it is,
+    /// through `control`, rather than what it is bound to.
+    fn captures_controls(&self) -> bool {
+        false
     }
+
+    fn control(&mut self, _control: Control, _game: &mut Game) -> GameResult<Transition> {
+        Ok(Transition::None)
+    }
+
+    /// Handles a typed character, which key codes don't tell apart across
+    /// keyboard layouts.
+    fn text_input(&mut self, _character: char, _game: &mut Game) -> GameResult<Transition> {
+        Ok(Transition::None)
+    }
+
+    /// Advances animations and anything else that takes time by `delta`.
+    fn update(&mut self, _delta: Duration, _game: &mut Game) -> GameResult<Transition> {
+        Ok(Transition::None)
+    }
+
+    fn draw(
+        &self,
+        ctx: &mut Context,
+        canvas: &mut Canvas,
+        game: &Game,
+        resources: &Resources
+    ) -> GameResult;
+
+    /// Takes what the scene above handed back when it popped.
+    fn resume(&mut self, _result: SceneResult, _game: &mut Game) -> GameResult<Transition> {
+        Ok(Transition::None)
+    }
+
+    /// Whether the scene only covers part of the screen, drawn on top of the
+    /// scene below it.
+    fn is_overlay(&self) -> bool {
+        false
+    }
+}
+
+/// What a scene asks the `SceneStack` to do after handling something.
+pub enum Transition {
+    None,
+    /// Puts a scene on top, which returns here once it pops.
+    Push(Box<dyn Scene>),
+    /// Removes the scene, handing the result to the one below.
+    Pop(SceneResult),
+    /// Puts a scene in the place of this one.
+    Replace(Box<dyn Scene>),
+    /// Removes every scene, starting over with the given one.
+    Reset(Box<dyn Scene>),
+    Quit
+}
+
+/// What a scene hands back to the scene below it when it pops.
+#[derive(Clone, Debug, Default)]
+pub enum SceneResult {
+    /// The scene was left without choosing anything.
+    #[default]
+    None,
+    /// Item `i` of the inventory was chosen for party member `target`.
+    Item { i: usize, target: usize },
+    /// Option `i` of a question was chosen.
+    Answer(usize),
+    /// A new character was rolled.
+    Character(Character),
+    /// Events ran, and it is up to the scene below to show what they led to.
+    Events(EventOutcome)
 }
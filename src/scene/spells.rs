@@ -0,0 +1,231 @@
+use ::ggez::{
+    *,
+    graphics::*
+};
+use crate::{
+    *,
+    drawing::*,
+    model::*,
+    scene::{
+        *,
+        explore::*,
+        spells::SpellsPhase::*,
+        view_inventory::*
+    }
+};
+
+/// Casting spells outside of battle.
+#[derive(Clone, Debug)]
+pub struct SpellsScene {
+    pub phase: SpellsPhase,
+    pub messages: Vec<String>
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum SpellsPhase {
+    ChooseCaster { selected: usize },
+    ChooseSpell { i: usize, selected: usize },
+    ChooseTarget { i: usize, spell: usize, selected: usize }
+}
+
+impl SpellsPhase {
+    /// The party member whose spells are listed.
+    fn caster(&self) -> usize {
+        match *self {
+            ChooseCaster { selected } => selected,
+            ChooseSpell { i, .. } | ChooseTarget { i, .. } => i
+        }
+    }
+}
+
+fn next_alive_member(party: &[Character], from: usize, step: usize) -> usize {
+    let len = party.len();
+    (1..=len).map(|j| (from + j * step) % len)
+        .find(|&j| party[j].is_alive())
+        .unwrap_or(from)
+}
+
+impl Scene for SpellsScene {
+    fn action(&mut self, action: Action, game: &mut Game) -> GameResult<Transition> {
+        let state = &mut game.state;
+        let len = state.party.len();
+        self.messages.clear();
+        match self.phase {
+            ChooseCaster { selected } => match action {
+                Action::Confirm => {
+                    let character = &state.party[selected];
+                    if character.spells().is_empty() {
+                        self.messages = vec![format!("{} KNOWS NO SPELLS.", character.name)];
+                    } else {
+                        self.phase = ChooseSpell { i: selected, selected: 0 };
+                    }
+                },
+                Action::Cancel => return Ok(Transition::Pop(SceneResult::None)),
+                Action::Up
+                    => self.phase = ChooseCaster { selected: (selected + len - 1) % len },
+                Action::Down
+                    => self.phase = ChooseCaster { selected: (selected + 1) % len },
+                _ => {}
+            },
+            ChooseSpell { i, selected } => {
+                let spells = state.party[i].spells();
+                match action {
+                    Action::Confirm => {
+                        let spell = spells[selected];
+                        if !spell.in_explore() {
+                            self.messages = vec!["ONLY IN BATTLE.".to_string()];
+                        } else if !state.party[i].can_act() {
+                            self.messages = vec![
+                                format!("{} CANNOT CAST.", state.party[i].name)
+                            ];
+                        } else if !state.party[i].can_cast(spell) {
+                            self.messages = vec!["NOT ENOUGH MP.".to_string()];
+                        } else if spell.target() == SpellTarget::Ally {
+                            self.phase = ChooseTarget {
+                                i,
+                                spell: selected,
+                                selected: next_alive_member(&state.party, len - 1, 1)
+                            };
+                        } else {
+                            self.messages = state.cast_spell(i, spell, 0, &mut []);
+                        }
+                    },
+                    Action::Cancel => self.phase = ChooseCaster { selected: i },
+                    Action::Up => self.phase = ChooseSpell {
+                        i,
+                        selected: (selected + spells.len() - 1) % spells.len()
+                    },
+                    Action::Down => self.phase = ChooseSpell {
+                        i,
+                        selected: (selected + 1) % spells.len()
+                    },
+                    _ => {}
+                }
+            },
+            ChooseTarget { i, spell, selected } => match action {
+                Action::Confirm => {
+                    let spell_i = spell;
+                    let spell = state.party[i].spells()[spell_i];
+                    self.messages = state.cast_spell(i, spell, selected, &mut []);
+                    self.phase = ChooseSpell { i, selected: spell_i };
+                },
+                Action::Cancel => self.phase = ChooseSpell { i, selected: spell },
+                Action::Up => self.phase = ChooseTarget {
+                    i,
+                    spell,
+                    selected: next_alive_member(&state.party, selected, len - 1)
+                },
+                Action::Down => self.phase = ChooseTarget {
+                    i,
+                    spell,
+                    selected: next_alive_member(&state.party, selected, 1)
+                },
+                _ => {}
+            }
+        }
+        Ok(Transition::None)
+    }
+
+    fn draw(
+        &self,
+        ctx: &mut Context,
+        canvas: &mut Canvas,
+        game: &Game,
+        resources: &Resources
+    ) -> GameResult {
+        let state = &game.state;
+        draw_rect(
+            ctx, canvas,
+            &rect_points(INVENTORY_BORDER_RECT),
+            Color::WHITE,
+            Color::BLACK
+        )?;
+
+        // draw spells of the chosen character
+        let character = &state.party[self.phase.caster()];
+        draw_bitmap_text(
+            canvas,
+            format!("SPELLS OF {}", character.name),
+            &resources.font_bold,
+            Color::WHITE,
+            24.0, 24.0
+        );
+        let spells = character.spells();
+        let selected_spell = match self.phase {
+            ChooseSpell { selected, .. } | ChooseTarget { spell: selected, .. }
+                => spells.get(selected).copied(),
+            ChooseCaster { .. } => None
+        };
+        for (j, spell) in spells.iter().enumerate() {
+            let y = 56.0 + j as f32 * 16.0;
+            if selected_spell == Some(*spell) {
+                draw_bitmap_text(
+                    canvas,
+                    ">",
+                    &resources.font_bold,
+                    Color::WHITE,
+                    24.0, y
+                );
+            }
+            draw_bitmap_text(
+                canvas,
+                format!("{:8} {} MP", spell.name(), spell.mp_cost()),
+                &resources.font_regular,
+                match spell.in_explore() && character.can_cast(*spell) {
+                    true => Color::WHITE,
+                    false => GRAY
+                },
+                56.0, y
+            );
+        }
+
+        // draw the result of the last spell, or what the chosen one does
+        let lines = match (&self.messages[..], selected_spell) {
+            ([], Some(spell)) => spell.desc().iter().map(|line| line.to_string()).collect(),
+            (messages, _) => messages.to_vec()
+        };
+        for (j, line) in lines.iter().enumerate() {
+            let y = VIEWPORT_TOP + VIEWPORT_HEIGHT - 8.0
+                - 16.0 * (lines.len() - j) as f32;
+            draw_bitmap_text(
+                canvas,
+                line,
+                &resources.font_regular,
+                Color::WHITE,
+                24.0, y
+            );
+        }
+
+        draw_location(
+            ctx, canvas,
+            resources,
+            state.floor,
+            state.level()
+        )?;
+        draw_partylist(
+            ctx, canvas,
+            resources,
+            &state.party,
+            &None,
+            Some(match self.phase {
+                ChooseTarget { selected, .. } => selected,
+                phase => phase.caster()
+            })
+        )?;
+        // draw control panel
+        draw_controls(
+            ctx, canvas,
+            game, resources,
+            &[
+                (Action::Confirm, match self.phase {
+                    ChooseCaster { .. } => "CASTER",
+                    ChooseSpell { .. } => "CAST",
+                    ChooseTarget { .. } => "TARGET"
+                }),
+                (Action::Cancel, "BACK")
+            ]
+        )?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,54 @@
+use crate::scene::*;
+
+/// The scenes in play, from the one the game started with to the one on
+/// top, which is the one receiving input.
+pub struct SceneStack {
+    scenes: Vec<Box<dyn Scene>>
+}
+
+impl SceneStack {
+    pub fn new(scene: Box<dyn Scene>) -> Self {
+        SceneStack {
+            scenes: vec![scene]
+        }
+    }
+
+    pub fn push(&mut self, scene: Box<dyn Scene>) {
+        self.scenes.push(scene);
+    }
+
+    pub fn pop(&mut self) -> Option<Box<dyn Scene>> {
+        self.scenes.pop()
+    }
+
+    pub fn replace(&mut self, scene: Box<dyn Scene>) {
+        self.scenes.pop();
+        self.scenes.push(scene);
+    }
+
+    pub fn reset(&mut self, scene: Box<dyn Scene>) {
+        self.scenes.clear();
+        self.scenes.push(scene);
+    }
+
+    pub fn top(&self) -> Option<&dyn Scene> {
+        self.scenes.last().map(Box::as_ref)
+    }
+
+    pub fn len(&self) -> usize {
+        self.scenes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scenes.is_empty()
+    }
+
+    /// The scenes that show on screen, bottom first: the top one and those
+    /// below it down to the first one that is not an overlay.
+    pub fn visible(&self) -> &[Box<dyn Scene>] {
+        let start = self.scenes.iter()
+            .rposition(|scene| !scene.is_overlay())
+            .unwrap_or(0);
+        &self.scenes[start..]
+    }
+}
@@ -0,0 +1,157 @@
+use ::ggez::{
+    *,
+    graphics::*
+};
+use crate::{
+    *,
+    drawing::*,
+    model::*,
+    scene::{
+        *,
+        explore::*,
+        view_inventory::*
+    }
+};
+
+/// The temple next to the guild, where the priests cure party members of
+/// every status, even death.
+#[derive(Clone, Debug)]
+pub struct TempleScene {
+    pub i: usize,
+    pub message: Option<String>
+}
+
+impl Scene for TempleScene {
+    fn action(&mut self, action: Action, game: &mut Game) -> GameResult<Transition> {
+        let state = &mut game.state;
+        let len = state.party.len();
+        match action {
+            Action::Confirm => {
+                let Some(character) = state.party.get_mut(self.i) else {
+                    return Ok(Transition::None);
+                };
+                self.message = Some(match character.worst_status() {
+                    Some(worst) => {
+                        for &status in Status::ALL {
+                            character.cure(status);
+                        }
+                        format!("{} {}", character.name, worst.cured_text())
+                    },
+                    None => format!("{} IS WELL.", character.name)
+                });
+            },
+            Action::Cancel => return Ok(Transition::Pop(SceneResult::None)),
+            Action::Up if len > 0 => {
+                self.i = (self.i + len - 1) % len;
+                self.message = None;
+            },
+            Action::Down if len > 0 => {
+                self.i = (self.i + 1) % len;
+                self.message = None;
+            },
+            _ => {}
+        }
+        Ok(Transition::None)
+    }
+
+    fn draw(
+        &self,
+        ctx: &mut Context,
+        canvas: &mut Canvas,
+        game: &Game,
+        resources: &Resources
+    ) -> GameResult {
+        let state = &game.state;
+        draw_rect(
+            ctx, canvas,
+            &rect_points(INVENTORY_BORDER_RECT),
+            Color::WHITE,
+            Color::BLACK
+        )?;
+
+        // draw the party with the worst status of each member
+        draw_bitmap_text(
+            canvas,
+            "TEMPLE",
+            &resources.font_bold,
+            Color::WHITE,
+            24.0, 24.0
+        );
+        for (i, character) in state.party.iter().enumerate() {
+            let y = 56.0 + i as f32 * 16.0;
+            if i == self.i {
+                draw_bitmap_text(
+                    canvas,
+                    ">",
+                    &resources.font_bold,
+                    Color::WHITE,
+                    24.0, y
+                );
+            }
+            draw_bitmap_text(
+                canvas,
+                &character.name,
+                &resources.font_regular,
+                Color::WHITE,
+                56.0, y
+            );
+            draw_bitmap_text(
+                canvas,
+                character.worst_status().map_or("OK", |status| status.name()),
+                &resources.font_regular,
+                Color::WHITE,
+                232.0, y
+            );
+        }
+        // draw every status of the selected member, or the result of the cure
+        draw_rect(
+            ctx, canvas,
+            &rect_points(ITEM_DETAILS_BORDER_RECT),
+            Color::WHITE,
+            Color::BLACK
+        )?;
+        if let Some(message) = &self.message {
+            draw_bitmap_text(
+                canvas,
+                message,
+                &resources.font_regular,
+                Color::WHITE,
+                24.0, 348.0
+            );
+        } else if let Some(character) = state.party.get(self.i) {
+            draw_bitmap_text(
+                canvas,
+                &character.name,
+                &resources.font_bold,
+                Color::WHITE,
+                24.0, 348.0
+            );
+            for (i, effect) in character.statuses.iter().enumerate() {
+                let line = match effect.left {
+                    Some(left) => format!(
+                        "{} {} {}",
+                        effect.status.name(),
+                        left,
+                        if effect.status.counts_turns() { "TURNS" } else { "STEPS" }
+                    ),
+                    None => effect.status.name().to_string()
+                };
+                draw_bitmap_text(
+                    canvas,
+                    line,
+                    &resources.font_regular,
+                    Color::WHITE,
+                    24.0, 364.0 + i as f32 * 16.0
+                );
+            }
+        }
+        // draw control panel
+        draw_controls(
+            ctx, canvas,
+            game, resources,
+            &[(Action::Confirm, "CURE"), (Action::Cancel, "LEAVE")]
+        )?;
+
+        Ok(())
+    }
+}
@@ -1,9 +1,7 @@
 use ::ggez::{
     *,
-    graphics::*,
-    input::keyboard::*
+    graphics::*
 };
-use ::std::mem::take;
 use crate::{
     *,
     drawing::*,
@@ -20,7 +18,9 @@ use crate::{
 #[derive(Clone, Debug)]
 pub struct ViewCharacterScene {
     pub i: usize,
-    pub selected: ItemSlot
+    pub selected: ItemSlot,
+    /// The stat whose modifiers are listed in place of the equipment.
+    pub stat: Option<Stat>
 }
 
 pub const CHAR_DETAILS_BORDER_RECT: Rect = Rect {
@@ -30,189 +30,219 @@ pub const CHAR_DETAILS_BORDER_RECT: Rect = Rect {
     h: (PARTYLIST_BOTTOM - VIEWPORT_TOP) + 2.0
 };
 
-pub const SLOTS: &[ItemSlot] = &[
-    Weapon,
-    Shield,
-    Armor
-];
-pub const SLOT_LABELS: &[&'static str] = &[
-    "WEAPON",
-    "SHIELD",
-    "ARMOR"
-];
-
-fn expect_view_character(scene: &Scene) -> &ViewCharacterScene {
-    match scene {
-        Scene::ViewCharacter(view_char_scene) => view_char_scene,
-        _ => unimplemented!()
-    }
-}
-fn expect_view_character_mut(scene: &mut Scene) -> &mut ViewCharacterScene {
-    match scene {
-        Scene::ViewCharacter(view_char_scene) => view_char_scene,
-        _ => unimplemented!()
+impl Scene for ViewCharacterScene {
+    fn action(&mut self, action: Action, game: &mut Game) -> GameResult<Transition> {
+        let state = &mut game.state;
+        if let Some(stat) = self.stat.as_mut() {
+            match action {
+                Action::Cancel => self.stat = None,
+                Action::Stats => {
+                    let i = Stat::ALL.iter().position(|s| s == stat).unwrap_or(0);
+                    *stat = Stat::ALL[(i + 1) % Stat::ALL.len()];
+                },
+                _ => {}
+            }
+            return Ok(Transition::None);
+        }
+        let slot_i = ItemSlot::ALL.iter().position(|slot| *slot == self.selected).unwrap_or(0);
+        let len = ItemSlot::ALL.len();
+        match action {
+            Action::Cancel => return Ok(Transition::Pop(SceneResult::None)),
+            Action::Confirm => {
+                let pred = ItemPredicate::Equippable(self.i, self.selected);
+                return Ok(Transition::Push(Box::new(ViewInventoryScene::new(pred))));
+            }
+            Action::Unequip => state.unequip(self.i, self.selected),
+            Action::Up => self.selected = ItemSlot::ALL[(slot_i + len - 1) % len],
+            Action::Down => self.selected = ItemSlot::ALL[(slot_i + 1) % len],
+            Action::Stats => self.stat = Some(Stat::Atk),
+            _   => {}
+        }
+        Ok(Transition::None)
     }
-}
 
-pub fn key_down_event(
-    _ctx: &mut Context,
-    input: KeyInput,
-    _repeated: bool,
-    game: &mut Game
-) -> GameResult {
-    let scene = expect_view_character_mut(&mut game.scene);
-    match input.keycode {
-        Some(KeyCode::Escape) => {
-            game.scene = Scene::Explore(ExploreScene {
-                anim: None,
-                selected: Some(scene.i)
-            });
-        },
-        Some(KeyCode::Return) => {
-            game.scene = Scene::ViewInventory(ViewInventoryScene {
-                i: 0,
-                pred: ItemPredicate::Equippable(scene.i, scene.selected),
-                parent: Box::new(take(&mut game.scene))
-            });
+    /// Equips the item chosen in the inventory.
+    fn resume(&mut self, result: SceneResult, game: &mut Game) -> GameResult<Transition> {
+        if let SceneResult::Item { i, .. } = result {
+            game.state.equip(self.i, self.selected, i);
         }
-        Some(KeyCode::Up) => scene.selected = match scene.selected {
-            Weapon => Armor,
-            Shield => Weapon,
-            Armor => Shield
-        },
-        Some(KeyCode::Down) => scene.selected = match scene.selected {
-            Weapon => Shield,
-            Shield => Armor,
-            Armor => Weapon
-        },
-        _   => {}
+        Ok(Transition::None)
     }
-    Ok(())
-}
-
-pub fn update(_ctx: &mut Context, _game: &mut Game) -> GameResult {
-    Ok(())
-}
-
 
-pub fn draw(ctx: &mut Context, game: &Game) -> GameResult {
-    let (state, scene) = (&game.state, expect_view_character(&game.scene));
-    // set up canvas
-    let mut canvas = Canvas::from_frame(ctx, Color::BLACK);
-    canvas.set_sampler(Sampler::nearest_clamp());
-
-    draw_rect(
-        ctx, &mut canvas,
-        &rect_points(CHAR_DETAILS_BORDER_RECT),
-        Color::WHITE,
-        Color::BLACK
-    )?;
-
-    // draw character stats
-    if let Some(character) = state.party.get(scene.i) {
-        draw_bitmap_text(
-            &mut canvas,
-            &character.name,
-            &game.resources.font_bold,
+    fn draw(
+        &self,
+        ctx: &mut Context,
+        canvas: &mut Canvas,
+        game: &Game,
+        resources: &Resources
+    ) -> GameResult {
+        let state = &game.state;
+        draw_rect(
+            ctx, canvas,
+            &rect_points(CHAR_DETAILS_BORDER_RECT),
             Color::WHITE,
-            24.0, 24.0
-        );
-        draw_bitmap_text(
-            &mut canvas,
-            format!("{} LV.{}", character.class.name(), character.lvl),
-            &game.resources.font_regular,
-            Color::WHITE,
-            24.0, 40.0
-        );
-        for (i, (stat, val, max)) in [
-            ("HP", character.hp, character.max_hp),
-            ("MP", character.mp, character.max_mp),
-        ].iter().enumerate() {
-            let y = 72.0 + i as f32 * 16.0;
-            draw_bitmap_text(
-                &mut canvas,
-                stat,
-                &game.resources.font_bold,
-                Color::WHITE,
-                24.0, y
-            );
-            draw_bitmap_text(
-                &mut canvas,
-                format!("{}/{}", val, max),
-                &game.resources.font_regular,
-                Color::WHITE,
-                72.0, y
-            );
-        }
-        for (i, (stat, val, base)) in [
-            ("ATK", character.atk(), character.base_atk),
-            ("DEF", character.def(), character.base_def),
-            ("MAG", character.matk(), character.base_matk),
-            ("RES", character.mdef(), character.base_mdef),
-            ("AGI", character.agi(), character.base_agi),
-            ("LUC", character.luck(), character.base_luck),
-        ].iter().enumerate() {
-            let y = 104.0 + i as f32 * 16.0;
+            Color::BLACK
+        )?;
+
+        // draw character stats
+        if let Some(character) = state.party.get(self.i) {
             draw_bitmap_text(
-                &mut canvas,
-                stat,
-                &game.resources.font_bold,
+                canvas,
+                &character.name,
+                &resources.font_bold,
                 Color::WHITE,
-                24.0, y
+                24.0, 24.0
             );
             draw_bitmap_text(
-                &mut canvas,
-                val.to_string(),
-                &game.resources.font_regular,
+                canvas,
+                format!("{} LV.{}", character.class.name(), character.lvl),
+                &resources.font_regular,
                 Color::WHITE,
-                88.0, y
+                24.0, 40.0
             );
-            if *val != *base {
+            for (i, (stat, val, max)) in [
+                ("HP", character.hp, character.max_hp),
+                ("MP", character.mp, character.max_mp),
+                ("XP", character.xp, character.xp_for_next_lvl().unwrap_or(character.xp)),
+            ].iter().enumerate() {
+                let y = 72.0 + i as f32 * 16.0;
+                draw_bitmap_text(
+                    canvas,
+                    stat,
+                    &resources.font_bold,
+                    Color::WHITE,
+                    24.0, y
+                );
                 draw_bitmap_text(
-                    &mut canvas,
-                    format!("(BASE {})", base),
-                    &game.resources.font_regular,
+                    canvas,
+                    format!("{}/{}", val, max),
+                    &resources.font_regular,
                     Color::WHITE,
-                    152.0, y
+                    72.0, y
                 );
             }
-        }
-        for (i, (slot, label)) in SLOTS.iter().zip(SLOT_LABELS).enumerate() {
-            let y = 216.0 + i as f32 * 16.0;
-            draw_bitmap_text(
-                &mut canvas,
-                label,
-                &game.resources.font_bold,
-                Color::WHITE,
-                24.0, y
-            );
-            draw_bitmap_text(
-                &mut canvas,
-                match character.item(*slot) {
-                    Some(item) => item.name(),
-                    None => "````"
-                },
-                &game.resources.font_regular,
-                Color::WHITE,
-                136.0, y
-            );
-            if *slot == scene.selected {
+            for (i, stat) in Stat::ALL.iter().enumerate() {
+                let y = 120.0 + i as f32 * 16.0;
+                draw_bitmap_text(
+                    canvas,
+                    stat.name(),
+                    &resources.font_bold,
+                    Color::WHITE,
+                    24.0, y
+                );
                 draw_bitmap_text(
-                    &mut canvas,
-                    "<",
-                    &game.resources.font_bold,
+                    canvas,
+                    character.stat(*stat).to_string(),
+                    &resources.font_regular,
                     Color::WHITE,
-                    392.0, y
+                    88.0, y
                 );
+                if Some(*stat) == self.stat {
+                    draw_bitmap_text(
+                        canvas,
+                        "<",
+                        &resources.font_bold,
+                        Color::WHITE,
+                        152.0, y
+                    );
+                }
+            }
+            match self.stat {
+                None => draw_equipment(canvas, resources, character, self.selected),
+                Some(stat) => draw_breakdown(canvas, resources, character, stat)
             }
         }
+        // draw control panel
+        draw_controls(
+            ctx, canvas,
+            game, resources,
+            match self.stat {
+                None => &[
+                    (Action::Confirm, "CHOOSE"),
+                    (Action::Unequip, "REMOVE"),
+                    (Action::Stats, "STATS"),
+                    (Action::Cancel, "BACK")
+                ],
+                Some(_) => &[(Action::Stats, "NEXT"), (Action::Cancel, "BACK")]
+            }
+        )?;
+
+        Ok(())
     }
-    // draw control panel
-    draw_controls(
-        ctx, &mut canvas,
-        &game.resources,
-        &[("[\n]", "CHOOSE"), ("[\x1b]", "BACK")]
-    )?;
+}
 
-    canvas.finish(ctx)
+fn draw_equipment(
+    canvas: &mut Canvas,
+    resources: &Resources,
+    character: &Character,
+    selected: ItemSlot
+) {
+    for (i, slot) in ItemSlot::ALL.iter().enumerate() {
+        let y = 232.0 + i as f32 * 16.0;
+        draw_bitmap_text(
+            canvas,
+            slot.name(),
+            &resources.font_bold,
+            Color::WHITE,
+            24.0, y
+        );
+        let (text, color) = match character.item(*slot) {
+            Some(item) => (item.name(), Color::WHITE),
+            None if *slot == Shield && character.shield_blocked()
+                => ("(BOTH HANDS)", GRAY),
+            None => ("````", Color::WHITE)
+        };
+        draw_bitmap_text(
+            canvas,
+            text,
+            &resources.font_regular,
+            color,
+            184.0, y
+        );
+        if *slot == selected {
+            draw_bitmap_text(
+                canvas,
+                "<",
+                &resources.font_bold,
+                Color::WHITE,
+                392.0, y
+            );
+        }
+    }
+}
+
+/// Lists where the points in `stat` come from.
+fn draw_breakdown(
+    canvas: &mut Canvas,
+    resources: &Resources,
+    character: &Character,
+    stat: Stat
+) {
+    draw_bitmap_text(
+        canvas,
+        format!("{} {}", stat.name(), character.stat(stat)),
+        &resources.font_bold,
+        Color::WHITE,
+        24.0, 232.0
+    );
+    let base = ("BASE".to_string(), character.base_stat(stat).to_string());
+    let modifiers = character.modifiers(stat).into_iter()
+        .map(|(source, modifier)| (source.name().to_string(), modifier.text()));
+    for (i, (source, value)) in [base].into_iter().chain(modifiers).enumerate() {
+        let y = 248.0 + i as f32 * 16.0;
+        draw_bitmap_text(
+            canvas,
+            source,
+            &resources.font_regular,
+            Color::WHITE,
+            24.0, y
+        );
+        draw_bitmap_text(
+            canvas,
+            value,
+            &resources.font_regular,
+            Color::WHITE,
+            264.0, y
+        );
+    }
 }
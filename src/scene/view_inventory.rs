@@ -1,7 +1,6 @@
 use ::ggez::{
     *,
-    graphics::*,
-    input::keyboard::*
+    graphics::*
 };
 use ::std::mem::take;
 use crate::{
@@ -17,8 +16,43 @@ use crate::{
 #[derive(Clone, Debug)]
 pub struct ViewInventoryScene {
     pub i: usize,
-    pub parent: Box<Scene>,
-    pub pred: ItemPredicate
+    pub pred: ItemPredicate,
+    /// The party member chosen to receive the selected item, while choosing.
+    pub target: Option<usize>,
+    /// What happened when the last item was used.
+    pub messages: Vec<String>
+}
+
+impl ViewInventoryScene {
+    pub fn new(pred: ItemPredicate) -> Self {
+        ViewInventoryScene {
+            i: 0,
+            pred,
+            target: None,
+            messages: Vec::new()
+        }
+    }
+
+    /// Hands item `i` for party member `target` back to the scene below.
+    /// While exploring, items are used right away instead and the inventory
+    /// stays open to show what happened, unless an event answers the item,
+    /// which is left for the scene below to show.
+    fn choose(&mut self, game: &mut Game, i: usize, target: usize) -> Transition {
+        if self.pred != ItemPredicate::Usable {
+            return Transition::Pop(SceneResult::Item { i, target });
+        }
+        let state = &mut game.state;
+        let trigger = Trigger::Item(state.inventory[i].item.name().to_string());
+        self.messages = state.use_item(i, target);
+        self.i = self.i.min(state.inventory.len().saturating_sub(1));
+        let Some(mut outcome) = state.run_events(&trigger, &game.items) else {
+            return Transition::None;
+        };
+        let mut messages = take(&mut self.messages);
+        messages.append(&mut outcome.messages);
+        outcome.messages = messages;
+        Transition::Pop(SceneResult::Events(outcome))
+    }
 }
 
 pub const INVENTORY_BORDER_RECT: Rect = Rect {
@@ -35,154 +69,181 @@ pub const ITEM_DETAILS_BORDER_RECT: Rect = Rect {
 };
 pub const ITEMS_PER_SCREEN: usize = 17;
 
-fn expect_view_inventory(scene: &Scene) -> &ViewInventoryScene {
-    match scene {
-        Scene::ViewInventory(view_inv_scene) => view_inv_scene,
-        _ => unimplemented!()
-    }
-}
-fn expect_view_inventory_mut(scene: &mut Scene) -> &mut ViewInventoryScene {
-    match scene {
-        Scene::ViewInventory(view_inv_scene) => view_inv_scene,
-        _ => unimplemented!()
-    }
-}
-
-fn answer_request(
-    game: &mut Game,
-    item_i: Option<usize>
-) {
-    let (state, scene) = (&mut game.state, expect_view_inventory_mut(&mut game.scene));
-    if let Some(item_i) = item_i {
-        match scene.parent.as_mut() {
-            Scene::ViewCharacter(ViewCharacterScene { i, selected }) => {
-                if let Some(character) = state.party.get_mut(*i) {
-                    let inv_item = state.inventory.get_mut(item_i).take().copied();
-                    let equipped_item = character.item_mut(*selected).take();
-                    *character.item_mut(*selected) = inv_item;
-                    if let Some(item) = equipped_item {
-                        state.inventory.push(item);
-                    }
-                }
-            },
-            _ => {}
+impl Scene for ViewInventoryScene {
+    fn action(&mut self, action: Action, game: &mut Game) -> GameResult<Transition> {
+        let state = &game.state;
+        let len = state.inventory.len();
+        if let Some(target) = self.target {
+            let party_len = state.party.len();
+            match action {
+                Action::Confirm => {
+                    self.target = None;
+                    return Ok(self.choose(game, self.i, target));
+                },
+                Action::Cancel => self.target = None,
+                Action::Up => self.target = Some((target + party_len - 1) % party_len),
+                Action::Down => self.target = Some((target + 1) % party_len),
+                _ => {}
+            }
+            return Ok(Transition::None);
         }
-    }
-    game.scene = take(&mut scene.parent);
-}
-
-pub fn key_down_event(
-    _ctx: &mut Context,
-    input: KeyInput,
-    _repeated: bool,
-    game: &mut Game
-) -> GameResult {
-    if matches!(
-        input.keycode,
-        Some(KeyCode::Escape | KeyCode::Return)
-    ) {
-        let (state, scene) = (&game.state, expect_view_inventory(&game.scene));
-        match input.keycode {
-            Some(KeyCode::Escape) => answer_request(game, None),
-            Some(KeyCode::Return) => {
-                if let Some(item) = state.inventory.get(scene.i) {
-                    if scene.pred.matches(*item, &game) {
-                        answer_request(game, Some(scene.i));
-                    }
+        match action {
+            Action::Cancel => return Ok(Transition::Pop(SceneResult::None)),
+            Action::Confirm => {
+                let Some(stack) = state.inventory.get(self.i) else {
+                    return Ok(Transition::None);
+                };
+                if !self.pred.matches(&stack.item, game) {
+                    return Ok(Transition::None);
+                }
+                match stack.item.effect {
+                    Some(effect) if effect.targets_member() => {
+                        self.messages.clear();
+                        self.target = Some(0);
+                    },
+                    _ => return Ok(self.choose(game, self.i, 0))
                 }
             },
-            _ => unimplemented!()
-        }
-    } else {
-        let (state, scene) = (&game.state, expect_view_inventory_mut(&mut game.scene));
-        let i = &mut scene.i;
-        match input.keycode {
-            Some(KeyCode::Up)
-                => *i = (*i + state.inventory.len() - 1) % state.inventory.len(),
-            Some(KeyCode::Down)
-                => *i = (*i + 1) % state.inventory.len(),
-            _   => {}
+            Action::Up if len > 0 => {
+                self.i = (self.i + len - 1) % len;
+                self.messages.clear();
+            },
+            Action::Down if len > 0 => {
+                self.i = (self.i + 1) % len;
+                self.messages.clear();
+            },
+            _ => {}
         }
+        Ok(Transition::None)
     }
-    Ok(())
-}
-
-pub fn update(_ctx: &mut Context, _game: &mut Game) -> GameResult {
-    Ok(())
-}
 
-pub fn draw(ctx: &mut Context, game: &Game) -> GameResult {
-    let (state, scene) = (&game.state, expect_view_inventory(&game.scene));
-    // set up canvas
-    let mut canvas = Canvas::from_frame(ctx, Color::BLACK);
-    canvas.set_sampler(Sampler::nearest_clamp());
-
-    draw_rect(
-        ctx, &mut canvas,
-        &rect_points(INVENTORY_BORDER_RECT),
-        Color::WHITE,
-        Color::BLACK
-    )?;
+    fn draw(
+        &self,
+        ctx: &mut Context,
+        canvas: &mut Canvas,
+        game: &Game,
+        resources: &Resources
+    ) -> GameResult {
+        let state = &game.state;
+        draw_rect(
+            ctx, canvas,
+            &rect_points(INVENTORY_BORDER_RECT),
+            Color::WHITE,
+            Color::BLACK
+        )?;
 
-    // draw list of items
-    let len = state.inventory.len();
-    let skip_len = scene.i.saturating_sub(ITEMS_PER_SCREEN / 2)
-        .min(len.saturating_sub(ITEMS_PER_SCREEN));
-    for (j, item) in state.inventory.iter().skip(skip_len).take(ITEMS_PER_SCREEN)
-        .enumerate()
-    {
-        let y = 24.0 + j as f32 * 16.0;
-        if scene.i == j + skip_len {
+        // draw list of items
+        let len = state.inventory.len();
+        let skip_len = self.i.saturating_sub(ITEMS_PER_SCREEN / 2)
+            .min(len.saturating_sub(ITEMS_PER_SCREEN));
+        for (j, stack) in state.inventory.iter().skip(skip_len).take(ITEMS_PER_SCREEN)
+            .enumerate()
+        {
+            let y = 24.0 + j as f32 * 16.0;
+            if self.i == j + skip_len {
+                draw_bitmap_text(
+                    canvas,
+                    ">",
+                    &resources.font_bold,
+                    Color::WHITE,
+                    24.0, y
+                );
+            }
+            let color = match self.pred.matches(&stack.item, game) {
+                true => Color::WHITE,
+                false => GRAY
+            };
             draw_bitmap_text(
-                &mut canvas,
-                ">",
-                &game.resources.font_bold,
-                Color::WHITE,
-                24.0, y
+                canvas,
+                stack.item.name(),
+                &resources.font_regular,
+                color,
+                56.0, y
             );
+            if stack.count > 1 {
+                let count = stack.count.to_string();
+                draw_bitmap_text(
+                    canvas,
+                    &count,
+                    &resources.font_regular,
+                    color,
+                    PARTYLIST_RIGHT - 8.0 - 16.0 * count.len() as f32, y
+                );
+            }
         }
-        draw_bitmap_text(
-            &mut canvas,
-            item.name(),
-            &game.resources.font_regular,
-            match scene.pred.matches(*item, game) {
-                true => Color::WHITE,
-                false => GRAY
-            },
-            56.0, y
-        );
-    }
-    // draw item details
-    draw_rect(
-        ctx, &mut canvas,
-        &rect_points(ITEM_DETAILS_BORDER_RECT),
-        Color::WHITE,
-        Color::BLACK
-    )?;
-    if let Some(item) = state.inventory.get(scene.i) {
-        draw_bitmap_text(
-            &mut canvas,
-            item.name(),
-            &game.resources.font_bold,
+        // while choosing who receives the item, the party takes the place of
+        // the item details
+        if self.target.is_some() {
+            draw_partylist(
+                ctx, canvas,
+                resources,
+                &state.party,
+                &None,
+                self.target
+            )?;
+            draw_controls(
+                ctx, canvas,
+                game, resources,
+                &[(Action::Confirm, "GIVE"), (Action::Cancel, "BACK")]
+            )?;
+            return Ok(());
+        }
+        // draw item details
+        draw_rect(
+            ctx, canvas,
+            &rect_points(ITEM_DETAILS_BORDER_RECT),
             Color::WHITE,
-            24.0, 348.0
-        );
-        for (i, line) in item.desc().iter().enumerate() {
+            Color::BLACK
+        )?;
+        if !self.messages.is_empty() {
+            for (i, line) in self.messages.iter().enumerate() {
+                draw_bitmap_text(
+                    canvas,
+                    line,
+                    &resources.font_regular,
+                    Color::WHITE,
+                    24.0, 348.0 + i as f32 * 16.0
+                );
+            }
+        } else if let Some(ItemStack { item, .. }) = state.inventory.get(self.i) {
             draw_bitmap_text(
-                &mut canvas,
-                line,
-                &game.resources.font_regular,
+                canvas,
+                item.name(),
+                &resources.font_bold,
                 Color::WHITE,
-                24.0, 364.0 + i as f32 * 16.0
+                24.0, 348.0
             );
+            let price = format!("{} G", item.price);
+            draw_bitmap_text(
+                canvas,
+                &price,
+                &resources.font_regular,
+                Color::WHITE,
+                PARTYLIST_RIGHT - 8.0 - 16.0 * price.len() as f32, 348.0
+            );
+            for (i, line) in item.desc().iter().enumerate() {
+                draw_bitmap_text(
+                    canvas,
+                    line,
+                    &resources.font_regular,
+                    Color::WHITE,
+                    24.0, 364.0 + i as f32 * 16.0
+                );
+            }
         }
-    }
-    // draw control panel
-    draw_controls(
-        ctx, &mut canvas,
-        &game.resources,
-        &[("[\n]", "CHOOSE"), ("[\x1b]", "BACK")]
-    )?;
+        // draw control panel
+        draw_controls(
+            ctx, canvas,
+            game, resources,
+            &[
+                (Action::Confirm, match self.pred {
+                    ItemPredicate::Equippable(..) => "EQUIP",
+                    ItemPredicate::Usable | ItemPredicate::UsableInBattle => "USE"
+                }),
+                (Action::Cancel, "BACK")
+            ]
+        )?;
 
-    canvas.finish(ctx)
+        Ok(())
+    }
 }
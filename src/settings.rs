@@ -0,0 +1,25 @@
+use ::std::path::PathBuf;
+use crate::*;
+
+/// Player preferences that are not part of the saved game.
+#[derive(Clone, Debug)]
+pub struct Settings {
+    /// Whether the explore scene shows the mini-map next to the viewport.
+    pub minimap: bool,
+    /// Whether the story variables are shown on top of every scene.
+    pub debug_overlay: bool,
+    pub bindings: Bindings,
+    /// Where changes to the bindings are written, if anywhere.
+    pub bindings_path: Option<PathBuf>
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            minimap: true,
+            debug_overlay: false,
+            bindings: Bindings::default(),
+            bindings_path: None
+        }
+    }
+}
@@ -0,0 +1,73 @@
+//! Checks that keys and buttons do whatever they are bound to.
+
+use ::ggez::{
+    event::Button,
+    input::keyboard::KeyCode
+};
+use ::std::time::Duration;
+use ::blackbox::*;
+
+const SETTLE: Input = Input::Wait(Duration::from_secs(1));
+
+#[test]
+fn reads_bindings() {
+    let bindings = Bindings::parse("# mine\nforward key K\n\nforward button R1\nminimap none\n")
+        .unwrap();
+    assert_eq!(bindings.controls(Action::MoveForward), [
+        Control::Key(KeyCode::K),
+        Control::Button(Button::RightTrigger)
+    ]);
+    assert_eq!(bindings.controls(Action::ToggleMinimap), []);
+    assert_eq!(bindings.controls(Action::Confirm), Bindings::default().controls(Action::Confirm));
+    assert_eq!(bindings.actions(Control::Key(KeyCode::Up)), [Action::Up]);
+    assert_eq!(bindings.label(Action::MoveForward, true), Some("R1".to_string()));
+    assert_eq!(Bindings::parse(&bindings.to_string()), Ok(bindings));
+    assert_eq!(
+        Bindings::parse("up key UP\njump key J"),
+        Err("line 2: unknown action `jump`".to_string())
+    );
+    assert_eq!(
+        Bindings::parse("up button NOPE"),
+        Err("line 1: unknown button `NOPE`".to_string())
+    );
+    assert_eq!(
+        Bindings::parse("up key UP\n\ncancel none"),
+        Err("line 3: `cancel` must stay bound".to_string())
+    );
+}
+
+#[test]
+fn rebinds_from_the_menu() {
+    let mut headless = Headless::new(0).unwrap();
+    headless.game.recording = Some(Replay::new(&headless.game.state));
+    // bind K to moving forward, and don't bind ESCAPE to it since it cancels
+    let down = vec![Input::Key(KeyCode::Down); 4];
+    headless.run(&[Input::Key(KeyCode::F1)]).unwrap();
+    headless.run(&down).unwrap();
+    headless.run(&[
+        Input::Key(KeyCode::Return),
+        Input::Key(KeyCode::K),
+        Input::Key(KeyCode::Return),
+        Input::Key(KeyCode::Escape),
+        Input::Key(KeyCode::Escape)
+    ]).unwrap();
+    let bindings = &headless.game.settings.bindings;
+    assert_eq!(bindings.actions(Control::Key(KeyCode::K)), [Action::MoveForward]);
+    assert_eq!(bindings.actions(Control::Key(KeyCode::Escape)), [Action::Cancel]);
+
+    // form a party with the gamepad and walk with the new key
+    headless.run(&[Input::Button(Button::South), Input::Button(Button::East)]).unwrap();
+    let start = headless.game.state.pos;
+    headless.run(&[Input::Key(KeyCode::K), SETTLE]).unwrap();
+    assert_eq!(headless.game.state.party.len(), 1);
+    assert_ne!(headless.game.state.pos, start);
+    assert!(!headless.game.gamepad);
+
+    // the keys pressed are recorded as actions, apart from those bound
+    let recording = headless.game.recording.take().unwrap();
+    assert!(recording.inputs.iter().any(|&(_, input)| input == Input::Key(KeyCode::K)));
+    assert!(recording.inputs.iter().any(|&(_, input)| input == Input::Action(Action::MoveForward)));
+    let mut playback = Headless::from_replay(&recording).unwrap();
+    playback.play(&recording).unwrap();
+    assert_eq!(playback.game.state.to_string(), headless.game.state.to_string());
+}
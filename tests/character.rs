@@ -0,0 +1,48 @@
+//! Checks the stats of characters and what modifies them.
+
+use ::blackbox::{
+    *,
+    model::*
+};
+
+/// A game with a party of one character with 50 AGI and nothing equipped.
+fn nimble() -> State {
+    let mut state = Headless::new(0).unwrap().game.state;
+    let character = state.roster.remove(0);
+    state.party.push(character);
+    state.party[0].base_agi = 50;
+    for &slot in ItemSlot::ALL {
+        state.unequip(0, slot);
+    }
+    state
+}
+
+#[test]
+fn statuses_modify_stats() {
+    let mut state = nimble();
+    assert_eq!(state.party[0].agi(), 50);
+    state.inflict(0, Status::Poison);
+    assert_eq!(state.party[0].agi(), 40);
+    state.inflict(0, Status::Paralysis);
+    assert_eq!(state.party[0].agi(), 15);
+    let sources = state.party[0].modifiers(Stat::Agi).into_iter()
+        .map(|(source, _)| source)
+        .collect::<Vec<_>>();
+    assert_eq!(sources, [ModSource::Status(Status::Poison), ModSource::Status(Status::Paralysis)]);
+    state.party[0].cure(Status::Poison);
+    assert_eq!(state.party[0].agi(), 25);
+}
+
+#[test]
+fn modifiers_of_statuses_wear_off_with_them() {
+    let mut state = nimble();
+    state.inflict(0, Status::Paralysis);
+    for _ in 1..PARALYSIS_STEPS {
+        state.party[0].step_statuses();
+    }
+    assert_eq!(state.party[0].agi(), 25);
+    state.party[0].step_statuses();
+    assert!(!state.party[0].has(Status::Paralysis));
+    assert_eq!(state.party[0].agi(), 50);
+    assert!(state.party[0].modifiers(Stat::Agi).is_empty());
+}
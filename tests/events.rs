@@ -0,0 +1,30 @@
+//! Runs the commands of events and checks what they changed.
+
+use ::blackbox::{
+    *,
+    model::*
+};
+
+#[test]
+fn adds_up_to_the_limits_of_variables() {
+    let items = load_items().unwrap();
+    let mut state = Headless::new(0).unwrap().game.state;
+    let pos = state.pos;
+    state.level_mut().events.push(Event {
+        pos,
+        trigger: Trigger::Step,
+        once: false,
+        conditions: Vec::new(),
+        commands: vec![
+            Command::Add("gold".to_string(), 10),
+            Command::Add("debt".to_string(), -10)
+        ]
+    });
+    state.set_var("gold", Value::Int(i64::MAX - 5));
+    state.set_var("debt", Value::Int(i64::MIN + 5));
+    state.run_events(&Trigger::Step, &items).unwrap();
+    assert_eq!(state.var("gold"), Some(&Value::Int(i64::MAX)));
+    assert_eq!(state.var("debt"), Some(&Value::Int(i64::MIN)));
+    state.run_events(&Trigger::Step, &items).unwrap();
+    assert_eq!(state.var("gold"), Some(&Value::Int(i64::MAX)));
+}
@@ -0,0 +1,75 @@
+//! Plays the game without a window and checks where it ends up.
+
+use ::ggez::input::keyboard::KeyCode;
+use ::std::time::Duration;
+use ::blackbox::{
+    *,
+    model::*
+};
+
+/// Long enough for any step or turn to finish.
+const SETTLE: Input = Input::Wait(Duration::from_secs(1));
+
+/// Starts a game, forms a party of the first character on the roster and
+/// enters the dungeon, keeping monsters away.
+fn explore() -> Headless {
+    let mut headless = Headless::new(0).unwrap();
+    headless.run(&[Input::Key(KeyCode::Return), Input::Key(KeyCode::Escape)]).unwrap();
+    headless.game.state.ward_steps = 1000;
+    headless
+}
+
+#[test]
+fn forms_a_party() {
+    let headless = explore();
+    let state = &headless.game.state;
+    assert_eq!(state.party.len(), 1);
+    assert_eq!(state.party[0].name, "TELLURIUS");
+    assert_eq!(state.roster.len(), 2);
+}
+
+#[test]
+fn turns_and_steps() {
+    let mut headless = explore();
+    let start = headless.game.state.pos;
+    headless.run(&[
+        Input::Key(KeyCode::Up), SETTLE,
+        Input::Key(KeyCode::Up), SETTLE,
+        Input::Key(KeyCode::Left), SETTLE,
+        Input::Key(KeyCode::Up), SETTLE
+    ]).unwrap();
+    let state = &headless.game.state;
+    assert_eq!(state.dir, Direction::East);
+    assert_eq!(state.pos, Position { x: start.x + 1, y: start.y + 2 });
+}
+
+#[test]
+fn equips_from_the_inventory() {
+    let mut headless = explore();
+    let old = headless.game.state.party[0].weapon.clone().unwrap();
+    let new = headless.game.state.inventory[0].item.clone();
+    // open the party member, then the inventory for the weapon slot
+    headless.run(&[
+        Input::Key(KeyCode::P),
+        Input::Key(KeyCode::Return),
+        Input::Key(KeyCode::Return),
+        Input::Key(KeyCode::Return)
+    ]).unwrap();
+    let state = &headless.game.state;
+    assert_eq!(state.party[0].weapon.as_ref().map(Item::name), Some(new.name()));
+    assert!(state.inventory.iter().any(|stack| stack.item.name() == old.name()));
+}
+
+#[test]
+fn reads_inputs() {
+    let inputs = parse_inputs("# comment\nkey UP\n\ntext A\nwait 0.5\n").unwrap();
+    assert_eq!(inputs, [
+        Input::Key(KeyCode::Up),
+        Input::Text('A'),
+        Input::Wait(Duration::from_millis(500))
+    ]);
+    for input in inputs {
+        assert_eq!(input.to_string().parse(), Ok(input));
+    }
+    assert_eq!(parse_inputs("key UP\nkey NOPE"), Err("line 2: unknown key `NOPE`".to_string()));
+}
@@ -0,0 +1,32 @@
+//! Uses items from the inventory on the party.
+
+use ::blackbox::{
+    *,
+    model::*
+};
+
+/// A game with the first character of the roster in the party, and the
+/// stack of potions it starts with.
+fn with_potions() -> (State, usize) {
+    let mut state = Headless::new(0).unwrap().game.state;
+    let character = state.roster.remove(0);
+    state.party.push(character);
+    let i = state.inventory.iter().position(|stack| stack.item.name() == "POTION").unwrap();
+    (state, i)
+}
+
+#[test]
+fn keeps_potions_that_have_no_effect() {
+    let (mut state, i) = with_potions();
+    let count = state.inventory[i].count;
+    state.party[0].inflict(Status::Dead, None);
+    assert_eq!(state.use_item(i, 0), ["IT HAS NO EFFECT."]);
+    assert_eq!(state.inventory[i].count, count);
+    assert_eq!(state.use_item(i, 5), ["IT HAS NO EFFECT."]);
+    assert_eq!(state.inventory[i].count, count);
+
+    state.party[0].cure(Status::Dead);
+    assert_eq!(state.use_item(i, 0).len(), 1);
+    assert_eq!(state.inventory[i].count, count - 1);
+    assert!(state.party[0].hp > 1);
+}
@@ -0,0 +1,46 @@
+//! Checks the item catalog shipped with the game and the errors of broken
+//! ones.
+
+use ::std::fs;
+use ::blackbox::model::*;
+
+#[test]
+fn reads_the_catalog() {
+    let catalog = fs::read_to_string("res/items.itm").unwrap().parse::<ItemCatalog>().unwrap();
+    let greatsword = catalog.get("GREATSWORD").unwrap();
+    assert_eq!(greatsword.slot, Some(ItemSlot::Weapon));
+    assert_eq!(greatsword.hands, 2);
+    assert_eq!(greatsword.mods, [(Stat::Atk, Modifier::Add(11)), (Stat::Agi, Modifier::Add(-1))]);
+    assert_eq!(catalog.get("BRONZE SWORD").unwrap().hands, 1);
+    assert!(catalog.get("POTION").unwrap().consumable());
+}
+
+#[test]
+fn rejects_weapons_for_no_or_many_hands() {
+    for (src, column, message) in [
+        ("item CLUB\nslot weapon\nhands 0\nend\n", 7, "items take 1 or 2 hands"),
+        ("item CLUB\nslot weapon\nhands 7\nend\n", 7, "items take 1 or 2 hands"),
+        ("item CLUB\nslot weapon\nhands two\nend\n", 7, "expected number of hands, found `two`")
+    ] {
+        let err = src.parse::<ItemCatalog>().unwrap_err();
+        assert_eq!((err.line, err.column, err.message.as_str()), (3, column, message), "{}", src);
+    }
+}
+
+#[test]
+fn reports_broken_items() {
+    let src = "item CLUB\nslot weapon\nend\n\nitem CLOAK\nslot back\nend\n";
+    let err = src.parse::<ItemCatalog>().unwrap_err();
+    assert_eq!((err.line, err.column), (6, 6));
+    assert_eq!(err.message, "unknown slot `back`");
+
+    let src = "item CLUB\nslot weapon\nend\n\nitem CLUB\nend\n";
+    let err = src.parse::<ItemCatalog>().unwrap_err();
+    assert_eq!((err.line, err.column), (5, 1));
+    assert_eq!(err.message, "duplicate item `CLUB`");
+
+    let src = "item CLUB\nslot weapon\natk +2\n";
+    let err = src.parse::<ItemCatalog>().unwrap_err();
+    assert_eq!((err.line, err.column), (1, 1));
+    assert_eq!(err.message, "`item` without `end`");
+}
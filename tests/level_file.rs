@@ -0,0 +1,95 @@
+//! Reads and writes levels, and checks where broken ones are reported.
+
+use ::std::fs;
+use ::blackbox::model::*;
+
+const LEVEL: &str = "\
+name ENTRANCE
+size 2 2
+start 0 0 south
+map
++-+-+
+|.D.|
++ +-+
+|. .|
++-+-+
+";
+
+fn error(extra: &str) -> LevelParseError {
+    format!("{}{}", LEVEL, extra).parse::<Level>().unwrap_err()
+}
+
+#[test]
+fn levels_survive_a_round_trip() {
+    for entry in fs::read_dir("res/levels").unwrap() {
+        let path = entry.unwrap().path();
+        let level = fs::read_to_string(&path).unwrap().parse::<Level>()
+            .unwrap_or_else(|err| panic!("{}: {}", path.display(), err));
+        let reparsed = level.to_string().parse::<Level>()
+            .unwrap_or_else(|err| panic!("{} printed: {}", path.display(), err));
+        assert_eq!(reparsed, level, "{}", path.display());
+    }
+}
+
+#[test]
+fn reports_unknown_commands() {
+    let err = error("frobnicate 1\n");
+    assert_eq!((err.line, err.column), (10, 1));
+    assert_eq!(err.message, "unknown directive `frobnicate`");
+    let err = error("event 0 0 step\ntext HELLO\njump high\nend\n");
+    assert_eq!((err.line, err.column), (12, 1));
+    assert_eq!(err.message, "unknown command `jump`");
+}
+
+#[test]
+fn reports_bad_positions() {
+    let err = error("fountain 1 zero\n");
+    assert_eq!((err.line, err.column), (10, 12));
+    assert_eq!(err.message, "expected y coordinate, found `zero`");
+    let err = error("fountain 5 1\n");
+    assert_eq!((err.line, err.column), (10, 10));
+    assert_eq!(err.message, "x coordinate outside the level");
+}
+
+#[test]
+fn reports_unterminated_events() {
+    let err = error("fountain 1 1\nevent 0 0 step\ntext HELLO\n");
+    assert_eq!((err.line, err.column), (11, 1));
+    assert_eq!(err.message, "`event` without `end`");
+}
+
+#[test]
+fn compares_with_negative_numbers() {
+    let level = format!("{}event 0 0 step\nif gold < -1\nadd gold -3\nend\n", LEVEL)
+        .parse::<Level>()
+        .unwrap();
+    let event = &level.events[0];
+    assert_eq!(event.conditions, [Condition { var: "gold".to_string(), test: Test::Lt(-1) }]);
+    assert_eq!(event.commands, [Command::Add("gold".to_string(), -3)]);
+    assert_eq!(level.to_string().parse::<Level>(), Ok(level));
+}
+
+#[test]
+fn keeps_quoted_text_as_text() {
+    let level = format!("{}event 0 0 step\nif answer = \"1\"\nset answer \"\"\nend\n", LEVEL)
+        .parse::<Level>()
+        .unwrap();
+    let event = &level.events[0];
+    let test = Test::Eq(Value::Str("1".to_string()));
+    assert_eq!(event.conditions, [Condition { var: "answer".to_string(), test }]);
+    assert_eq!(event.commands, [Command::Set("answer".to_string(), Value::Str(String::new()))]);
+    assert_eq!(level.to_string().parse::<Level>(), Ok(level));
+}
+
+#[test]
+fn reports_missing_and_duplicate_directives() {
+    let err = "name ENTRANCE\nsize 2 2\nstart 0 0 south\n".parse::<Level>().unwrap_err();
+    assert_eq!((err.line, err.column), (1, 1));
+    assert_eq!(err.message, "missing `map`");
+    let err = LEVEL.replace("start 0 0 south\n", "").parse::<Level>().unwrap_err();
+    assert_eq!((err.line, err.column), (1, 1));
+    assert_eq!(err.message, "missing `start`");
+    let err = error("name CELLAR\n");
+    assert_eq!((err.line, err.column), (10, 1));
+    assert_eq!(err.message, "duplicate `name`");
+}
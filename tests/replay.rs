@@ -0,0 +1,101 @@
+//! Records games without a window and plays them back.
+
+use ::ggez::input::keyboard::KeyCode;
+use ::std::{
+    fs,
+    time::Duration
+};
+use ::blackbox::{
+    *,
+    model::*
+};
+
+const SETTLE: Input = Input::Wait(Duration::from_secs(1));
+
+#[test]
+fn plays_back_what_was_recorded() {
+    let mut headless = Headless::new(7).unwrap();
+    headless.game.recording = Some(Replay::new(&headless.game.state));
+    // walk far enough for random encounters to happen, pressing on through
+    // any battle
+    let mut inputs = vec![
+        Input::Key(KeyCode::Return),
+        Input::Key(KeyCode::Down),
+        Input::Key(KeyCode::Return),
+        Input::Key(KeyCode::Escape)
+    ];
+    for i in 0..40 {
+        let turn = match i % 10 {
+            9 => KeyCode::Left,
+            _ => KeyCode::Return
+        };
+        inputs.extend([Input::Key(KeyCode::Up), SETTLE, Input::Key(turn), SETTLE]);
+    }
+    headless.run(&inputs).unwrap();
+    assert_eq!(headless.game.state.party.len(), 2);
+    let recording = headless.game.recording.take().unwrap();
+
+    let items = load_items().unwrap();
+    let replay = Replay::parse(&recording.to_string(), &items).unwrap();
+    assert_eq!(replay.updates, headless.game.updates);
+    assert_eq!(replay.inputs, recording.inputs);
+    let mut playback = Headless::from_replay(&replay).unwrap();
+    playback.play(&replay).unwrap();
+    assert_eq!(playback.game.updates, headless.game.updates);
+    assert_eq!(playback.game.state.to_string(), headless.game.state.to_string());
+}
+
+#[test]
+fn plays_back_without_the_bindings_it_was_recorded_with() {
+    let mut headless = Headless::new(7).unwrap();
+    headless.game.settings.bindings = Bindings::parse(
+        "confirm key SPACE\nforward key K\nturn-left key J\nup key I\ndown key M\n"
+    ).unwrap();
+    headless.game.recording = Some(Replay::new(&headless.game.state));
+    let mut inputs = vec![
+        Input::Key(KeyCode::Space),
+        Input::Key(KeyCode::Escape)
+    ];
+    for _ in 0..3 {
+        inputs.extend([Input::Key(KeyCode::K), SETTLE, Input::Key(KeyCode::J), SETTLE]);
+    }
+    headless.run(&inputs).unwrap();
+    let state = &headless.game.state;
+    assert_eq!(state.party.len(), 1);
+    assert_ne!(state.pos, Headless::new(7).unwrap().game.state.pos);
+
+    let recording = headless.game.recording.take().unwrap();
+    let replay = Replay::parse(&recording.to_string(), &load_items().unwrap()).unwrap();
+    let mut playback = Headless::from_replay(&replay).unwrap();
+    assert_eq!(playback.game.settings.bindings, Bindings::default());
+    assert_eq!(playback.game.settings.bindings_path, None);
+    playback.play(&replay).unwrap();
+    assert_eq!(playback.game.state.to_string(), headless.game.state.to_string());
+}
+
+/// Steps used to be lost when their animation finished.
+#[test]
+fn walks_in_a_replay() {
+    let items = load_items().unwrap();
+    let src = fs::read_to_string("tests/replays/walk.rpl").unwrap();
+    let replay = Replay::parse(&src, &items).unwrap();
+    let mut headless = Headless::from_replay(&replay).unwrap();
+    headless.play(&replay).unwrap();
+    let state = &headless.game.state;
+    assert_eq!(state.party.len(), 1);
+    assert_eq!(state.pos, Position { x: 1, y: 2 });
+    assert_eq!(state.dir, Direction::East);
+}
+
+#[test]
+fn reports_broken_replays() {
+    let items = load_items().unwrap();
+    let err = Replay::parse("blackbox-replay 1\nseed 1\nupdates 3\ninput 0 key NOPE\n", &items);
+    assert_eq!(err.unwrap_err(), SaveError::Malformed {
+        line: 4,
+        column: 9,
+        message: "unknown key `NOPE`".to_string()
+    });
+    let err = Replay::parse("blackbox-replay 3\n", &items);
+    assert_eq!(err.unwrap_err(), SaveError::IncompatibleVersion(3));
+}
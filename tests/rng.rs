@@ -0,0 +1,64 @@
+//! Checks that the dice of a game only depend on its seed.
+
+use ::blackbox::{
+    *,
+    model::*
+};
+
+#[test]
+fn streams_do_not_disturb_each_other() {
+    let mut a = Rngs::new(42);
+    let mut b = Rngs::new(42);
+    for _ in 0..100 {
+        a.combat.next_u64();
+    }
+    assert_eq!(a.loot, b.loot);
+    assert_eq!(a.encounters.next_u64(), b.encounters.next_u64());
+    assert_ne!(Rngs::new(42).combat, Rngs::new(42).loot);
+    assert_ne!(Rngs::new(42).combat, Rngs::new(43).combat);
+}
+
+#[test]
+fn saves_keep_the_seed_and_streams() {
+    let items = load_items().unwrap();
+    let mut state = Headless::new(42).unwrap().game.state;
+    state.rng.stats.next_u64();
+    let loaded = State::from_save(&state.to_string(), &items).unwrap();
+    assert_eq!(loaded.rng, state.rng);
+    assert_eq!(loaded.rng.seed, 42);
+}
+
+#[test]
+fn old_saves_split_their_generator() {
+    let items = load_items().unwrap();
+    let state = Headless::new(42).unwrap().game.state;
+    let header = format!("{} {}", SAVE_MAGIC, SAVE_VERSION);
+    let save = state.to_string().replacen(&header, &format!("{} 9", SAVE_MAGIC), 1);
+    let mut lines = save.lines()
+        .filter(|line| !line.starts_with("seed ") && !line.starts_with("rng "))
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    lines.insert(1, "rng 1234".to_string());
+    let loaded = State::from_save(&lines.join("\n"), &items).unwrap();
+    assert_eq!(loaded.rng, Rngs::new(1234));
+}
+
+/// Migrating story flags to variables used to turn the `flag` commands of
+/// events in the saved levels into `var` lines too.
+#[test]
+fn old_saves_keep_the_flag_commands_of_events() {
+    let items = load_items().unwrap();
+    let state = Headless::new(42).unwrap().game.state;
+    let header = format!("{} {}", SAVE_MAGIC, SAVE_VERSION);
+    let save = state.to_string().replacen(&header, &format!("{} 8", SAVE_MAGIC), 1);
+    assert!(save.lines().any(|line| line == "flag found_cache"));
+    let mut lines = save.lines()
+        .filter(|line| !line.starts_with("seed ") && !line.starts_with("rng "))
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    lines.insert(1, "rng 1234".to_string());
+    lines.insert(2, "flag met_ghost".to_string());
+    let loaded = State::from_save(&lines.join("\n"), &items).unwrap();
+    assert_eq!(loaded.var("met_ghost"), Some(&Value::Bool(true)));
+    assert_eq!(loaded.dungeon.floors, state.dungeon.floors);
+}
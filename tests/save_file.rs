@@ -0,0 +1,32 @@
+//! Checks that saved games load the way they were.
+
+use ::blackbox::{
+    *,
+    model::*
+};
+
+#[test]
+fn saves_keep_the_type_of_variables() {
+    let items = load_items().unwrap();
+    let mut state = Headless::new(42).unwrap().game.state;
+    let values = [
+        Value::Bool(true),
+        Value::Int(-7),
+        Value::Str("42".to_string()),
+        Value::Str("true".to_string()),
+        Value::Str(String::new()),
+        Value::Str("UP THE LADDER".to_string())
+    ];
+    for (i, value) in values.iter().enumerate() {
+        state.set_var(&format!("var_{}", i), value.clone());
+    }
+    let loaded = State::from_save(&state.to_string(), &items).unwrap();
+    for (i, value) in values.iter().enumerate() {
+        assert_eq!(loaded.var(&format!("var_{}", i)), Some(value));
+    }
+
+    // saves from before quoting read bare text as a string
+    let save = state.to_string().replace("var var_5 \"UP THE LADDER\"", "var var_5 UP THE LADDER");
+    let loaded = State::from_save(&save, &items).unwrap();
+    assert_eq!(loaded.var("var_5"), Some(&values[5]));
+}